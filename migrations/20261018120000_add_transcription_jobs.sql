@@ -0,0 +1,27 @@
+-- Persistent queue for transcription work
+CREATE TABLE transcription_jobs (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    recording_id UUID NOT NULL REFERENCES recordings(id) ON DELETE CASCADE,
+    status TEXT NOT NULL DEFAULT 'QUEUED',
+    attempts INT NOT NULL DEFAULT 0,
+    max_attempts INT NOT NULL DEFAULT 5,
+    run_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    locked_at TIMESTAMP WITH TIME ZONE,
+    last_error TEXT,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW()
+);
+
+-- At most one active job per recording
+CREATE UNIQUE INDEX idx_transcription_jobs_active
+    ON transcription_jobs(recording_id)
+    WHERE status IN ('QUEUED', 'RUNNING');
+
+-- Index for picking the next due job
+CREATE INDEX idx_transcription_jobs_due
+    ON transcription_jobs(run_at)
+    WHERE status = 'QUEUED';
+
+-- Error message for recordings whose transcription gave up
+ALTER TABLE recordings
+ADD COLUMN transcription_error TEXT;
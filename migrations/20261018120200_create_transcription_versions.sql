@@ -0,0 +1,22 @@
+-- Every revision of a recording's transcription
+CREATE TABLE transcription_versions (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    recording_id UUID NOT NULL REFERENCES recordings(id) ON DELETE CASCADE,
+    transcription_text JSONB NOT NULL,
+    -- Where a revision came from: 'model', 'manual', 'restore' or 'legacy'
+    source TEXT NOT NULL,
+    model TEXT,
+    prompt_hash TEXT,
+    restored_from UUID REFERENCES transcription_versions(id) ON DELETE SET NULL,
+    diff TEXT,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW()
+);
+
+-- Index for listing a recording's versions
+CREATE INDEX idx_transcription_versions_recording_id ON transcription_versions(recording_id);
+
+-- Current transcriptions become the latest version of their recording
+INSERT INTO transcription_versions (recording_id, transcription_text, source)
+SELECT id, transcription_text, 'legacy'
+FROM recordings
+WHERE transcription_text IS NOT NULL;
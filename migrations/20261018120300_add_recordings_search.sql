@@ -0,0 +1,10 @@
+-- Full-text search over title and transcripts, weighted title > improved > verbatim
+ALTER TABLE recordings
+ADD COLUMN search_vector tsvector GENERATED ALWAYS AS (
+    setweight(to_tsvector('english', coalesce(transcription_text->>'title', '')), 'A') ||
+    setweight(to_tsvector('english', coalesce(transcription_text->>'improved_transcript', '')), 'B') ||
+    setweight(to_tsvector('english', coalesce(transcription_text->>'transcript', '')), 'C')
+) STORED;
+
+-- Index for text search
+CREATE INDEX idx_recordings_search_vector ON recordings USING GIN (search_vector);
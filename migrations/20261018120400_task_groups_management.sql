@@ -0,0 +1,7 @@
+-- Archived groups stay attached to their recordings but are hidden from pickers
+ALTER TABLE task_groups
+ADD COLUMN archived_at TIMESTAMP WITH TIME ZONE;
+
+-- Keep ordering unique; deferrable so a reorder can swap positions inside one transaction
+ALTER TABLE task_groups
+ADD CONSTRAINT task_groups_ordering_key UNIQUE (ordering) DEFERRABLE INITIALLY IMMEDIATE;
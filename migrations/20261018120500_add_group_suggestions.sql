@@ -0,0 +1,4 @@
+-- Task group proposed by the transcription model, with its confidence (0 to 1)
+ALTER TABLE recordings
+ADD COLUMN suggested_group_id UUID REFERENCES task_groups(id) ON DELETE SET NULL,
+ADD COLUMN suggestion_confidence REAL;
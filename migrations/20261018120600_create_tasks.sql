@@ -0,0 +1,16 @@
+-- Action items extracted from recordings
+CREATE TABLE tasks (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    recording_id UUID NOT NULL REFERENCES recordings(id) ON DELETE CASCADE,
+    description TEXT NOT NULL,
+    assignee TEXT,
+    due_date DATE,
+    done_at TIMESTAMP WITH TIME ZONE,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT clock_timestamp()
+);
+
+-- Index for a recording's tasks
+CREATE INDEX idx_tasks_recording_id ON tasks(recording_id);
+
+-- Index for listing open tasks
+CREATE INDEX idx_tasks_open ON tasks(created_at) WHERE done_at IS NULL;
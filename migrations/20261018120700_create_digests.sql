@@ -0,0 +1,13 @@
+-- Generated end-of-day and end-of-week reviews
+CREATE TABLE digests (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    kind TEXT NOT NULL CHECK (kind IN ('day', 'week')),
+    period_start DATE NOT NULL,
+    period_end DATE NOT NULL,
+    content JSONB NOT NULL,
+    generated_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    UNIQUE (kind, period_start)
+);
+
+-- Index for listing the latest digests
+CREATE INDEX idx_digests_period_start ON digests(period_start DESC);
@@ -0,0 +1,5 @@
+-- Detected audio type of each recording, e.g. audio/webm;codecs=opus
+ALTER TABLE recordings ADD COLUMN mime_type TEXT NOT NULL DEFAULT 'audio/webm';
+
+-- Existing recordings were all stored as WebM; new ones always set it
+ALTER TABLE recordings ALTER COLUMN mime_type DROP DEFAULT;
@@ -0,0 +1,18 @@
+-- Resumable uploads; partial data lives in recordings/<relative_dir>/.upload-<id>.part
+CREATE TABLE upload_sessions (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    relative_dir TEXT NOT NULL,
+    recorded_at TIMESTAMP WITH TIME ZONE NOT NULL,
+    original_filename TEXT,
+    received_bytes BIGINT NOT NULL DEFAULT 0,
+    status TEXT NOT NULL DEFAULT 'OPEN' CHECK (status IN ('OPEN', 'COMPLETED')),
+    recording_id UUID REFERENCES recordings(id) ON DELETE SET NULL,
+    -- Request currently streaming a chunk; it may only advance received_bytes while it holds the claim
+    writer_id UUID,
+    -- Pushed back by every chunk; expired sessions and their partial files are purged
+    expires_at TIMESTAMP WITH TIME ZONE NOT NULL,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_upload_sessions_expires_at ON upload_sessions(expires_at);
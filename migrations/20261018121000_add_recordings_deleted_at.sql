@@ -0,0 +1,4 @@
+-- Deleted recordings stay in the trash until the purge removes them for good
+ALTER TABLE recordings ADD COLUMN deleted_at TIMESTAMPTZ;
+
+CREATE INDEX idx_recordings_deleted_at ON recordings (deleted_at) WHERE deleted_at IS NOT NULL;
@@ -0,0 +1,5 @@
+-- Size of the stored file, checked by the integrity checker; NULL for recordings stored before it was tracked
+ALTER TABLE recordings ADD COLUMN size_bytes BIGINT;
+
+-- Problem found by the last integrity check, e.g. the file is missing
+ALTER TABLE recordings ADD COLUMN integrity_error TEXT;
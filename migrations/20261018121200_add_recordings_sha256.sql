@@ -0,0 +1,5 @@
+-- Hex SHA-256 of the audio, used to recognise re-uploads; NULL for recordings stored before it was tracked
+ALTER TABLE recordings ADD COLUMN sha256 TEXT;
+
+-- At most one live recording per audio file; a copy may sit in the trash
+CREATE UNIQUE INDEX idx_recordings_sha256 ON recordings (sha256) WHERE deleted_at IS NULL;
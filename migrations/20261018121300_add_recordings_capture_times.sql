@@ -0,0 +1,4 @@
+-- When the client started and stopped capturing; NULL for recordings made before they were sent
+ALTER TABLE recordings ADD COLUMN started_at TIMESTAMPTZ;
+ALTER TABLE recordings ADD COLUMN ended_at TIMESTAMPTZ;
+ALTER TABLE recordings ADD COLUMN duration_ms BIGINT CHECK (duration_ms >= 0);
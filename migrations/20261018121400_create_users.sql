@@ -0,0 +1,43 @@
+-- Accounts; passwords are stored as Argon2 PHC strings
+CREATE TABLE users (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    username TEXT NOT NULL,
+    password_hash TEXT NOT NULL,
+    is_admin BOOLEAN NOT NULL DEFAULT FALSE,
+    -- IANA name; NULL uses APP_TIMEZONE
+    timezone TEXT,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW()
+);
+
+CREATE UNIQUE INDEX idx_users_username ON users (lower(username));
+
+-- Signed-in browsers; only the SHA-256 of the cookie token is stored
+CREATE TABLE sessions (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    token_hash TEXT NOT NULL UNIQUE,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    expires_at TIMESTAMP WITH TIME ZONE NOT NULL
+);
+
+CREATE INDEX idx_sessions_user_id ON sessions(user_id);
+
+-- Data is scoped to the user who created it; rows from before accounts have no owner until claimed
+ALTER TABLE recordings ADD COLUMN owner_id UUID REFERENCES users(id);
+ALTER TABLE task_groups ADD COLUMN owner_id UUID REFERENCES users(id);
+ALTER TABLE upload_sessions ADD COLUMN owner_id UUID REFERENCES users(id);
+ALTER TABLE digests ADD COLUMN owner_id UUID REFERENCES users(id);
+
+CREATE INDEX idx_recordings_owner_id ON recordings(owner_id, created_at);
+CREATE INDEX idx_task_groups_owner_id ON task_groups(owner_id);
+
+-- Group order, duplicate detection and digest periods are per owner
+ALTER TABLE task_groups DROP CONSTRAINT task_groups_ordering_key;
+ALTER TABLE task_groups
+ADD CONSTRAINT task_groups_ordering_key UNIQUE NULLS NOT DISTINCT (owner_id, ordering) DEFERRABLE INITIALLY IMMEDIATE;
+
+DROP INDEX idx_recordings_sha256;
+CREATE UNIQUE INDEX idx_recordings_sha256 ON recordings (owner_id, sha256) WHERE deleted_at IS NULL;
+
+ALTER TABLE digests DROP CONSTRAINT digests_kind_period_start_key;
+ALTER TABLE digests ADD CONSTRAINT digests_owner_kind_period_key UNIQUE NULLS NOT DISTINCT (owner_id, kind, period_start);
@@ -0,0 +1,9 @@
+-- Read-only links to a single recording; the link carries a signature over the id and expiry
+CREATE TABLE recording_shares (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    recording_id UUID NOT NULL REFERENCES recordings(id) ON DELETE CASCADE,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    expires_at TIMESTAMP WITH TIME ZONE NOT NULL
+);
+
+CREATE INDEX idx_recording_shares_recording_id ON recording_shares(recording_id);
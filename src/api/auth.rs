@@ -0,0 +1,404 @@
+use std::marker::PhantomData;
+use std::ops::Deref;
+use async_trait::async_trait;
+use axum::{
+    extract::{Form, FromRef, FromRequestParts, Json, Request, State},
+    http::{header, request::Parts, HeaderMap, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Redirect, Response},
+};
+use chrono::{DateTime, Utc};
+use leptos::server_fn::ServerFn;
+use sqlx::PgPool;
+use crate::api::error::ApiError;
+use crate::app::GetSharedRecording;
+use crate::models::dtos::{Account, LoginRequest, TokenScope};
+use crate::service::accounts::{authenticate, create_session, end_session, session_user, AuthUser};
+use crate::service::api_tokens::token_user;
+use crate::service::shares::SHARE_PATH;
+
+pub const SESSION_COOKIE: &str = "workflow_session";
+pub const LOGIN_PATH: &str = "/login";
+
+/// The signed-in user. Routes that take an `AuthUser` refuse API tokens; those that tokens
+/// may call take a [`TokenAuth`] instead.
+#[async_trait]
+impl<S> FromRequestParts<S> for AuthUser
+where
+    PgPool: FromRef<S>,
+    S: Send + Sync,
+{
+    type Rejection = ApiError;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        request_user(parts, &PgPool::from_ref(state), None).await
+    }
+}
+
+/// The user of a route that API tokens with the scope `S` may call, as well as signed-in users.
+pub struct TokenAuth<S>(pub AuthUser, PhantomData<S>);
+
+/// Names the token scope a route needs, as in `TokenAuth<Upload>`.
+pub trait RequiredScope {
+    const SCOPE: TokenScope;
+}
+
+pub struct Read;
+pub struct Upload;
+pub struct Edit;
+pub struct Delete;
+
+impl RequiredScope for Read {
+    const SCOPE: TokenScope = TokenScope::Read;
+}
+
+impl RequiredScope for Upload {
+    const SCOPE: TokenScope = TokenScope::Upload;
+}
+
+impl RequiredScope for Edit {
+    const SCOPE: TokenScope = TokenScope::Edit;
+}
+
+impl RequiredScope for Delete {
+    const SCOPE: TokenScope = TokenScope::Delete;
+}
+
+impl<S> Deref for TokenAuth<S> {
+    type Target = AuthUser;
+
+    fn deref(&self) -> &AuthUser {
+        &self.0
+    }
+}
+
+#[async_trait]
+impl<S, R> FromRequestParts<S> for TokenAuth<R>
+where
+    PgPool: FromRef<S>,
+    S: Send + Sync,
+    R: RequiredScope,
+{
+    type Rejection = ApiError;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        let user = request_user(parts, &PgPool::from_ref(state), Some(R::SCOPE)).await?;
+        Ok(TokenAuth(user, PhantomData))
+    }
+}
+
+// A token is only accepted where the route names a scope it has; accounts, tokens, shares,
+// webhooks and maintenance name none, so a leaked token can't reach beyond its recordings
+async fn request_user(parts: &Parts, pool: &PgPool, scope: Option<TokenScope>) -> Result<AuthUser, ApiError> {
+    // Pages have already been through `require_login`
+    if let Some(user) = parts.extensions.get::<AuthUser>() {
+        return Ok(user.clone());
+    }
+
+    // Scripts send an API token instead of the session cookie
+    if let Some(secret) = bearer_token(&parts.headers)? {
+        let user = token_user(pool, secret).await?.ok_or(ApiError::Unauthorized)?;
+        return match scope {
+            Some(scope) if user.allows(scope) => Ok(user),
+            _ => Err(ApiError::Forbidden),
+        };
+    }
+    user_from_headers(pool, &parts.headers).await?.ok_or(ApiError::Unauthorized)
+}
+
+fn bearer_token(headers: &HeaderMap) -> Result<Option<&str>, ApiError> {
+    let Some(value) = headers.get(header::AUTHORIZATION) else {
+        return Ok(None);
+    };
+    value
+        .to_str()
+        .ok()
+        .and_then(|v| v.strip_prefix("Bearer "))
+        .map(|token| Some(token.trim()))
+        .ok_or(ApiError::Unauthorized)
+}
+
+/// The user whose session cookie came with a request.
+pub async fn user_from_headers(pool: &PgPool, headers: &HeaderMap) -> Result<Option<AuthUser>, sqlx::Error> {
+    match session_token(headers) {
+        Some(token) => session_user(pool, &token).await,
+        None => Ok(None),
+    }
+}
+
+// Middleware for the pages: visitors without a valid session are sent to the sign-in page
+pub async fn require_login(State(pool): State<PgPool>, mut request: Request, next: Next) -> Response {
+    if is_public(request.uri().path()) {
+        return next.run(request).await;
+    }
+    let user = match user_from_headers(&pool, request.headers()).await {
+        Ok(user) => user,
+        Err(e) => return ApiError::from(e).into_response(),
+    };
+
+    match (user, request.uri().path() == LOGIN_PATH) {
+        (Some(_), true) => Redirect::to("/").into_response(),
+        (None, true) => next.run(request).await,
+        (Some(user), false) => {
+            request.extensions_mut().insert(user);
+            next.run(request).await
+        }
+        (None, false) => Redirect::to(LOGIN_PATH).into_response(),
+    }
+}
+
+// Shared recordings are public, the link itself is the credential. The share page loads its
+// data through a server function, which is routed alongside the pages.
+fn is_public(path: &str) -> bool {
+    path.starts_with(SHARE_PATH) || path == <GetSharedRecording as ServerFn>::PATH
+}
+
+// Handler to sign in from an API client; the session cookie comes back with the account
+pub async fn login(
+    State(pool): State<PgPool>,
+    Json(payload): Json<LoginRequest>
+) -> Result<impl IntoResponse, ApiError> {
+    let (user, cookie) = login_inner(&pool, &payload).await?;
+    Ok(([(header::SET_COOKIE, cookie)], Json(user.account())))
+}
+
+// Handler for the sign-in form
+pub async fn login_form(
+    State(pool): State<PgPool>,
+    Form(payload): Form<LoginRequest>
+) -> Result<Response, ApiError> {
+    match login_inner(&pool, &payload).await {
+        Ok((_, cookie)) => Ok(([(header::SET_COOKIE, cookie)], Redirect::to("/")).into_response()),
+        Err(ApiError::Unauthorized) => Ok(Redirect::to(&format!("{}?error=1", LOGIN_PATH)).into_response()),
+        Err(e) => Err(e),
+    }
+}
+
+async fn login_inner(pool: &PgPool, payload: &LoginRequest) -> Result<(AuthUser, String), ApiError> {
+    let user_id = authenticate(pool, &payload.username, &payload.password)
+        .await?
+        .ok_or(ApiError::Unauthorized)?;
+    let (token, expires_at) = create_session(pool, user_id).await?;
+    let user = session_user(pool, &token).await?.ok_or(ApiError::Unauthorized)?;
+    Ok((user, session_cookie(&token, expires_at)))
+}
+
+// Handler to sign out from an API client
+pub async fn logout(
+    State(pool): State<PgPool>,
+    headers: HeaderMap
+) -> Result<impl IntoResponse, ApiError> {
+    logout_inner(&pool, &headers).await?;
+    Ok((StatusCode::NO_CONTENT, [(header::SET_COOKIE, expired_cookie())]))
+}
+
+// Handler for the sign-out button
+pub async fn logout_form(
+    State(pool): State<PgPool>,
+    headers: HeaderMap
+) -> Result<impl IntoResponse, ApiError> {
+    logout_inner(&pool, &headers).await?;
+    Ok(([(header::SET_COOKIE, expired_cookie())], Redirect::to(LOGIN_PATH)))
+}
+
+async fn logout_inner(pool: &PgPool, headers: &HeaderMap) -> Result<(), ApiError> {
+    if let Some(token) = session_token(headers) {
+        end_session(pool, &token).await?;
+    }
+    Ok(())
+}
+
+// Handler to return the signed-in user
+pub async fn current_account(user: AuthUser) -> Json<Account> {
+    Json(user.account())
+}
+
+fn session_token(headers: &HeaderMap) -> Option<String> {
+    headers
+        .get_all(header::COOKIE)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(';'))
+        .filter_map(|pair| pair.trim().split_once('='))
+        .find(|(name, _)| *name == SESSION_COOKIE)
+        .map(|(_, token)| token.to_string())
+}
+
+fn session_cookie(token: &str, expires_at: DateTime<Utc>) -> String {
+    let max_age = (expires_at - Utc::now()).num_seconds().max(0);
+    format!("{}={}; Max-Age={}{}", SESSION_COOKIE, token, max_age, cookie_attributes())
+}
+
+fn expired_cookie() -> String {
+    format!("{}=; Max-Age=0{}", SESSION_COOKIE, cookie_attributes())
+}
+
+// `SESSION_COOKIE_SECURE=true` keeps the cookie off plain HTTP once the app is served over HTTPS
+fn cookie_attributes() -> &'static str {
+    if std::env::var("SESSION_COOKIE_SECURE").is_ok_and(|v| v.eq_ignore_ascii_case("true")) {
+        "; Path=/; HttpOnly; SameSite=Lax; Secure"
+    } else {
+        "; Path=/; HttpOnly; SameSite=Lax"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{body::Body, extract::FromRef, routing::{get, post, put}, Router};
+    use tower::ServiceExt;
+    use uuid::Uuid;
+    use crate::api::groups::{create_group, get_groups, reorder_groups};
+    use crate::api::shares::post_share;
+    use crate::api::tokens::{get_tokens, post_token};
+    use crate::api::uploads::get_upload;
+    use crate::api::versions::restore_version;
+    use crate::api::webhooks::post_webhook;
+    use crate::models::dtos::CreateTokenRequest;
+    use crate::service::accounts::{create_user, NewUser};
+    use crate::service::api_tokens::create_token;
+    use crate::service::events::EventBus;
+    use crate::service::shares::create_share;
+
+    #[derive(Clone, FromRef)]
+    struct TestState {
+        pool: PgPool,
+        events: EventBus,
+    }
+
+    fn app(pool: PgPool) -> Router {
+        Router::new()
+            .route("/auth/me", get(current_account))
+            .route("/auth/tokens", get(get_tokens).post(post_token))
+            .route("/uploads/:id", get(get_upload))
+            .route("/groups", get(get_groups).post(create_group))
+            .route("/groups/order", put(reorder_groups))
+            .route("/recordings/:id/shares", post(post_share))
+            .route("/recordings/:id/versions/:version_id/restore", post(restore_version))
+            .route("/webhooks", post(post_webhook))
+            .with_state(TestState { pool, events: EventBus::new() })
+    }
+
+    async fn token(pool: &PgPool, scopes: &[TokenScope]) -> String {
+        let username = format!("user-{}", Uuid::new_v4().simple());
+        let user = NewUser { username: &username, password: "correct horse", is_admin: true, timezone: None };
+        let user_id = create_user(pool, &user).await.unwrap();
+        let request = CreateTokenRequest { name: "script".to_string(), scopes: scopes.to_vec() };
+        create_token(pool, user_id, &request).await.unwrap().secret
+    }
+
+    async fn status(pool: &PgPool, secret: &str, method: &str, uri: &str, body: &str) -> StatusCode {
+        let request = Request::builder()
+            .method(method)
+            .uri(uri)
+            .header(header::AUTHORIZATION, format!("Bearer {}", secret))
+            .header(header::CONTENT_TYPE, "application/json")
+            .body(Body::from(body.to_string()))
+            .unwrap();
+        app(pool.clone()).oneshot(request).await.unwrap().status()
+    }
+
+    #[sqlx::test]
+    async fn upload_tokens_are_refused_outside_uploads(pool: PgPool) {
+        let secret = token(&pool, &[TokenScope::Upload]).await;
+        let id = Uuid::new_v4();
+        let refused = [
+            ("GET", "/auth/me".to_string(), ""),
+            ("GET", "/auth/tokens".to_string(), ""),
+            ("POST", "/auth/tokens".to_string(), r#"{"name": "more", "scopes": ["read", "upload", "edit", "delete"]}"#),
+            ("POST", "/webhooks".to_string(), r#"{"url": "https://example.com/hook", "events": ["uploaded"]}"#),
+            ("POST", format!("/recordings/{}/shares", id), "{}"),
+            ("POST", format!("/recordings/{}/versions/{}/restore", id, Uuid::new_v4()), ""),
+            ("POST", "/groups".to_string(), r#"{"name": "Mine now"}"#),
+            ("PUT", "/groups/order".to_string(), r#"{"ids": []}"#),
+            ("GET", "/groups".to_string(), ""),
+        ];
+        for (method, uri, body) in refused {
+            assert_eq!(status(&pool, &secret, method, &uri, body).await, StatusCode::FORBIDDEN, "{} {}", method, uri);
+        }
+
+        // Past the scope check, the session simply doesn't exist
+        assert_eq!(status(&pool, &secret, "GET", &format!("/uploads/{}", id), "").await, StatusCode::NOT_FOUND);
+    }
+
+    #[sqlx::test]
+    async fn tokens_reach_the_routes_their_scopes_name(pool: PgPool) {
+        let secret = token(&pool, &[TokenScope::Read, TokenScope::Edit]).await;
+        assert_eq!(status(&pool, &secret, "GET", "/groups", "").await, StatusCode::OK);
+        assert_eq!(status(&pool, &secret, "POST", "/groups", r#"{"name": "Errands"}"#).await, StatusCode::CREATED);
+        assert_eq!(status(&pool, &secret, "GET", &format!("/uploads/{}", Uuid::new_v4()), "").await, StatusCode::FORBIDDEN);
+    }
+
+    #[sqlx::test]
+    async fn tokens_with_every_scope_still_cannot_manage_the_account(pool: PgPool) {
+        let secret = token(&pool, &TokenScope::ALL).await;
+        assert_eq!(status(&pool, &secret, "GET", "/auth/me", "").await, StatusCode::FORBIDDEN);
+        assert_eq!(status(&pool, &secret, "GET", "/auth/tokens", "").await, StatusCode::FORBIDDEN);
+        assert_eq!(status(&pool, &secret, "POST", "/webhooks", r#"{"url": "https://example.com/hook", "events": ["uploaded"]}"#).await, StatusCode::FORBIDDEN);
+    }
+
+    // The pages router as `main` builds it, with the share page's server function
+    fn pages(pool: PgPool) -> Router {
+        let context_pool = pool.clone();
+        Router::new()
+            .route("/", get(|| async { "home" }))
+            .route(<GetSharedRecording as ServerFn>::PATH, post(move |request: Request| {
+                let pool = context_pool.clone();
+                leptos_axum::handle_server_fns_with_context(move || leptos::provide_context(pool.clone()), request)
+            }))
+            .route_layer(axum::middleware::from_fn_with_state(pool, require_login))
+    }
+
+    async fn fetch_shared(pool: &PgPool, token: &str) -> (StatusCode, String) {
+        let request = Request::builder()
+            .method("POST")
+            .uri(<GetSharedRecording as ServerFn>::PATH)
+            .header(header::CONTENT_TYPE, "application/x-www-form-urlencoded")
+            .body(Body::from(format!("token={}", token)))
+            .unwrap();
+        let response = pages(pool.clone()).oneshot(request).await.unwrap();
+        let status = response.status();
+        let body = http_body_util::BodyExt::collect(response.into_body()).await.unwrap().to_bytes();
+        (status, String::from_utf8_lossy(&body).into_owned())
+    }
+
+    #[sqlx::test]
+    async fn anonymous_visitors_can_load_a_shared_recording(pool: PgPool) {
+        let user = NewUser { username: "alice", password: "correct horse", is_admin: false, timezone: None };
+        let owner_id = create_user(&pool, &user).await.unwrap();
+        let recording_id = sqlx::query_scalar!(
+            r#"
+            INSERT INTO recordings (filename, file_path, mime_type, owner_id, transcription_status, transcription_text)
+            VALUES ('a.webm', '2026/10/18/a.webm', 'audio/webm', $1, 'COMPLETED', '{"title": "Standup notes"}')
+            RETURNING id
+            "#,
+            owner_id
+        )
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+        let share = create_share(&pool, owner_id, recording_id, chrono::Duration::days(7)).await.unwrap();
+        let token = share.url.strip_prefix(SHARE_PATH).unwrap();
+
+        let (status, body) = fetch_shared(&pool, token).await;
+        assert_eq!(status, StatusCode::OK);
+        assert!(body.contains("Standup notes"), "{}", body);
+
+        // Bad links are answered, just without a recording
+        let (status, body) = fetch_shared(&pool, "not-a-token").await;
+        assert_eq!((status, body.as_str()), (StatusCode::OK, "null"));
+    }
+
+    #[sqlx::test]
+    async fn anonymous_visitors_are_sent_to_sign_in_elsewhere(pool: PgPool) {
+        let request = Request::builder().uri("/").body(Body::empty()).unwrap();
+        let response = pages(pool).oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::SEE_OTHER);
+        assert_eq!(response.headers()[header::LOCATION], LOGIN_PATH);
+    }
+
+    #[sqlx::test]
+    async fn unknown_tokens_are_unauthorized(pool: PgPool) {
+        assert_eq!(status(&pool, "wf_not-a-token", "GET", "/groups", "").await, StatusCode::UNAUTHORIZED);
+    }
+}
@@ -0,0 +1,126 @@
+use axum::{
+    extract::{Json, Path, Query, State},
+    response::IntoResponse,
+    http::header,
+};
+use chrono::{DateTime, NaiveDate, Utc};
+use sqlx::PgPool;
+use uuid::Uuid;
+use crate::api::auth::{Edit, Read, TokenAuth};
+use crate::api::error::ApiError;
+use crate::models::dtos::{Digest, DigestListQuery, GenerateDigestRequest};
+use crate::service::accounts::AuthUser;
+use crate::service::digest::{generate_digest, parse_kind, render_markdown};
+use crate::timezone::today;
+
+const DEFAULT_LIMIT: i64 = 30;
+const MAX_LIMIT: i64 = 365;
+
+// Handler to list stored digests, newest period first
+pub async fn list_digests(
+    State(pool): State<PgPool>,
+    user: TokenAuth<Read>,
+    Query(query): Query<DigestListQuery>
+) -> Result<Json<Vec<Digest>>, ApiError> {
+    Ok(Json(list_digests_inner(pool, user.id, query.kind, query.limit).await?))
+}
+
+pub async fn list_digests_inner(pool: PgPool, owner_id: Uuid, kind: Option<String>, limit: Option<i64>) -> Result<Vec<Digest>, ApiError> {
+    if let Some(kind) = &kind {
+        parse_kind(kind).ok_or_else(|| ApiError::BadRequest("kind must be day or week".to_string()))?;
+    }
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).clamp(1, MAX_LIMIT);
+
+    let rows = sqlx::query!(
+        r#"
+        SELECT id, kind, period_start, period_end, content, generated_at
+        FROM digests
+        WHERE owner_id = $3 AND ($1::text IS NULL OR kind = $1)
+        ORDER BY period_start DESC, kind ASC
+        LIMIT $2
+        "#,
+        kind,
+        limit,
+        owner_id
+    )
+    .fetch_all(&pool)
+    .await?;
+
+    rows.into_iter()
+        .map(|row| to_digest(row.id, row.kind, row.period_start, row.period_end, row.content, row.generated_at))
+        .collect()
+}
+
+// Handler to (re)generate the digest of a day or week
+pub async fn create_digest(
+    State(pool): State<PgPool>,
+    user: TokenAuth<Edit>,
+    Json(payload): Json<GenerateDigestRequest>
+) -> Result<Json<Digest>, ApiError> {
+    Ok(Json(generate_digest_inner(pool, &user, payload).await?))
+}
+
+pub async fn generate_digest_inner(pool: PgPool, user: &AuthUser, request: GenerateDigestRequest) -> Result<Digest, ApiError> {
+    let span = parse_kind(&request.kind)
+        .ok_or_else(|| ApiError::BadRequest("kind must be day or week".to_string()))?;
+    let tz = user.timezone;
+    let date = match request.date.as_deref().filter(|d| !d.is_empty()) {
+        Some(date) => NaiveDate::parse_from_str(date, "%Y-%m-%d")
+            .map_err(|_| ApiError::BadRequest(format!("Invalid date {}, expected YYYY-MM-DD", date)))?,
+        None => today(tz),
+    };
+
+    Ok(generate_digest(&pool, user.id, span, date, tz).await?)
+}
+
+// Handler to fetch one digest
+pub async fn get_digest(
+    State(pool): State<PgPool>,
+    user: TokenAuth<Read>,
+    Path(id): Path<Uuid>
+) -> Result<Json<Digest>, ApiError> {
+    Ok(Json(get_digest_inner(pool, user.id, id).await?))
+}
+
+pub async fn get_digest_inner(pool: PgPool, owner_id: Uuid, id: Uuid) -> Result<Digest, ApiError> {
+    let row = sqlx::query!(
+        "SELECT id, kind, period_start, period_end, content, generated_at FROM digests WHERE id = $1 AND owner_id = $2",
+        id,
+        owner_id
+    )
+    .fetch_one(&pool)
+    .await?;
+
+    to_digest(row.id, row.kind, row.period_start, row.period_end, row.content, row.generated_at)
+}
+
+// Handler to download a digest as Markdown
+pub async fn export_digest(
+    State(pool): State<PgPool>,
+    user: TokenAuth<Read>,
+    Path(id): Path<Uuid>
+) -> Result<impl IntoResponse, ApiError> {
+    let digest = get_digest_inner(pool, user.id, id).await?;
+    let filename = format!("digest-{}-{}.md", digest.kind, digest.period_start);
+
+    Ok((
+        [
+            (header::CONTENT_TYPE, "text/markdown; charset=utf-8".to_string()),
+            (header::CONTENT_DISPOSITION, format!("attachment; filename=\"{}\"", filename)),
+        ],
+        render_markdown(&digest, user.timezone),
+    ))
+}
+
+fn to_digest(
+    id: Uuid,
+    kind: String,
+    period_start: NaiveDate,
+    period_end: NaiveDate,
+    content: serde_json::Value,
+    generated_at: DateTime<Utc>
+) -> Result<Digest, ApiError> {
+    let content = serde_json::from_value(content)
+        .map_err(|e| ApiError::Database(sqlx::Error::Decode(Box::new(e))))?;
+    Ok(Digest { id, kind, period_start, period_end, content, generated_at })
+}
@@ -0,0 +1,98 @@
+use std::fmt;
+use axum::{
+    response::{IntoResponse, Response},
+    http::StatusCode,
+};
+use crate::service::accounts::AccountError;
+use crate::service::storage::StorageError;
+
+/// Errors returned by the REST handlers, mapped to HTTP status codes.
+#[derive(Debug)]
+pub enum ApiError {
+    BadRequest(String),
+    Unauthorized,
+    Forbidden,
+    NotFound,
+    Conflict(String),
+    PayloadTooLarge(u64),
+    UnsupportedMediaType(String),
+    Database(sqlx::Error),
+    Io(std::io::Error),
+    Storage(StorageError),
+}
+
+impl fmt::Display for ApiError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ApiError::BadRequest(message) => write!(f, "{}", message),
+            ApiError::Unauthorized => write!(f, "Sign in required"),
+            ApiError::Forbidden => write!(f, "Not allowed"),
+            ApiError::NotFound => write!(f, "Not found"),
+            ApiError::Conflict(message) => write!(f, "{}", message),
+            ApiError::PayloadTooLarge(limit) => write!(f, "Upload exceeds the limit of {} bytes", limit),
+            ApiError::UnsupportedMediaType(message) => write!(f, "{}", message),
+            ApiError::Database(e) => write!(f, "Database error: {}", e),
+            ApiError::Io(e) => write!(f, "I/O error: {}", e),
+            ApiError::Storage(e) => write!(f, "Storage error: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for ApiError {}
+
+impl From<sqlx::Error> for ApiError {
+    fn from(e: sqlx::Error) -> Self {
+        match e {
+            sqlx::Error::RowNotFound => ApiError::NotFound,
+            e => ApiError::Database(e),
+        }
+    }
+}
+
+impl From<AccountError> for ApiError {
+    fn from(e: AccountError) -> Self {
+        match e {
+            AccountError::Invalid(message) => ApiError::BadRequest(message),
+            AccountError::UsernameTaken => ApiError::Conflict(e.to_string()),
+            AccountError::Database(e) => e.into(),
+        }
+    }
+}
+
+impl From<std::io::Error> for ApiError {
+    fn from(e: std::io::Error) -> Self {
+        ApiError::Io(e)
+    }
+}
+
+impl From<StorageError> for ApiError {
+    fn from(e: StorageError) -> Self {
+        ApiError::Storage(e)
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        match self {
+            ApiError::BadRequest(message) => (StatusCode::BAD_REQUEST, message).into_response(),
+            ApiError::Unauthorized => (StatusCode::UNAUTHORIZED, self.to_string()).into_response(),
+            ApiError::Forbidden => (StatusCode::FORBIDDEN, self.to_string()).into_response(),
+            ApiError::NotFound => StatusCode::NOT_FOUND.into_response(),
+            ApiError::Conflict(message) => (StatusCode::CONFLICT, message).into_response(),
+            ApiError::PayloadTooLarge(_) => (StatusCode::PAYLOAD_TOO_LARGE, self.to_string()).into_response(),
+            ApiError::UnsupportedMediaType(message) => (StatusCode::UNSUPPORTED_MEDIA_TYPE, message).into_response(),
+            ApiError::Database(e) => {
+                eprintln!("Database error: {}", e);
+                StatusCode::INTERNAL_SERVER_ERROR.into_response()
+            }
+            ApiError::Io(e) => {
+                eprintln!("I/O error: {}", e);
+                StatusCode::INTERNAL_SERVER_ERROR.into_response()
+            }
+            ApiError::Storage(e) => {
+                eprintln!("Storage error: {}", e);
+                StatusCode::INTERNAL_SERVER_ERROR.into_response()
+            }
+        }
+    }
+}
@@ -0,0 +1,31 @@
+use axum::{
+    extract::State,
+    response::sse::{Event, KeepAlive, Sse},
+};
+use futures_util::stream::{self, Stream};
+use tokio::sync::broadcast::error::RecvError;
+use crate::api::auth::{Read, TokenAuth};
+use crate::models::dtos::RecordingEvent;
+use crate::service::events::EventBus;
+
+// Handler for live updates: a `recording` event with a JSON `RecordingEvent` whenever one of
+// the user's recordings is uploaded, changes status, or is deleted or restored
+pub async fn recording_events(
+    State(events): State<EventBus>,
+    user: TokenAuth<Read>
+) -> Sse<impl Stream<Item = Result<Event, axum::Error>>> {
+    let owner_id = user.id;
+    let stream = stream::unfold(events.subscribe(), move |mut receiver| async move {
+        loop {
+            let event = match receiver.recv().await {
+                Ok(owned) if owned.owner_id == owner_id => owned.event,
+                Ok(_) => continue,
+                // Missed changes can't be replayed, so the page reloads its list instead
+                Err(RecvError::Lagged(_)) => RecordingEvent::Resync,
+                Err(RecvError::Closed) => return None,
+            };
+            return Some((Event::default().event("recording").json_data(event), receiver));
+        }
+    });
+    Sse::new(stream).keep_alive(KeepAlive::default())
+}
@@ -0,0 +1,164 @@
+use std::time::Duration;
+use axum::{
+    body::Body,
+    extract::{Path, State},
+    http::{header, HeaderMap, StatusCode},
+    response::{IntoResponse, Redirect, Response},
+};
+use sqlx::PgPool;
+use crate::api::auth::{Read, TokenAuth};
+use crate::api::error::ApiError;
+use crate::service::storage::{validate_key, ByteRange, SharedStorage, Storage};
+use crate::service::uploads::AudioFormat;
+
+// Presigned links only need to outlive the request that follows the redirect
+const PRESIGN_EXPIRY: Duration = Duration::from_secs(15 * 60);
+
+// Handler to serve one of the user's recordings from storage, redirecting when the backend can
+// hand out a direct link
+pub async fn serve_file(
+    State(pool): State<PgPool>,
+    State(storage): State<SharedStorage>,
+    user: TokenAuth<Read>,
+    Path(key): Path<String>,
+    headers: HeaderMap
+) -> Result<Response, ApiError> {
+    validate_key(&key).map_err(|_| ApiError::NotFound)?;
+
+    // Other users' files look the same as missing ones
+    sqlx::query!(
+        "SELECT id FROM recordings WHERE file_path = $1 AND owner_id = $2",
+        key,
+        user.id
+    )
+    .fetch_optional(&pool)
+    .await?
+    .ok_or(ApiError::NotFound)?;
+
+    if let Some(url) = storage.presign(&key, PRESIGN_EXPIRY).await? {
+        return Ok(Redirect::temporary(&url).into_response());
+    }
+    stream_file(storage.as_ref(), &key, &headers).await
+}
+
+/// Streams a stored recording through the server, honouring a `Range` header so players can seek.
+pub async fn stream_file(storage: &dyn Storage, key: &str, headers: &HeaderMap) -> Result<Response, ApiError> {
+    let range = headers
+        .get(header::RANGE)
+        .and_then(|v| v.to_str().ok())
+        .and_then(parse_range);
+
+    let object = storage.get_range(key, range).await?.ok_or(ApiError::NotFound)?;
+
+    if range.is_some() && object.start >= object.total_size {
+        return Ok((
+            StatusCode::RANGE_NOT_SATISFIABLE,
+            [(header::CONTENT_RANGE, format!("bytes */{}", object.total_size))],
+        ).into_response());
+    }
+
+    let content_type = std::path::Path::new(key)
+        .extension()
+        .and_then(|e| e.to_str())
+        .and_then(AudioFormat::from_extension)
+        .map(|f| f.essence())
+        .unwrap_or("application/octet-stream");
+    let length = if object.total_size == 0 { 0 } else { object.end - object.start + 1 };
+
+    let mut response = Response::builder()
+        .header(header::CONTENT_TYPE, content_type)
+        .header(header::CONTENT_LENGTH, length)
+        .header(header::ACCEPT_RANGES, "bytes");
+    response = match range {
+        Some(_) => response
+            .status(StatusCode::PARTIAL_CONTENT)
+            .header(header::CONTENT_RANGE, format!("bytes {}-{}/{}", object.start, object.end, object.total_size)),
+        None => response.status(StatusCode::OK),
+    };
+
+    response
+        .body(Body::from_stream(object.body))
+        .map_err(|e| ApiError::Storage(e.into()))
+}
+
+// Single `bytes=start-[end]` ranges; anything else is answered with the whole file
+fn parse_range(value: &str) -> Option<ByteRange> {
+    let (start, end) = value.strip_prefix("bytes=")?.split_once('-')?;
+    if end.contains(',') {
+        return None;
+    }
+    let start = start.trim().parse().ok()?;
+    let end = match end.trim() {
+        "" => None,
+        end => Some(end.parse().ok()?),
+    };
+    if end.is_some_and(|end| end < start) {
+        return None;
+    }
+    Some(ByteRange { start, end })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use http_body_util::BodyExt;
+    use crate::service::storage::local::LocalStorage;
+
+    fn range(start: u64, end: Option<u64>) -> Option<ByteRange> {
+        Some(ByteRange { start, end })
+    }
+
+    #[test]
+    fn parses_single_ranges() {
+        assert_eq!(parse_range("bytes=0-9"), range(0, Some(9)));
+        assert_eq!(parse_range("bytes=100-"), range(100, None));
+        assert_eq!(parse_range("bytes= 5 - 5 "), range(5, Some(5)));
+    }
+
+    #[test]
+    fn ignores_ranges_it_does_not_serve() {
+        for value in ["bytes=-500", "bytes=0-9,20-29", "bytes=9-0", "items=0-9", "bytes=a-b", "bytes=5"] {
+            assert_eq!(parse_range(value), None, "parsed {:?}", value);
+        }
+    }
+
+    async fn request(storage: &LocalStorage, value: Option<&str>) -> (StatusCode, HeaderMap, Vec<u8>) {
+        let mut headers = HeaderMap::new();
+        if let Some(value) = value {
+            headers.insert(header::RANGE, value.parse().unwrap());
+        }
+        let response = stream_file(storage, "2026/10/18/a.webm", &headers).await.unwrap();
+        let (parts, body) = response.into_parts();
+        (parts.status, parts.headers, body.collect().await.unwrap().to_bytes().to_vec())
+    }
+
+    #[tokio::test]
+    async fn serves_whole_files_and_ranges() {
+        let root = std::env::temp_dir().join(format!("workflow-files-{}", uuid::Uuid::new_v4()));
+        tokio::fs::create_dir_all(root.join("2026/10/18")).await.unwrap();
+        tokio::fs::write(root.join("2026/10/18/a.webm"), b"0123456789").await.unwrap();
+        let storage = LocalStorage::new(&root);
+
+        let (status, headers, body) = request(&storage, None).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(headers[header::CONTENT_TYPE], "audio/webm");
+        assert_eq!(body, b"0123456789");
+
+        let (status, headers, body) = request(&storage, Some("bytes=2-4")).await;
+        assert_eq!(status, StatusCode::PARTIAL_CONTENT);
+        assert_eq!(headers[header::CONTENT_RANGE], "bytes 2-4/10");
+        assert_eq!(headers[header::CONTENT_LENGTH], "3");
+        assert_eq!(body, b"234");
+
+        let (status, headers, body) = request(&storage, Some("bytes=7-")).await;
+        assert_eq!(status, StatusCode::PARTIAL_CONTENT);
+        assert_eq!(headers[header::CONTENT_RANGE], "bytes 7-9/10");
+        assert_eq!(body, b"789");
+
+        let (status, headers, _) = request(&storage, Some("bytes=10-")).await;
+        assert_eq!(status, StatusCode::RANGE_NOT_SATISFIABLE);
+        assert_eq!(headers[header::CONTENT_RANGE], "bytes */10");
+
+        tokio::fs::remove_dir_all(&root).await.unwrap();
+    }
+}
@@ -1,14 +1,27 @@
 use axum::{
-    extract::{State},
+    extract::{Query, Json, State, Path},
     response::{IntoResponse, Json as AxumJson},
     http::StatusCode,
 };
 use sqlx::PgPool;
-use crate::models::dtos::TaskGroup;
+use uuid::Uuid;
+use crate::api::auth::{Delete, Edit, Read, TokenAuth};
+use crate::api::error::ApiError;
+use crate::service::events::EventBus;
+use crate::service::grouping::list_groups;
+use crate::service::webhooks;
+use crate::models::dtos::{
+    TaskGroup, WebhookEvent, GroupListQuery, CreateGroupRequest, UpdateGroupRequest,
+    ReorderGroupsRequest, DeleteGroupQuery
+};
 
 // Handler to get task groups
-pub async fn get_groups(State(pool): State<PgPool>) -> impl IntoResponse {
-    match get_groups_inner(pool).await {
+pub async fn get_groups(
+    State(pool): State<PgPool>,
+    user: TokenAuth<Read>,
+    Query(query): Query<GroupListQuery>
+) -> impl IntoResponse {
+    match get_groups_inner(pool, user.id, query.include_archived.unwrap_or(false)).await {
         Ok(groups) => AxumJson(groups).into_response(),
         Err(e) => {
             eprintln!("Database error: {}", e);
@@ -17,11 +30,231 @@ pub async fn get_groups(State(pool): State<PgPool>) -> impl IntoResponse {
     }
 }
 
-pub async fn get_groups_inner(pool: PgPool) -> Result<Vec<TaskGroup>, sqlx::Error> {
-    sqlx::query_as!(
+pub async fn get_groups_inner(pool: PgPool, owner_id: Uuid, include_archived: bool) -> Result<Vec<TaskGroup>, sqlx::Error> {
+    list_groups(&pool, owner_id, include_archived).await
+}
+
+// Handler to create a task group at the end of the list
+pub async fn create_group(
+    State(pool): State<PgPool>,
+    user: TokenAuth<Edit>,
+    Json(payload): Json<CreateGroupRequest>
+) -> Result<impl IntoResponse, ApiError> {
+    let group = create_group_inner(pool, user.id, payload.name, payload.description).await?;
+    Ok((StatusCode::CREATED, AxumJson(group)))
+}
+
+pub async fn create_group_inner(pool: PgPool, owner_id: Uuid, name: String, description: Option<String>) -> Result<TaskGroup, ApiError> {
+    let name = validate_name(&name)?;
+    let description = description.map(|d| d.trim().to_string()).filter(|d| !d.is_empty());
+
+    let mut tx = pool.begin().await?;
+
+    // Serialize concurrent creates by the same user so they don't pick the same position. The
+    // owner's row is locked rather than their groups, which would lock nothing for a first group.
+    sqlx::query!("SELECT id FROM users WHERE id = $1 FOR NO KEY UPDATE", owner_id)
+        .fetch_optional(&mut *tx)
+        .await?;
+
+    let group = sqlx::query_as!(
+        TaskGroup,
+        r#"
+        INSERT INTO task_groups (name, description, ordering, owner_id)
+        VALUES ($1, $2, (SELECT COALESCE(MAX(ordering), 0) + 1 FROM task_groups WHERE owner_id = $3), $3)
+        RETURNING id, name, description, ordering, archived_at IS NOT NULL as "archived!"
+        "#,
+        name,
+        description,
+        owner_id
+    )
+    .fetch_one(&mut *tx)
+    .await?;
+
+    tx.commit().await?;
+    Ok(group)
+}
+
+// Handler to rename or describe a task group
+pub async fn update_group(
+    State(pool): State<PgPool>,
+    user: TokenAuth<Edit>,
+    Path(id): Path<Uuid>,
+    Json(payload): Json<UpdateGroupRequest>
+) -> Result<AxumJson<TaskGroup>, ApiError> {
+    Ok(AxumJson(update_group_inner(pool, user.id, id, payload.name, payload.description).await?))
+}
+
+pub async fn update_group_inner(
+    pool: PgPool,
+    owner_id: Uuid,
+    id: Uuid,
+    name: Option<String>,
+    description: Option<String>
+) -> Result<TaskGroup, ApiError> {
+    let name = name.as_deref().map(validate_name).transpose()?;
+    let clear_description = description.as_deref().is_some_and(|d| d.trim().is_empty());
+    let description = description.map(|d| d.trim().to_string()).filter(|d| !d.is_empty());
+
+    let group = sqlx::query_as!(
+        TaskGroup,
+        r#"
+        UPDATE task_groups
+        SET name = COALESCE($1, name),
+            description = CASE WHEN $2 THEN NULL ELSE COALESCE($3, description) END
+        WHERE id = $4 AND owner_id = $5
+        RETURNING id, name, description, ordering, archived_at IS NOT NULL as "archived!"
+        "#,
+        name,
+        clear_description,
+        description,
+        id,
+        owner_id
+    )
+    .fetch_one(&pool)
+    .await?;
+
+    Ok(group)
+}
+
+// Handler to archive a task group
+pub async fn archive_group(
+    State(pool): State<PgPool>,
+    user: TokenAuth<Edit>,
+    Path(id): Path<Uuid>
+) -> Result<AxumJson<TaskGroup>, ApiError> {
+    Ok(AxumJson(set_group_archived_inner(pool, user.id, id, true).await?))
+}
+
+// Handler to bring an archived task group back
+pub async fn unarchive_group(
+    State(pool): State<PgPool>,
+    user: TokenAuth<Edit>,
+    Path(id): Path<Uuid>
+) -> Result<AxumJson<TaskGroup>, ApiError> {
+    Ok(AxumJson(set_group_archived_inner(pool, user.id, id, false).await?))
+}
+
+pub async fn set_group_archived_inner(pool: PgPool, owner_id: Uuid, id: Uuid, archived: bool) -> Result<TaskGroup, ApiError> {
+    let group = sqlx::query_as!(
         TaskGroup,
-        "SELECT id, name, description, ordering FROM task_groups ORDER BY ordering ASC"
+        r#"
+        UPDATE task_groups
+        SET archived_at = CASE WHEN $1 THEN COALESCE(archived_at, NOW()) ELSE NULL END
+        WHERE id = $2 AND owner_id = $3
+        RETURNING id, name, description, ordering, archived_at IS NOT NULL as "archived!"
+        "#,
+        archived,
+        id,
+        owner_id
+    )
+    .fetch_one(&pool)
+    .await?;
+
+    Ok(group)
+}
+
+// Handler to reorder all task groups at once
+pub async fn reorder_groups(
+    State(pool): State<PgPool>,
+    user: TokenAuth<Edit>,
+    Json(payload): Json<ReorderGroupsRequest>
+) -> Result<AxumJson<Vec<TaskGroup>>, ApiError> {
+    Ok(AxumJson(reorder_groups_inner(pool, user.id, payload.ids).await?))
+}
+
+pub async fn reorder_groups_inner(pool: PgPool, owner_id: Uuid, ids: Vec<Uuid>) -> Result<Vec<TaskGroup>, ApiError> {
+    let mut tx = pool.begin().await?;
+
+    let mut existing: Vec<Uuid> = sqlx::query_scalar!("SELECT id FROM task_groups WHERE owner_id = $1 FOR UPDATE", owner_id)
+        .fetch_all(&mut *tx)
+        .await?;
+    let mut requested = ids.clone();
+    existing.sort();
+    requested.sort();
+    if existing != requested {
+        return Err(ApiError::BadRequest("ids must list every task group exactly once".to_string()));
+    }
+
+    // Positions are swapped within the transaction; uniqueness is checked at commit
+    sqlx::query!("SET CONSTRAINTS task_groups_ordering_key DEFERRED")
+        .execute(&mut *tx)
+        .await?;
+
+    sqlx::query!(
+        r#"
+        UPDATE task_groups
+        SET ordering = new_order.position
+        FROM UNNEST($1::uuid[]) WITH ORDINALITY AS new_order(id, position)
+        WHERE task_groups.id = new_order.id AND task_groups.owner_id = $2
+        "#,
+        &ids,
+        owner_id
+    )
+    .execute(&mut *tx)
+    .await?;
+
+    tx.commit().await?;
+
+    Ok(get_groups_inner(pool, owner_id, true).await?)
+}
+
+// Handler to delete a task group, moving or ungrouping its recordings
+pub async fn delete_group(
+    State(pool): State<PgPool>,
+    State(events): State<EventBus>,
+    user: TokenAuth<Delete>,
+    Path(id): Path<Uuid>,
+    Query(query): Query<DeleteGroupQuery>
+) -> Result<StatusCode, ApiError> {
+    delete_group_inner(pool, events, user.id, id, query.reassign_to).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+pub async fn delete_group_inner(pool: PgPool, events: EventBus, owner_id: Uuid, id: Uuid, reassign_to: Option<Uuid>) -> Result<(), ApiError> {
+    if reassign_to == Some(id) {
+        return Err(ApiError::BadRequest("Cannot reassign recordings to the group being deleted".to_string()));
+    }
+
+    let mut tx = pool.begin().await?;
+
+    sqlx::query!("SELECT id FROM task_groups WHERE id = $1 AND owner_id = $2 FOR UPDATE", id, owner_id)
+        .fetch_one(&mut *tx)
+        .await?;
+
+    if let Some(target) = reassign_to {
+        let exists = sqlx::query!("SELECT id FROM task_groups WHERE id = $1 AND owner_id = $2", target, owner_id)
+            .fetch_optional(&mut *tx)
+            .await?;
+        if exists.is_none() {
+            return Err(ApiError::BadRequest("reassign_to does not name an existing task group".to_string()));
+        }
+    }
+
+    let regrouped = sqlx::query_scalar!(
+        "UPDATE recordings SET group_id = $1 WHERE group_id = $2 RETURNING id",
+        reassign_to,
+        id
     )
-    .fetch_all(&pool)
-    .await
+    .fetch_all(&mut *tx)
+    .await?;
+
+    sqlx::query!("DELETE FROM task_groups WHERE id = $1", id)
+        .execute(&mut *tx)
+        .await?;
+
+    tx.commit().await?;
+
+    for recording_id in regrouped {
+        webhooks::trigger(&pool, WebhookEvent::Regrouped, recording_id).await;
+        events.recording_changed(&pool, recording_id).await;
+    }
+    Ok(())
+}
+
+fn validate_name(name: &str) -> Result<String, ApiError> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err(ApiError::BadRequest("name must not be empty".to_string()));
+    }
+    Ok(name.to_string())
 }
@@ -0,0 +1,46 @@
+use axum::{
+    extract::{Json, State},
+    response::Json as AxumJson,
+};
+use sqlx::PgPool;
+use crate::api::error::ApiError;
+use crate::models::dtos::{IntegrityOptions, IntegrityReport};
+use crate::service::accounts::AuthUser;
+use crate::service::integrity::check_storage;
+use crate::service::jobs::JobQueue;
+use crate::service::storage::SharedStorage;
+
+// Handler to compare storage with the recordings table without changing anything
+pub async fn check_integrity(
+    State(pool): State<PgPool>,
+    State(jobs): State<JobQueue>,
+    State(storage): State<SharedStorage>,
+    user: AuthUser
+) -> Result<AxumJson<IntegrityReport>, ApiError> {
+    require_admin(&user)?;
+    let report = check_storage(&pool, &jobs, storage.as_ref(), &IntegrityOptions::default(), None).await?;
+    Ok(AxumJson(report))
+}
+
+// Handler to run the same check and reimport orphans and/or mark broken rows.
+// Reimported recordings go to the admin who asked for the repair.
+pub async fn repair_integrity(
+    State(pool): State<PgPool>,
+    State(jobs): State<JobQueue>,
+    State(storage): State<SharedStorage>,
+    user: AuthUser,
+    Json(options): Json<IntegrityOptions>
+) -> Result<AxumJson<IntegrityReport>, ApiError> {
+    require_admin(&user)?;
+    let report = check_storage(&pool, &jobs, storage.as_ref(), &options, Some(&user)).await?;
+    Ok(AxumJson(report))
+}
+
+// Storage spans every account, so only admins may look at it
+fn require_admin(user: &AuthUser) -> Result<(), ApiError> {
+    if user.is_admin {
+        Ok(())
+    } else {
+        Err(ApiError::Forbidden)
+    }
+}
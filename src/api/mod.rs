@@ -1,2 +1,15 @@
+pub mod auth;
+pub mod digests;
+pub mod error;
+pub mod events;
 pub mod recordings;
-pub mod groups;
\ No newline at end of file
+pub mod files;
+pub mod groups;
+pub mod maintenance;
+pub mod versions;
+pub mod search;
+pub mod shares;
+pub mod tasks;
+pub mod tokens;
+pub mod uploads;
+pub mod webhooks;
@@ -1,27 +1,48 @@
 use axum::{
-    extract::{Query, Json, State, Multipart, Path},
+    extract::{Query, Json, State, Multipart, Path, multipart::{Field, MultipartError}},
     response::{IntoResponse, Json as AxumJson},
     http::StatusCode,
 };
-use std::fs;
-use std::fs::File;
-use std::io::Write;
 use std::path::{Path as FilePath};
 use std::time::{SystemTime, UNIX_EPOCH};
-use chrono::{DateTime, Local, Datelike, NaiveDate};
+use base64::{Engine as _, engine::general_purpose};
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use sha2::{Digest, Sha256};
 use sqlx::PgPool;
+use tokio::io::AsyncWriteExt;
 use uuid::Uuid;
-use crate::models::dtos::{DateFilter, RecordingFile, DeleteRequest, UpdateRecordingRequest};
-use crate::service::transcription::transcribe_audio;
+use crate::api::auth::{Delete, Edit, Read, TokenAuth, Upload};
+use crate::api::error::ApiError;
+use crate::api::uploads::detect_format;
+use crate::models::dtos::{CaptureTimes, DateFilter, RecordingFile, RecordingPage, RecordingQuery, DeleteRequest, TrashedRecording, UpdateRecordingRequest, UploadedRecording, WebhookEvent};
+use crate::service::accounts::AuthUser;
+use crate::service::events::EventBus;
+use crate::service::jobs::JobQueue;
+use crate::service::storage::{SharedStorage, Storage};
+use crate::service::trash::retention_days_from_env;
+use crate::service::uploads::{
+    discard_stored, find_duplicate, max_upload_bytes, normalize_capture, register_recording, spool_dir, store_upload, upload_target,
+    AudioFormat, NewRecording, Registered, SNIFF_LEN
+};
+use crate::service::versions::{save_transcription, VersionSource};
+use crate::service::webhooks;
+use crate::timezone::{start_of_day, today};
+
+const DEFAULT_PAGE_SIZE: i64 = 100;
+const MAX_PAGE_SIZE: i64 = 500;
+const RECORDING_STATUSES: [&str; 3] = ["PENDING", "COMPLETED", "FAILED"];
 
 // Handler to update a recording (e.g. set group)
 pub async fn update_recording(
     State(pool): State<PgPool>,
+    State(events): State<EventBus>,
+    user: TokenAuth<Edit>,
     Path(id): Path<Uuid>,
     Json(payload): Json<UpdateRecordingRequest>
 ) -> impl IntoResponse {
-    match update_recording_inner(pool, id, payload.group_id).await {
+    match update_recording_inner(pool, events, user.id, id, payload.group_id).await {
         Ok(_) => StatusCode::OK.into_response(),
+        Err(sqlx::Error::RowNotFound) => StatusCode::NOT_FOUND.into_response(),
         Err(e) => {
             eprintln!("Database error on update: {}", e);
             StatusCode::INTERNAL_SERVER_ERROR.into_response()
@@ -29,58 +50,140 @@ pub async fn update_recording(
     }
 }
 
-pub async fn update_recording_inner(pool: PgPool, id: Uuid, group_id: Option<Uuid>) -> Result<(), sqlx::Error> {
-    sqlx::query!(
-        "UPDATE recordings SET group_id = $1 WHERE id = $2",
+// Recordings can only be filed under their owner's groups
+pub async fn update_recording_inner(pool: PgPool, events: EventBus, owner_id: Uuid, id: Uuid, group_id: Option<Uuid>) -> Result<(), sqlx::Error> {
+    let record = sqlx::query!(
+        r#"
+        UPDATE recordings r SET group_id = $1
+        FROM (SELECT id, group_id FROM recordings WHERE id = $2 FOR UPDATE) previous
+        WHERE r.id = previous.id AND r.owner_id = $3
+          AND ($1::uuid IS NULL OR EXISTS (SELECT 1 FROM task_groups WHERE id = $1 AND owner_id = $3))
+        RETURNING previous.group_id as previous_group_id
+        "#,
         group_id,
-        id
+        id,
+        owner_id
     )
-    .execute(&pool)
-    .await?;
+    .fetch_optional(&pool)
+    .await?
+    .ok_or(sqlx::Error::RowNotFound)?;
+
+    if record.previous_group_id != group_id {
+        webhooks::trigger(&pool, WebhookEvent::Regrouped, id).await;
+    }
+    events.recording_changed(&pool, id).await;
     Ok(())
 }
 
-pub async fn update_recording_title_inner(pool: PgPool, id: Uuid, title: String) -> Result<(), sqlx::Error> {
-    sqlx::query!(
+// Manual title edits are stored as a new transcription version
+pub async fn update_recording_title_inner(pool: PgPool, events: EventBus, owner_id: Uuid, id: Uuid, title: String) -> Result<(), sqlx::Error> {
+    let mut tx = pool.begin().await?;
+
+    let record = sqlx::query!(
+        "SELECT transcription_text FROM recordings WHERE id = $1 AND owner_id = $2 FOR UPDATE",
+        id,
+        owner_id
+    )
+    .fetch_optional(&mut *tx)
+    .await?
+    .ok_or(sqlx::Error::RowNotFound)?;
+
+    let mut transcription = record.transcription_text
+        .filter(|v| v.is_object())
+        .unwrap_or_else(|| serde_json::json!({}));
+    transcription["title"] = serde_json::Value::String(title);
+
+    save_transcription(&mut tx, id, &transcription, VersionSource::Manual).await?;
+
+    tx.commit().await?;
+    events.recording_changed(&pool, id).await;
+    Ok(())
+}
+
+// Handler to transcribe a recording again
+pub async fn retranscribe_recording(
+    State(pool): State<PgPool>,
+    State(jobs): State<JobQueue>,
+    user: TokenAuth<Edit>,
+    Path(id): Path<Uuid>
+) -> Result<StatusCode, ApiError> {
+    retranscribe_recording_inner(pool, jobs, user.id, id).await?;
+    Ok(StatusCode::ACCEPTED)
+}
+
+// Resets the status and requeues the job; earlier results stay in transcription_versions.
+// A recording whose job is still queued or running is left alone, since that job would
+// overwrite the status and the request would be lost.
+pub async fn retranscribe_recording_inner(pool: PgPool, jobs: JobQueue, owner_id: Uuid, id: Uuid) -> Result<(), ApiError> {
+    let reset = sqlx::query!(
         r#"
-        UPDATE recordings
-        SET transcription_text = jsonb_set(
-            COALESCE(transcription_text, '{}'::jsonb),
-            '{title}',
-            to_jsonb($1::text)
-        )
-        WHERE id = $2
+        UPDATE recordings r
+        SET transcription_status = 'PENDING', transcription_error = NULL
+        WHERE r.id = $1 AND r.owner_id = $2 AND r.deleted_at IS NULL
+          AND NOT EXISTS (
+              SELECT 1 FROM transcription_jobs j
+              WHERE j.recording_id = r.id AND j.status IN ('QUEUED', 'RUNNING')
+          )
+        RETURNING r.id
         "#,
-        title,
-        id
+        id,
+        owner_id
     )
-    .execute(&pool)
+    .fetch_optional(&pool)
     .await?;
+
+    if reset.is_none() {
+        sqlx::query!("SELECT id FROM recordings WHERE id = $1 AND owner_id = $2 AND deleted_at IS NULL", id, owner_id)
+            .fetch_optional(&pool)
+            .await?
+            .ok_or(ApiError::NotFound)?;
+        return Err(already_transcribing());
+    }
+
+    let queued = jobs.enqueue(&pool, id).await?;
+    jobs.events().recording_changed(&pool, id).await;
+    if !queued {
+        return Err(already_transcribing());
+    }
     Ok(())
 }
 
-// Handler to list recordings (optionally filtered by date)
+fn already_transcribing() -> ApiError {
+    ApiError::Conflict("The recording is already queued for transcription".to_string())
+}
+
+// Handler to list recordings with filters and cursor pagination
 pub async fn list_recordings(
     State(pool): State<PgPool>,
-    Query(filter): Query<DateFilter>
-) -> impl IntoResponse {
-    match list_recordings_inner(pool, filter.date).await {
-        Ok(files) => AxumJson(files).into_response(),
-        Err(e) => {
-            eprintln!("Database error: {}", e);
-            StatusCode::INTERNAL_SERVER_ERROR.into_response()
-        }
-    }
+    user: TokenAuth<Read>,
+    Query(query): Query<RecordingQuery>
+) -> Result<AxumJson<RecordingPage>, ApiError> {
+    Ok(AxumJson(list_recordings_inner(pool, &user, query).await?))
 }
 
-pub async fn list_recordings_inner(pool: PgPool, date: Option<String>) -> Result<Vec<RecordingFile>, sqlx::Error> {
-    let date_str = date.unwrap_or_else(|| Local::now().format("%Y-%m-%d").to_string());
+// Validated paging and date range of a `RecordingQuery`
+struct ListWindow {
+    from: Option<NaiveDate>,
+    to: Option<NaiveDate>,
+    limit: i64,
+    cursor: Option<(DateTime<Utc>, Uuid)>,
+}
 
-    // Parse date_str to NaiveDate for SQL query
-    let query_date = NaiveDate::parse_from_str(&date_str, "%Y-%m-%d")
-        .map_err(|_| sqlx::Error::RowNotFound)?; // Generic error for bad date
+pub async fn list_recordings_inner(pool: PgPool, user: &AuthUser, query: RecordingQuery) -> Result<RecordingPage, ApiError> {
+    let tz = user.timezone;
+    let ListWindow { from, to, limit, cursor } = list_window(&query, today(tz))?;
 
-    sqlx::query_as!(
+    // Days begin and end at midnight in the user's timezone, not the database's
+    let from = from.map(|day| start_of_day(day, tz));
+    let until = to.map(|day| start_of_day(day + Duration::days(1), tz));
+    let (cursor_created_at, cursor_id) = cursor.unzip();
+
+    let text_query = query.q.filter(|q| !q.trim().is_empty());
+    let title_pattern = query.title
+        .filter(|t| !t.trim().is_empty())
+        .map(|t| format!("%{}%", t.replace('\\', "\\\\").replace('%', "\\%").replace('_', "\\_")));
+
+    let mut items = sqlx::query_as!(
         RecordingFile,
         r#"
         SELECT
@@ -89,143 +192,331 @@ pub async fn list_recordings_inner(pool: PgPool, date: Option<String>) -> Result
             filename as "name!",
             transcription_status as "status!",
             transcription_text as "transcription",
-            group_id
+            transcription_error as "error",
+            mime_type,
+            group_id,
+            suggested_group_id,
+            suggestion_confidence,
+            integrity_error,
+            coalesce(started_at, created_at) as "started_at!",
+            ended_at,
+            duration_ms,
+            created_at as "created_at!"
         FROM recordings
-        WHERE date(created_at) = $1
-        ORDER BY created_at DESC
+        WHERE deleted_at IS NULL AND owner_id = $10
+          AND ($1::timestamptz IS NULL OR created_at >= $1)
+          AND ($2::timestamptz IS NULL OR created_at < $2)
+          AND ($3::uuid IS NULL OR group_id = $3)
+          AND ($4::text IS NULL OR transcription_status = $4)
+          AND ($5::text IS NULL OR search_vector @@ websearch_to_tsquery('english', $5))
+          AND ($6::text IS NULL OR transcription_text->>'title' ILIKE $6)
+          AND ($7::timestamptz IS NULL OR (created_at, id) < ($7, $8::uuid))
+        ORDER BY created_at DESC, id DESC
+        LIMIT $9
         "#,
-        query_date
+        from,
+        until,
+        query.group_id,
+        query.status,
+        text_query,
+        title_pattern,
+        cursor_created_at,
+        cursor_id,
+        limit + 1,
+        user.id
     )
     .fetch_all(&pool)
-    .await
+    .await?;
+
+    let next_cursor = if items.len() as i64 > limit {
+        items.truncate(limit as usize);
+        items.last().map(|last| encode_cursor(last.created_at, last.id))
+    } else {
+        None
+    };
+
+    Ok(RecordingPage { items, next_cursor })
+}
+
+fn list_window(query: &RecordingQuery, today: NaiveDate) -> Result<ListWindow, ApiError> {
+    let (from, to) = match (&query.date, &query.from, &query.to) {
+        (Some(_), Some(_), _) | (Some(_), _, Some(_)) => {
+            return Err(ApiError::BadRequest("Use either date or from/to, not both".to_string()));
+        }
+        (Some(date), None, None) => {
+            let date = parse_date("date", date)?;
+            (Some(date), Some(date))
+        }
+        (None, None, None) => (Some(today), Some(today)),
+        (None, from, to) => (
+            from.as_deref().map(|d| parse_date("from", d)).transpose()?,
+            to.as_deref().map(|d| parse_date("to", d)).transpose()?,
+        ),
+    };
+
+    if let (Some(from), Some(to)) = (from, to) {
+        if from > to {
+            return Err(ApiError::BadRequest("from must not be after to".to_string()));
+        }
+    }
+
+    if let Some(status) = &query.status {
+        if !RECORDING_STATUSES.contains(&status.as_str()) {
+            return Err(ApiError::BadRequest(format!(
+                "Unknown status {}, expected one of {}",
+                status,
+                RECORDING_STATUSES.join(", ")
+            )));
+        }
+    }
+
+    let limit = query.limit.unwrap_or(DEFAULT_PAGE_SIZE);
+    if !(1..=MAX_PAGE_SIZE).contains(&limit) {
+        return Err(ApiError::BadRequest(format!("limit must be between 1 and {}", MAX_PAGE_SIZE)));
+    }
+
+    let cursor = query.cursor.as_deref().map(decode_cursor).transpose()?;
+    Ok(ListWindow { from, to, limit, cursor })
+}
+
+fn parse_date(field: &str, value: &str) -> Result<NaiveDate, ApiError> {
+    NaiveDate::parse_from_str(value, "%Y-%m-%d")
+        .map_err(|_| ApiError::BadRequest(format!("{} must be a date in YYYY-MM-DD format", field)))
+}
+
+// Cursors point at the last row of a page: "<created_at>|<id>", base64 encoded
+fn encode_cursor(created_at: DateTime<Utc>, id: Uuid) -> String {
+    general_purpose::URL_SAFE_NO_PAD.encode(format!("{}|{}", created_at.to_rfc3339(), id))
+}
+
+fn decode_cursor(cursor: &str) -> Result<(DateTime<Utc>, Uuid), ApiError> {
+    let invalid = || ApiError::BadRequest("Invalid cursor".to_string());
+
+    let decoded = general_purpose::URL_SAFE_NO_PAD.decode(cursor).map_err(|_| invalid())?;
+    let decoded = String::from_utf8(decoded).map_err(|_| invalid())?;
+    let (created_at, id) = decoded.split_once('|').ok_or_else(invalid)?;
+
+    let created_at = DateTime::parse_from_rfc3339(created_at).map_err(|_| invalid())?.with_timezone(&Utc);
+    let id = Uuid::parse_str(id).map_err(|_| invalid())?;
+    Ok((created_at, id))
 }
 
-// Handler for uploading audio
+// Handler for uploading audio. Capture time fields (`started_at`, `ended_at`, `duration_ms`)
+// apply to the files that follow them in the form.
 pub async fn upload_handler(
     State(pool): State<PgPool>,
+    State(jobs): State<JobQueue>,
+    State(storage): State<SharedStorage>,
+    user: TokenAuth<Upload>,
     Query(filter): Query<DateFilter>,
     mut multipart: Multipart
-) -> impl IntoResponse {
-    let now: DateTime<Local> = Local::now();
-
-    // Determine the upload directory based on the optional date query param
-    let (year, month, day) = if let Some(date_str) = filter.date.clone() {
-        if let Ok(date) = NaiveDate::parse_from_str(&date_str, "%Y-%m-%d") {
-            (date.year(), date.month(), date.day())
-        } else {
-            (now.year(), now.month(), now.day())
+) -> Result<AxumJson<Vec<UploadedRecording>>, ApiError> {
+    let mut capture = CaptureTimes::default();
+    let mut uploaded = Vec::new();
+    while let Some(field) = multipart.next_field().await.map_err(multipart_error)? {
+        match field.name() {
+            Some("file") => {}
+            Some(name @ ("started_at" | "ended_at" | "duration_ms")) => {
+                let name = name.to_string();
+                let value = field.text().await.map_err(multipart_error)?;
+                set_capture_field(&mut capture, &name, value.trim())?;
+                continue;
+            }
+            _ => continue,
         }
-    } else {
-        (now.year(), now.month(), now.day())
-    };
+        let capture = normalize_capture(capture).map_err(ApiError::BadRequest)?;
 
-    let relative_dir = format!("{}/{}/{}", year, month, day);
-    let upload_dir = format!("recordings/{}", relative_dir);
+        // Determine the upload directory based on the optional date query param and capture start
+        let target = upload_target(filter.date.as_deref(), capture.started_at, user.timezone);
+        let upload_dir = spool_dir(&target.relative_dir);
+        tokio::fs::create_dir_all(&upload_dir).await?;
 
-    if let Err(e) = fs::create_dir_all(&upload_dir) {
-        eprintln!("Failed to create upload directory: {}", e);
-        return StatusCode::INTERNAL_SERVER_ERROR.into_response();
-    }
+        let file_name = field.file_name().unwrap_or("").to_string();
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs();
+
+        // If uploaded filename starts with "test_", preserve that prefix for easier cleanup
+        let prefix = if file_name.starts_with("test_") { "test_" } else { "" };
+        let stem = format!("{}recording_{}", prefix, timestamp);
 
-    while let Ok(Some(field)) = multipart.next_field().await {
-        let name = field.name().unwrap_or("unknown").to_string();
-
-        if name == "file" {
-            let file_name = field.file_name().unwrap_or("").to_string();
-            if let Ok(data) = field.bytes().await {
-                let timestamp = SystemTime::now()
-                    .duration_since(UNIX_EPOCH)
-                    .unwrap()
-                    .as_secs();
-
-                // If uploaded filename starts with "test_", preserve that prefix for easier cleanup
-                let prefix = if file_name.starts_with("test_") { "test_" } else { "" };
-                let filename = format!("{}recording_{}.webm", prefix, timestamp);
-
-                let filepath_in_db = format!("{}/{}", relative_dir, filename);
-                let full_filepath = FilePath::new(&upload_dir).join(&filename);
-
-                if let Ok(mut file) = File::create(&full_filepath) {
-                    if file.write_all(&data).is_ok() {
-                        println!("Saved file: {}", full_filepath.display());
-
-                        // Insert into database
-                        let res = sqlx::query!(
-                            r#"
-                            INSERT INTO recordings (filename, file_path, created_at)
-                            VALUES ($1, $2, $3)
-                            RETURNING id
-                            "#,
-                            filename,
-                            filepath_in_db,
-                            if let Some(ref ds) = filter.date {
-                                NaiveDate::parse_from_str(ds, "%Y-%m-%d").ok().and_then(|d| d.and_hms_opt(12, 0, 0)).map(|dt| DateTime::<Local>::from_naive_utc_and_offset(dt.and_utc().naive_utc(), *Local::now().offset()))
-                            } else {
-                                Some(Local::now())
-                            }
-                        )
-                        .fetch_one(&pool)
-                        .await;
-
-                        match res {
-                            Ok(record) => {
-                                // Spawn transcription task
-                                let pool_clone = pool.clone();
-                                let full_filepath_clone = full_filepath.clone();
-                                let record_id = record.id;
-
-                                tokio::spawn(async move {
-                                    if let Err(e) = transcribe_and_update(pool_clone, record_id, full_filepath_clone).await {
-                                        eprintln!("Transcription failed: {}", e);
-                                    }
-                                });
-                            },
-                            Err(e) => {
-                                eprintln!("Failed to insert into DB: {}", e);
-                                return StatusCode::INTERNAL_SERVER_ERROR.into_response();
-                            }
-                        }
-                    } else {
-                        eprintln!("Failed to write to file: {}", full_filepath.display());
-                        return StatusCode::INTERNAL_SERVER_ERROR.into_response();
-                    }
-                } else {
-                    eprintln!("Failed to create file: {}", full_filepath.display());
-                    return StatusCode::INTERNAL_SERVER_ERROR.into_response();
-                }
+        // Retries of an upload that already arrived get the existing recording back
+        let saved = match save_upload(field, &pool, user.id, storage.as_ref(), &upload_dir, &target.relative_dir, &stem).await? {
+            SavedUpload::Stored(saved) => saved,
+            SavedUpload::Duplicate(id) => {
+                println!("Skipped duplicate of recording {}", id);
+                uploaded.push(UploadedRecording { id, duplicate: true });
+                continue;
+            }
+        };
+        let filepath_in_db = format!("{}/{}", target.relative_dir, saved.filename);
+        println!("Saved file: {} ({})", filepath_in_db, saved.mime_type);
+
+        // Insert into database
+        let recording = NewRecording {
+            owner_id: user.id,
+            filename: &saved.filename,
+            file_path: &filepath_in_db,
+            mime_type: &saved.mime_type,
+            size_bytes: saved.size_bytes as i64,
+            sha256: &saved.sha256,
+            recorded_at: target.recorded_at,
+            capture,
+            transcribe_at: None,
+        };
+        match register_recording(&pool, &jobs, &recording).await {
+            Ok(Registered::New(id)) => uploaded.push(UploadedRecording { id, duplicate: false }),
+            Ok(Registered::Duplicate(id)) => {
+                discard_stored(storage.as_ref(), &filepath_in_db).await;
+                uploaded.push(UploadedRecording { id, duplicate: true });
+            }
+            Err(e) => {
+                discard_stored(storage.as_ref(), &filepath_in_db).await;
+                return Err(e.into());
             }
         }
     }
-    StatusCode::OK.into_response()
+
+    Ok(AxumJson(uploaded))
 }
 
-// Helper function to transcribe and update the database
-async fn transcribe_and_update(
-    pool: PgPool,
-    id: Uuid,
-    path: std::path::PathBuf
-) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-    let transcription_text = transcribe_audio(path.clone()).await?;
-    let json_value: serde_json::Value = serde_json::from_str(&transcription_text)?;
+fn set_capture_field(capture: &mut CaptureTimes, name: &str, value: &str) -> Result<(), ApiError> {
+    let timestamp = || {
+        DateTime::parse_from_rfc3339(value)
+            .map(|t| t.with_timezone(&Utc))
+            .map_err(|_| ApiError::BadRequest(format!("{} must be an RFC 3339 timestamp", name)))
+    };
+    match name {
+        "started_at" => capture.started_at = Some(timestamp()?),
+        "ended_at" => capture.ended_at = Some(timestamp()?),
+        _ => {
+            let duration = value.parse()
+                .map_err(|_| ApiError::BadRequest("duration_ms must be a whole number".to_string()))?;
+            capture.duration_ms = Some(duration);
+        }
+    }
+    Ok(())
+}
 
-    sqlx::query!(
-        r#"
-        UPDATE recordings
-        SET transcription_text = $1, transcription_status = 'COMPLETED'
-        WHERE id = $2
-        "#,
-        json_value,
-        id
-    )
-    .execute(&pool)
-    .await?;
+enum SavedUpload {
+    Stored(StoredUpload),
+    /// A live recording already has this audio
+    Duplicate(Uuid),
+}
 
-    Ok(())
+// An upload that has been moved into storage
+struct StoredUpload {
+    filename: String,
+    mime_type: String,
+    size_bytes: u64,
+    sha256: String,
+}
+
+// What was written to the temp file
+struct WrittenUpload {
+    format: AudioFormat,
+    mime_type: String,
+    size_bytes: u64,
+    sha256: String,
+}
+
+// Streams one multipart field to a temp file in `upload_dir` and, once it is complete
+// and valid, moves it into storage unless the same audio is already there.
+async fn save_upload(
+    mut field: Field<'_>,
+    pool: &PgPool,
+    owner_id: Uuid,
+    storage: &dyn Storage,
+    upload_dir: &FilePath,
+    relative_dir: &str,
+    stem: &str
+) -> Result<SavedUpload, ApiError> {
+    let temp_path = upload_dir.join(format!(".upload-{}.part", Uuid::new_v4()));
+
+    let saved = match write_upload(&mut field, &temp_path).await {
+        Ok(written) => store_written(pool, owner_id, storage, &temp_path, relative_dir, stem, written).await,
+        Err(e) => Err(e),
+    };
+
+    // The temp file is gone once storage has it
+    if !matches!(saved, Ok(SavedUpload::Stored(_))) {
+        let _ = tokio::fs::remove_file(&temp_path).await;
+    }
+    saved
+}
+
+async fn store_written(
+    pool: &PgPool,
+    owner_id: Uuid,
+    storage: &dyn Storage,
+    temp_path: &FilePath,
+    relative_dir: &str,
+    stem: &str,
+    written: WrittenUpload
+) -> Result<SavedUpload, ApiError> {
+    if let Some(id) = find_duplicate(pool, owner_id, &written.sha256).await? {
+        return Ok(SavedUpload::Duplicate(id));
+    }
+
+    let filename = store_upload(storage, temp_path, relative_dir, stem, written.format, &written.mime_type).await?;
+    Ok(SavedUpload::Stored(StoredUpload {
+        filename,
+        mime_type: written.mime_type,
+        size_bytes: written.size_bytes,
+        sha256: written.sha256,
+    }))
+}
+
+async fn write_upload(field: &mut Field<'_>, temp_path: &FilePath) -> Result<WrittenUpload, ApiError> {
+    let max_bytes = max_upload_bytes();
+    let mut file = tokio::fs::File::create(temp_path).await?;
+    let mut header = Vec::with_capacity(SNIFF_LEN);
+    let mut hasher = Sha256::new();
+    let mut written: u64 = 0;
+
+    while let Some(chunk) = field.chunk().await.map_err(multipart_error)? {
+        written += chunk.len() as u64;
+        if written > max_bytes {
+            return Err(ApiError::PayloadTooLarge(max_bytes));
+        }
+
+        // Reject unknown formats as soon as enough of the file has arrived
+        if header.len() < SNIFF_LEN {
+            let needed = SNIFF_LEN - header.len();
+            header.extend_from_slice(&chunk[..needed.min(chunk.len())]);
+            if header.len() == SNIFF_LEN {
+                detect_format(&header)?;
+            }
+        }
+
+        hasher.update(&chunk);
+        file.write_all(&chunk).await?;
+    }
+
+    let format = detect_format(&header)?;
+    file.sync_all().await?;
+    Ok(WrittenUpload {
+        format,
+        mime_type: format.mime_type(&header),
+        size_bytes: written,
+        sha256: format!("{:x}", hasher.finalize()),
+    })
+}
+
+fn multipart_error(e: MultipartError) -> ApiError {
+    if e.status() == StatusCode::PAYLOAD_TOO_LARGE {
+        ApiError::PayloadTooLarge(max_upload_bytes())
+    } else {
+        ApiError::BadRequest(e.body_text())
+    }
 }
 
-// Handler to delete a recording
+// Handler to delete a recording; it moves to the trash until the purge removes it
 pub async fn delete_recording(
     State(pool): State<PgPool>,
+    State(events): State<EventBus>,
+    user: TokenAuth<Delete>,
     Json(payload): Json<DeleteRequest>
 ) -> impl IntoResponse {
     if !payload.path.starts_with("/files/") || payload.path.contains("..") {
@@ -234,21 +525,20 @@ pub async fn delete_recording(
 
     let relative_path = &payload.path["/files/".len()..];
 
-    // Delete from DB first
     let res = sqlx::query!(
-        "DELETE FROM recordings WHERE file_path = $1 RETURNING id",
-        relative_path
+        "UPDATE recordings SET deleted_at = NOW() WHERE file_path = $1 AND owner_id = $2 AND deleted_at IS NULL RETURNING id",
+        relative_path,
+        user.id
     )
     .fetch_optional(&pool)
     .await;
 
     match res {
-        Ok(Some(_)) => {
-            // Delete from disk
-            let file_path = FilePath::new("recordings").join(relative_path);
-            let _ = fs::remove_file(&file_path);
+        Ok(Some(record)) => {
+            events.recording_removed(user.id, record.id);
+            webhooks::trigger(&pool, WebhookEvent::Deleted, record.id).await;
             StatusCode::OK
-        },
+        }
         Ok(None) => StatusCode::NOT_FOUND,
         Err(e) => {
             eprintln!("DB error on delete: {}", e);
@@ -257,25 +547,231 @@ pub async fn delete_recording(
     }
 }
 
-pub async fn delete_recording_by_id_inner(pool: PgPool, id: Uuid) -> Result<(), sqlx::Error> {
-    // Fetch path first to delete from disk
+pub async fn delete_recording_by_id_inner(pool: PgPool, events: EventBus, owner_id: Uuid, id: Uuid) -> Result<(), sqlx::Error> {
+    let deleted = sqlx::query!(
+        "UPDATE recordings SET deleted_at = NOW() WHERE id = $1 AND owner_id = $2 AND deleted_at IS NULL",
+        id,
+        owner_id
+    )
+    .execute(&pool)
+    .await?;
+    if deleted.rows_affected() > 0 {
+        events.recording_removed(owner_id, id);
+        webhooks::trigger(&pool, WebhookEvent::Deleted, id).await;
+    }
+    Ok(())
+}
+
+// Handler to list recordings in the trash
+pub async fn list_trash(
+    State(pool): State<PgPool>,
+    user: TokenAuth<Read>
+) -> Result<AxumJson<Vec<TrashedRecording>>, ApiError> {
+    Ok(AxumJson(list_trash_inner(pool, user.id).await?))
+}
+
+pub async fn list_trash_inner(pool: PgPool, owner_id: Uuid) -> Result<Vec<TrashedRecording>, sqlx::Error> {
+    sqlx::query_as!(
+        TrashedRecording,
+        r#"
+        SELECT
+            id,
+            filename as "name!",
+            transcription_text->>'title' as "title",
+            created_at as "created_at!",
+            deleted_at as "deleted_at!",
+            deleted_at + make_interval(days => $1) as "purge_at!"
+        FROM recordings
+        WHERE deleted_at IS NOT NULL AND owner_id = $2
+        ORDER BY deleted_at DESC, id DESC
+        "#,
+        retention_days_from_env() as i32,
+        owner_id
+    )
+    .fetch_all(&pool)
+    .await
+}
+
+// Handler to take a recording out of the trash
+pub async fn restore_recording(
+    State(pool): State<PgPool>,
+    State(events): State<EventBus>,
+    user: TokenAuth<Delete>,
+    Path(id): Path<Uuid>
+) -> Result<StatusCode, ApiError> {
+    restore_recording_inner(pool, events, user.id, id).await?;
+    Ok(StatusCode::OK)
+}
+
+pub async fn restore_recording_inner(pool: PgPool, events: EventBus, owner_id: Uuid, id: Uuid) -> Result<(), ApiError> {
     let record = sqlx::query!(
-        "SELECT file_path FROM recordings WHERE id = $1",
+        "SELECT sha256 FROM recordings WHERE id = $1 AND owner_id = $2 AND deleted_at IS NOT NULL",
+        id,
+        owner_id
+    )
+    .fetch_optional(&pool)
+    .await?
+    .ok_or(ApiError::NotFound)?;
+
+    // Only one live copy of the same audio is allowed
+    if let Some(sha256) = record.sha256 {
+        if find_duplicate(&pool, owner_id, &sha256).await?.is_some() {
+            return Err(ApiError::Conflict("The same recording has been uploaded again since it was deleted".to_string()));
+        }
+    }
+
+    sqlx::query!(
+        "UPDATE recordings SET deleted_at = NULL WHERE id = $1 AND deleted_at IS NOT NULL RETURNING id",
         id
     )
     .fetch_optional(&pool)
-    .await?;
+    .await?
+    .ok_or(sqlx::Error::RowNotFound)?;
+    events.recording_changed(&pool, id).await;
+    Ok(())
+}
 
-    if let Some(record) = record {
-        // Delete from DB
-        sqlx::query!("DELETE FROM recordings WHERE id = $1", id)
-            .execute(&pool)
-            .await?;
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use crate::models::dtos::RecordingEvent;
 
-        // Delete from disk
-        let file_path = FilePath::new("recordings").join(record.file_path);
-        let _ = fs::remove_file(&file_path);
+    fn day(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
     }
 
-    Ok(())
+    fn rejected(query: RecordingQuery) -> bool {
+        matches!(list_window(&query, day(2026, 10, 18)), Err(ApiError::BadRequest(_)))
+    }
+
+    #[test]
+    fn cursor_round_trips() {
+        let created_at = Utc.with_ymd_and_hms(2026, 10, 18, 9, 30, 15).unwrap() + Duration::microseconds(123_456);
+        let id = Uuid::new_v4();
+        assert_eq!(decode_cursor(&encode_cursor(created_at, id)).unwrap(), (created_at, id));
+    }
+
+    #[test]
+    fn malformed_cursors_are_bad_requests() {
+        let garbage = general_purpose::URL_SAFE_NO_PAD.encode("2026-10-18T09:30:15Z|not-a-uuid");
+        let no_separator = general_purpose::URL_SAFE_NO_PAD.encode("2026-10-18T09:30:15Z");
+        for cursor in ["", "%%%", garbage.as_str(), no_separator.as_str()] {
+            assert!(matches!(decode_cursor(cursor), Err(ApiError::BadRequest(_))), "accepted {:?}", cursor);
+        }
+    }
+
+    #[test]
+    fn empty_query_lists_today() {
+        let window = list_window(&RecordingQuery::default(), day(2026, 10, 18)).unwrap();
+        assert_eq!((window.from, window.to), (Some(day(2026, 10, 18)), Some(day(2026, 10, 18))));
+        assert_eq!(window.limit, DEFAULT_PAGE_SIZE);
+        assert!(window.cursor.is_none());
+    }
+
+    #[test]
+    fn date_is_a_single_day_range() {
+        let query = RecordingQuery { date: Some("2026-05-14".to_string()), ..Default::default() };
+        let window = list_window(&query, day(2026, 10, 18)).unwrap();
+        assert_eq!((window.from, window.to), (Some(day(2026, 5, 14)), Some(day(2026, 5, 14))));
+    }
+
+    #[test]
+    fn open_ended_ranges_are_allowed() {
+        let query = RecordingQuery { from: Some("2026-05-14".to_string()), ..Default::default() };
+        let window = list_window(&query, day(2026, 10, 18)).unwrap();
+        assert_eq!((window.from, window.to), (Some(day(2026, 5, 14)), None));
+    }
+
+    #[test]
+    fn invalid_queries_are_rejected() {
+        let text = |s: &str| Some(s.to_string());
+        assert!(rejected(RecordingQuery { date: text("2026-05-14"), from: text("2026-05-01"), ..Default::default() }));
+        assert!(rejected(RecordingQuery { date: text("14/05/2026"), ..Default::default() }));
+        assert!(rejected(RecordingQuery { from: text("2026-05-15"), to: text("2026-05-14"), ..Default::default() }));
+        assert!(rejected(RecordingQuery { status: text("DONE"), ..Default::default() }));
+        assert!(rejected(RecordingQuery { limit: Some(0), ..Default::default() }));
+        assert!(rejected(RecordingQuery { limit: Some(MAX_PAGE_SIZE + 1), ..Default::default() }));
+        assert!(rejected(RecordingQuery { cursor: text("nonsense"), ..Default::default() }));
+    }
+
+    #[test]
+    fn capture_fields_are_parsed() {
+        let mut capture = CaptureTimes::default();
+        set_capture_field(&mut capture, "started_at", "2026-10-18T11:30:00+02:00").unwrap();
+        set_capture_field(&mut capture, "ended_at", "2026-10-18T09:31:00Z").unwrap();
+        set_capture_field(&mut capture, "duration_ms", "60000").unwrap();
+        assert_eq!(capture.started_at, Some(Utc.with_ymd_and_hms(2026, 10, 18, 9, 30, 0).unwrap()));
+        assert_eq!(capture.ended_at, Some(Utc.with_ymd_and_hms(2026, 10, 18, 9, 31, 0).unwrap()));
+        assert_eq!(capture.duration_ms, Some(60_000));
+    }
+
+    #[test]
+    fn malformed_capture_fields_are_bad_requests() {
+        let mut capture = CaptureTimes::default();
+        for (name, value) in [("started_at", "2026-10-18 09:30"), ("ended_at", ""), ("duration_ms", "1.5")] {
+            assert!(matches!(set_capture_field(&mut capture, name, value), Err(ApiError::BadRequest(_))), "accepted {}", name);
+        }
+    }
+
+    async fn recording(pool: &PgPool, trashed: bool) -> (Uuid, Uuid) {
+        let user = crate::service::accounts::NewUser { username: "alice", password: "correct horse", is_admin: false, timezone: None };
+        let owner_id = crate::service::accounts::create_user(pool, &user).await.unwrap();
+        let id = sqlx::query_scalar!(
+            r#"
+            INSERT INTO recordings (filename, file_path, mime_type, owner_id, transcription_status, deleted_at)
+            VALUES ('a.webm', '2026/10/18/a.webm', 'audio/webm', $1, 'FAILED', CASE WHEN $2 THEN NOW() END)
+            RETURNING id
+            "#,
+            owner_id,
+            trashed
+        )
+        .fetch_one(pool)
+        .await
+        .unwrap();
+        (owner_id, id)
+    }
+
+    #[sqlx::test]
+    async fn retranscribing_queues_one_job(pool: PgPool) {
+        let jobs = JobQueue::from_env();
+        let (owner_id, id) = recording(&pool, false).await;
+        retranscribe_recording_inner(pool.clone(), jobs.clone(), owner_id, id).await.unwrap();
+
+        // The first job hasn't run yet, so asking again is refused rather than lost
+        let again = retranscribe_recording_inner(pool.clone(), jobs, owner_id, id).await;
+        assert!(matches!(again, Err(ApiError::Conflict(_))));
+        let status = sqlx::query_scalar!("SELECT transcription_status FROM recordings WHERE id = $1", id)
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert_eq!(status.as_deref(), Some("PENDING"));
+    }
+
+    #[sqlx::test]
+    async fn trashed_recordings_are_not_retranscribed(pool: PgPool) {
+        let (owner_id, id) = recording(&pool, true).await;
+        let result = retranscribe_recording_inner(pool.clone(), JobQueue::from_env(), owner_id, id).await;
+        assert!(matches!(result, Err(ApiError::NotFound)));
+        let jobs = sqlx::query_scalar!(r#"SELECT COUNT(*) as "count!" FROM transcription_jobs WHERE recording_id = $1"#, id)
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert_eq!(jobs, 0);
+    }
+
+    #[sqlx::test]
+    async fn title_edits_reach_open_pages(pool: PgPool) {
+        let events = EventBus::new();
+        let mut receiver = events.subscribe();
+        let (owner_id, id) = recording(&pool, false).await;
+        update_recording_title_inner(pool, events, owner_id, id, "Standup".to_string()).await.unwrap();
+
+        let event = receiver.try_recv().unwrap();
+        assert_eq!(event.owner_id, owner_id);
+        match event.event {
+            RecordingEvent::Changed { recording } => assert_eq!(recording.transcription.unwrap()["title"], "Standup"),
+            _ => panic!("expected the edited recording"),
+        }
+    }
 }
@@ -0,0 +1,138 @@
+use axum::extract::{Json, Query, State};
+use sqlx::PgPool;
+use uuid::Uuid;
+use crate::api::auth::{Read, TokenAuth};
+use crate::api::error::ApiError;
+use crate::models::dtos::{SearchQuery, SearchResult, SnippetPart};
+
+const DEFAULT_LIMIT: i64 = 50;
+const MAX_LIMIT: i64 = 200;
+
+// ts_headline marks matches with these control characters so the snippet can be split safely
+const HIGHLIGHT_START: char = '\u{2}';
+const HIGHLIGHT_STOP: char = '\u{3}';
+
+// Handler for full-text search across the user's recordings
+pub async fn search_recordings(
+    State(pool): State<PgPool>,
+    user: TokenAuth<Read>,
+    Query(query): Query<SearchQuery>
+) -> Result<Json<Vec<SearchResult>>, ApiError> {
+    if query.q.trim().is_empty() {
+        return Err(ApiError::BadRequest("Query parameter q must not be empty".to_string()));
+    }
+
+    Ok(Json(search_recordings_inner(pool, user.id, query.q, query.limit).await?))
+}
+
+pub async fn search_recordings_inner(pool: PgPool, owner_id: Uuid, q: String, limit: Option<i64>) -> Result<Vec<SearchResult>, sqlx::Error> {
+    if q.trim().is_empty() {
+        return Ok(Vec::new());
+    }
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).clamp(1, MAX_LIMIT);
+    let headline_options = format!(
+        "StartSel={}, StopSel={}, MaxFragments=2, MaxWords=25, MinWords=8, FragmentDelimiter=\" … \"",
+        HIGHLIGHT_START, HIGHLIGHT_STOP
+    );
+
+    let rows = sqlx::query!(
+        r#"
+        SELECT
+            r.id,
+            '/files/' || r.file_path as "path!",
+            r.filename as "name!",
+            r.transcription_status as "status!",
+            r.transcription_text->>'title' as "title",
+            r.group_id,
+            r.created_at as "created_at!",
+            ts_rank(r.search_vector, query) as "rank!",
+            ts_headline(
+                'english',
+                coalesce(r.transcription_text->>'improved_transcript', r.transcription_text->>'transcript', ''),
+                query,
+                $2
+            ) as "snippet!"
+        FROM recordings r, websearch_to_tsquery('english', $1) query
+        WHERE r.search_vector @@ query
+          AND r.deleted_at IS NULL
+          AND r.owner_id = $4
+        ORDER BY "rank!" DESC, r.created_at DESC
+        LIMIT $3
+        "#,
+        q,
+        headline_options,
+        limit,
+        owner_id
+    )
+    .fetch_all(&pool)
+    .await?;
+
+    Ok(rows
+        .into_iter()
+        .map(|row| SearchResult {
+            id: row.id,
+            path: row.path,
+            name: row.name,
+            status: row.status,
+            title: row.title,
+            group_id: row.group_id,
+            created_at: row.created_at,
+            rank: row.rank,
+            snippet: split_snippet(&row.snippet),
+        })
+        .collect())
+}
+
+fn split_snippet(snippet: &str) -> Vec<SnippetPart> {
+    let mut parts = Vec::new();
+    let mut current = String::new();
+    let mut highlight = false;
+
+    for c in snippet.chars() {
+        if c == HIGHLIGHT_START || c == HIGHLIGHT_STOP {
+            if !current.is_empty() {
+                parts.push(SnippetPart { text: std::mem::take(&mut current), highlight });
+            }
+            highlight = c == HIGHLIGHT_START;
+        } else {
+            current.push(c);
+        }
+    }
+    if !current.is_empty() {
+        parts.push(SnippetPart { text: current, highlight });
+    }
+
+    parts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn part(text: &str, highlight: bool) -> SnippetPart {
+        SnippetPart { text: text.to_string(), highlight }
+    }
+
+    #[test]
+    fn splits_highlighted_matches() {
+        let snippet = "call \u{2}Anna\u{3} about the \u{2}budget\u{3}";
+        assert_eq!(split_snippet(snippet), vec![
+            part("call ", false),
+            part("Anna", true),
+            part(" about the ", false),
+            part("budget", true),
+        ]);
+    }
+
+    #[test]
+    fn snippet_without_matches_is_one_part() {
+        assert_eq!(split_snippet("nothing to see"), vec![part("nothing to see", false)]);
+        assert!(split_snippet("").is_empty());
+    }
+
+    #[test]
+    fn adjacent_markers_leave_no_empty_parts() {
+        let snippet = "\u{2}one\u{3}\u{2}two\u{3}";
+        assert_eq!(split_snippet(snippet), vec![part("one", true), part("two", true)]);
+    }
+}
@@ -0,0 +1,55 @@
+use axum::{
+    extract::{Json, Path, State},
+    http::{HeaderMap, StatusCode},
+    response::Response,
+};
+use sqlx::PgPool;
+use uuid::Uuid;
+use crate::api::error::ApiError;
+use crate::api::files::stream_file;
+use crate::models::dtos::{CreateShareRequest, RecordingShare};
+use crate::service::accounts::AuthUser;
+use crate::service::shares::{create_share, list_shares, revoke_share, share_lifetime, shared_recording};
+use crate::service::storage::SharedStorage;
+
+// Handler to list the links to one of the user's recordings
+pub async fn get_shares(
+    State(pool): State<PgPool>,
+    user: AuthUser,
+    Path(id): Path<Uuid>
+) -> Result<Json<Vec<RecordingShare>>, ApiError> {
+    Ok(Json(list_shares(&pool, user.id, id).await?))
+}
+
+// Handler to create a read-only link to one of the user's recordings
+pub async fn post_share(
+    State(pool): State<PgPool>,
+    user: AuthUser,
+    Path(id): Path<Uuid>,
+    Json(payload): Json<CreateShareRequest>
+) -> Result<(StatusCode, Json<RecordingShare>), ApiError> {
+    let lifetime = share_lifetime(payload.expires_in_days).map_err(ApiError::BadRequest)?;
+    Ok((StatusCode::CREATED, Json(create_share(&pool, user.id, id, lifetime).await?)))
+}
+
+// Handler to revoke a link
+pub async fn delete_share(
+    State(pool): State<PgPool>,
+    user: AuthUser,
+    Path((id, share_id)): Path<(Uuid, Uuid)>
+) -> Result<StatusCode, ApiError> {
+    revoke_share(&pool, user.id, id, share_id).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+// Handler for the audio of a shared recording. It is always streamed through the server, so
+// the storage key and any presigned link stay private; bad and expired links look missing
+pub async fn shared_audio(
+    State(pool): State<PgPool>,
+    State(storage): State<SharedStorage>,
+    Path(token): Path<String>,
+    headers: HeaderMap
+) -> Result<Response, ApiError> {
+    let shared = shared_recording(&pool, &token).await?.ok_or(ApiError::NotFound)?;
+    stream_file(storage.as_ref(), &shared.file_path, &headers).await
+}
@@ -0,0 +1,109 @@
+use axum::extract::{Json, Path, Query, State};
+use sqlx::PgPool;
+use uuid::Uuid;
+use crate::api::auth::{Edit, Read, TokenAuth};
+use crate::api::error::ApiError;
+use crate::models::dtos::{Task, TaskQuery};
+
+// Handler to list action items, open ones by default
+pub async fn list_tasks(
+    State(pool): State<PgPool>,
+    user: TokenAuth<Read>,
+    Query(query): Query<TaskQuery>
+) -> Result<Json<Vec<Task>>, ApiError> {
+    Ok(Json(list_tasks_inner(pool, user.id, query).await?))
+}
+
+pub async fn list_tasks_inner(pool: PgPool, owner_id: Uuid, query: TaskQuery) -> Result<Vec<Task>, ApiError> {
+    let status = query.status.as_deref().unwrap_or("open");
+    if !matches!(status, "open" | "done" | "all") {
+        return Err(ApiError::BadRequest("status must be open, done or all".to_string()));
+    }
+    let assignee = query.assignee.map(|a| a.trim().to_string()).filter(|a| !a.is_empty());
+
+    let tasks = sqlx::query_as!(
+        Task,
+        r#"
+        SELECT
+            t.id,
+            t.recording_id,
+            t.description,
+            t.assignee,
+            t.due_date,
+            t.done_at,
+            t.created_at,
+            r.transcription_text->>'title' as "recording_title",
+            r.group_id
+        FROM tasks t
+        JOIN recordings r ON r.id = t.recording_id
+        WHERE r.deleted_at IS NULL
+          AND r.owner_id = $5
+          AND ($1 = 'all' OR ($1 = 'done') = (t.done_at IS NOT NULL))
+          AND ($2::uuid IS NULL OR t.recording_id = $2)
+          AND ($3::uuid IS NULL OR r.group_id = $3)
+          AND ($4::text IS NULL OR lower(t.assignee) = lower($4))
+        ORDER BY t.done_at DESC NULLS FIRST, t.due_date ASC NULLS LAST, t.created_at ASC
+        "#,
+        status,
+        query.recording_id,
+        query.group_id,
+        assignee,
+        owner_id
+    )
+    .fetch_all(&pool)
+    .await?;
+
+    Ok(tasks)
+}
+
+// Handler to mark a task as done
+pub async fn complete_task(
+    State(pool): State<PgPool>,
+    user: TokenAuth<Edit>,
+    Path(id): Path<Uuid>
+) -> Result<Json<Task>, ApiError> {
+    Ok(Json(set_task_done_inner(pool, user.id, id, true).await?))
+}
+
+// Handler to re-open a finished task
+pub async fn reopen_task(
+    State(pool): State<PgPool>,
+    user: TokenAuth<Edit>,
+    Path(id): Path<Uuid>
+) -> Result<Json<Task>, ApiError> {
+    Ok(Json(set_task_done_inner(pool, user.id, id, false).await?))
+}
+
+pub async fn set_task_done_inner(pool: PgPool, owner_id: Uuid, id: Uuid, done: bool) -> Result<Task, ApiError> {
+    let task = sqlx::query_as!(
+        Task,
+        r#"
+        WITH updated AS (
+            UPDATE tasks
+            SET done_at = CASE WHEN $1 THEN COALESCE(done_at, NOW()) ELSE NULL END
+            WHERE id = $2
+              AND recording_id IN (SELECT id FROM recordings WHERE owner_id = $3)
+            RETURNING *
+        )
+        SELECT
+            t.id as "id!",
+            t.recording_id as "recording_id!",
+            t.description as "description!",
+            t.assignee,
+            t.due_date,
+            t.done_at,
+            t.created_at as "created_at!",
+            r.transcription_text->>'title' as "recording_title",
+            r.group_id
+        FROM updated t
+        JOIN recordings r ON r.id = t.recording_id
+        "#,
+        done,
+        id,
+        owner_id
+    )
+    .fetch_one(&pool)
+    .await?;
+
+    Ok(task)
+}
@@ -0,0 +1,37 @@
+use axum::{
+    extract::{Json, Path, State},
+    http::StatusCode,
+};
+use sqlx::PgPool;
+use uuid::Uuid;
+use crate::api::error::ApiError;
+use crate::models::dtos::{ApiToken, CreateTokenRequest, CreatedToken};
+use crate::service::accounts::AuthUser;
+use crate::service::api_tokens::{create_token, list_tokens, revoke_token};
+
+// Handler to list the user's API tokens
+pub async fn get_tokens(
+    State(pool): State<PgPool>,
+    user: AuthUser
+) -> Result<Json<Vec<ApiToken>>, ApiError> {
+    Ok(Json(list_tokens(&pool, user.id).await?))
+}
+
+// Handler to create an API token; the secret is only in this response
+pub async fn post_token(
+    State(pool): State<PgPool>,
+    user: AuthUser,
+    Json(payload): Json<CreateTokenRequest>
+) -> Result<(StatusCode, Json<CreatedToken>), ApiError> {
+    Ok((StatusCode::CREATED, Json(create_token(&pool, user.id, &payload).await?)))
+}
+
+// Handler to revoke an API token
+pub async fn delete_token(
+    State(pool): State<PgPool>,
+    user: AuthUser,
+    Path(id): Path<Uuid>
+) -> Result<StatusCode, ApiError> {
+    revoke_token(&pool, user.id, id).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
@@ -0,0 +1,352 @@
+use std::io::SeekFrom;
+use axum::{
+    body::{Body, Bytes},
+    extract::{Json, Path, Query, State},
+    http::StatusCode,
+};
+use futures_util::StreamExt;
+use sqlx::PgPool;
+use tokio::io::{AsyncReadExt, AsyncSeekExt, AsyncWriteExt};
+use uuid::Uuid;
+use crate::api::auth::{TokenAuth, Upload};
+use crate::api::error::ApiError;
+use crate::models::dtos::{CaptureTimes, ChunkQuery, CreateUploadRequest, UploadStatus};
+use crate::service::jobs::JobQueue;
+use crate::service::storage::SharedStorage;
+use crate::service::uploads::{
+    discard_stored, file_sha256, find_duplicate, max_upload_bytes, normalize_capture, register_recording, session_part_path,
+    spool_dir, store_upload, upload_session_ttl_hours, upload_target, AudioFormat, NewRecording, Registered, SNIFF_LEN
+};
+use crate::timezone::{local_date, on_day};
+
+// Handler to start a resumable upload
+pub async fn create_upload(
+    State(pool): State<PgPool>,
+    user: TokenAuth<Upload>,
+    Json(payload): Json<CreateUploadRequest>
+) -> Result<(StatusCode, Json<UploadStatus>), ApiError> {
+    let target = upload_target(payload.date.as_deref(), None, user.timezone);
+    tokio::fs::create_dir_all(spool_dir(&target.relative_dir)).await?;
+
+    let session = sqlx::query!(
+        r#"
+        INSERT INTO upload_sessions (relative_dir, recorded_at, original_filename, owner_id, expires_at)
+        VALUES ($1, $2, $3, $4, NOW() + make_interval(hours => $5))
+        RETURNING id
+        "#,
+        target.relative_dir,
+        target.recorded_at,
+        payload.filename,
+        user.id,
+        upload_session_ttl_hours()
+    )
+    .fetch_one(&pool)
+    .await?;
+
+    tokio::fs::File::create(session_part_path(&target.relative_dir, session.id)).await?;
+
+    Ok((StatusCode::CREATED, Json(UploadStatus {
+        id: session.id,
+        offset: 0,
+        status: "OPEN".to_string(),
+        recording_id: None,
+    })))
+}
+
+// Handler to report how much of an upload has arrived
+pub async fn get_upload(
+    State(pool): State<PgPool>,
+    user: TokenAuth<Upload>,
+    Path(id): Path<Uuid>
+) -> Result<Json<UploadStatus>, ApiError> {
+    let status = sqlx::query_as!(
+        UploadStatus,
+        r#"
+        SELECT id, received_bytes as "offset", status, recording_id
+        FROM upload_sessions
+        WHERE id = $1 AND owner_id = $2
+        "#,
+        id,
+        user.id
+    )
+    .fetch_one(&pool)
+    .await?;
+
+    Ok(Json(status))
+}
+
+// Handler to write a chunk at `offset`. Anything already stored past the offset
+// is replaced, so a client can resend a chunk whose response it never saw.
+//
+// No transaction is open while the body arrives: the request claims the session with a short
+// update, streams the chunk, and then advances the offset only if the session still holds the
+// claim and the offset it started from. A newer request for the same session takes the claim over.
+pub async fn put_upload_chunk(
+    State(pool): State<PgPool>,
+    user: TokenAuth<Upload>,
+    Path(id): Path<Uuid>,
+    Query(query): Query<ChunkQuery>,
+    body: Body
+) -> Result<Json<UploadStatus>, ApiError> {
+    let session = sqlx::query!(
+        "SELECT received_bytes, status FROM upload_sessions WHERE id = $1 AND owner_id = $2 AND expires_at > NOW()",
+        id,
+        user.id
+    )
+    .fetch_one(&pool)
+    .await?;
+
+    if session.status != "OPEN" {
+        return Err(ApiError::Conflict("Upload is already finalized".to_string()));
+    }
+    if query.offset < 0 || query.offset > session.received_bytes {
+        return Err(ApiError::Conflict(format!("Expected a chunk at offset {}", session.received_bytes)));
+    }
+
+    let writer_id = Uuid::new_v4();
+    let claimed = sqlx::query!(
+        r#"
+        UPDATE upload_sessions
+        SET writer_id = $2, updated_at = NOW()
+        WHERE id = $1 AND status = 'OPEN' AND received_bytes >= $3
+        RETURNING relative_dir, received_bytes
+        "#,
+        id,
+        writer_id,
+        query.offset
+    )
+    .fetch_optional(&pool)
+    .await?
+    .ok_or_else(changed_meanwhile)?;
+
+    let part = session_part_path(&claimed.relative_dir, id);
+    let end = match write_chunk(&part, query.offset as u64, body).await {
+        Ok(end) => end,
+        Err(e) => {
+            release_claim(&pool, id, writer_id).await;
+            return Err(e);
+        }
+    };
+
+    sqlx::query_as!(
+        UploadStatus,
+        r#"
+        UPDATE upload_sessions
+        SET received_bytes = $1, writer_id = NULL, updated_at = NOW(),
+            expires_at = NOW() + make_interval(hours => $5)
+        WHERE id = $2 AND writer_id = $3 AND received_bytes = $4
+        RETURNING id, received_bytes as "offset", status, recording_id
+        "#,
+        end as i64,
+        id,
+        writer_id,
+        claimed.received_bytes,
+        upload_session_ttl_hours()
+    )
+    .fetch_optional(&pool)
+    .await?
+    .map(Json)
+    .ok_or_else(changed_meanwhile)
+}
+
+fn changed_meanwhile() -> ApiError {
+    ApiError::Conflict("Another request changed this upload; check its offset and resend".to_string())
+}
+
+// Writes a chunk into the partial file at `offset` and returns where it ends
+async fn write_chunk(part: &std::path::Path, offset: u64, body: Body) -> Result<u64, ApiError> {
+    let max_bytes = max_upload_bytes();
+    let mut file = tokio::fs::OpenOptions::new()
+        .write(true)
+        .open(part)
+        .await?;
+    file.set_len(offset).await?;
+    file.seek(SeekFrom::Start(offset)).await?;
+
+    let mut header = Vec::new();
+    let mut end = offset;
+    let mut stream = body.into_data_stream();
+
+    while let Some(chunk) = stream.next().await {
+        let chunk = chunk.map_err(|e| ApiError::BadRequest(e.to_string()))?;
+        end += chunk.len() as u64;
+        if end > max_bytes {
+            return Err(ApiError::PayloadTooLarge(max_bytes));
+        }
+
+        // Reject unknown formats with the first chunk rather than at finalize
+        if offset == 0 && header.len() < SNIFF_LEN {
+            let needed = SNIFF_LEN - header.len();
+            header.extend_from_slice(&chunk[..needed.min(chunk.len())]);
+            if header.len() == SNIFF_LEN {
+                detect_format(&header)?;
+            }
+        }
+
+        file.write_all(&chunk).await?;
+    }
+    file.sync_all().await?;
+    Ok(end)
+}
+
+// Gives up a claim after a failed chunk so finalize isn't blocked by it
+async fn release_claim(pool: &PgPool, id: Uuid, writer_id: Uuid) {
+    let result = sqlx::query!(
+        "UPDATE upload_sessions SET writer_id = NULL WHERE id = $1 AND writer_id = $2",
+        id,
+        writer_id
+    )
+    .execute(pool)
+    .await;
+
+    if let Err(e) = result {
+        eprintln!("Failed to release upload {} after a failed chunk: {}", id, e);
+    }
+}
+
+// Handler to turn a complete upload into a recording. Finalizing twice returns the same recording.
+// The body may carry the capture times as JSON.
+pub async fn finalize_upload(
+    State(pool): State<PgPool>,
+    State(jobs): State<JobQueue>,
+    State(storage): State<SharedStorage>,
+    user: TokenAuth<Upload>,
+    Path(id): Path<Uuid>,
+    body: Bytes
+) -> Result<Json<UploadStatus>, ApiError> {
+    let capture = if body.is_empty() {
+        CaptureTimes::default()
+    } else {
+        serde_json::from_slice(&body).map_err(|e| ApiError::BadRequest(format!("Invalid capture times: {}", e)))?
+    };
+    let capture = normalize_capture(capture).map_err(ApiError::BadRequest)?;
+
+    let mut tx = pool.begin().await?;
+
+    let session = sqlx::query!(
+        r#"
+        SELECT relative_dir, recorded_at, original_filename, received_bytes, status, recording_id,
+            writer_id, expires_at < NOW() as "expired!"
+        FROM upload_sessions
+        WHERE id = $1 AND owner_id = $2
+        FOR UPDATE
+        "#,
+        id,
+        user.id
+    )
+    .fetch_one(&mut *tx)
+    .await?;
+
+    if session.status == "COMPLETED" {
+        return Ok(Json(UploadStatus {
+            id,
+            offset: session.received_bytes,
+            status: session.status,
+            recording_id: session.recording_id,
+        }));
+    }
+
+    if session.expired {
+        return Err(ApiError::NotFound);
+    }
+    if session.writer_id.is_some() {
+        return Err(ApiError::Conflict("A chunk of this upload is still being written".to_string()));
+    }
+
+    // Drop anything a request that lost its claim wrote past the confirmed offset
+    let part = session_part_path(&session.relative_dir, id);
+    tokio::fs::OpenOptions::new()
+        .write(true)
+        .open(&part)
+        .await?
+        .set_len(session.received_bytes as u64)
+        .await?;
+
+    let mut header = Vec::with_capacity(SNIFF_LEN);
+    tokio::fs::File::open(&part)
+        .await?
+        .take(SNIFF_LEN as u64)
+        .read_to_end(&mut header)
+        .await?;
+    let format = detect_format(&header)?;
+    let mime_type = format.mime_type(&header);
+
+    // Chunks can be resent and overwritten, so the checksum is taken from the assembled file
+    let sha256 = file_sha256(&part).await?;
+    if let Some(existing) = find_duplicate(&pool, user.id, &sha256).await? {
+        println!("Skipped duplicate of recording {}", existing);
+        tokio::fs::remove_file(&part).await?;
+        return complete_session(tx, id, existing).await;
+    }
+
+    // If uploaded filename starts with "test_", preserve that prefix for easier cleanup
+    let prefix = match &session.original_filename {
+        Some(name) if name.starts_with("test_") => "test_",
+        _ => "",
+    };
+    let stem = format!("{}recording_{}", prefix, session.recorded_at.timestamp());
+    let filename = store_upload(storage.as_ref(), &part, &session.relative_dir, &stem, format, &mime_type).await?;
+    let filepath_in_db = format!("{}/{}", session.relative_dir, filename);
+    println!("Saved file: {} ({})", filepath_in_db, mime_type);
+
+    // The session fixed the day; the capture start supplies the time of day
+    let tz = user.timezone;
+    let recorded_at = match capture.started_at {
+        Some(started_at) => on_day(local_date(session.recorded_at, tz), started_at, tz),
+        None => session.recorded_at,
+    };
+    let recording = NewRecording {
+        owner_id: user.id,
+        filename: &filename,
+        file_path: &filepath_in_db,
+        mime_type: &mime_type,
+        size_bytes: session.received_bytes,
+        sha256: &sha256,
+        recorded_at,
+        capture,
+        transcribe_at: None,
+    };
+    let recording_id = match register_recording(&pool, &jobs, &recording).await {
+        Ok(Registered::New(recording_id)) => recording_id,
+        Ok(Registered::Duplicate(existing)) => {
+            discard_stored(storage.as_ref(), &filepath_in_db).await;
+            existing
+        }
+        Err(e) => {
+            discard_stored(storage.as_ref(), &filepath_in_db).await;
+            return Err(e.into());
+        }
+    };
+
+    complete_session(tx, id, recording_id).await
+}
+
+async fn complete_session(
+    mut tx: sqlx::Transaction<'_, sqlx::Postgres>,
+    id: Uuid,
+    recording_id: Uuid
+) -> Result<Json<UploadStatus>, ApiError> {
+    let status = sqlx::query_as!(
+        UploadStatus,
+        r#"
+        UPDATE upload_sessions
+        SET status = 'COMPLETED', recording_id = $1, updated_at = NOW()
+        WHERE id = $2
+        RETURNING id, received_bytes as "offset", status, recording_id
+        "#,
+        recording_id,
+        id
+    )
+    .fetch_one(&mut *tx)
+    .await?;
+
+    tx.commit().await?;
+    Ok(Json(status))
+}
+
+/// Checks the start of an upload against the accepted audio formats.
+pub fn detect_format(header: &[u8]) -> Result<AudioFormat, ApiError> {
+    AudioFormat::detect(header).ok_or_else(|| ApiError::UnsupportedMediaType(
+        "Unsupported audio format, expected WebM, Ogg, MP4, WAV, MP3 or FLAC".to_string()
+    ))
+}
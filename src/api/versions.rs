@@ -0,0 +1,140 @@
+use axum::{
+    extract::{State, Path},
+    response::{IntoResponse, Json as AxumJson},
+    http::StatusCode,
+};
+use sqlx::PgPool;
+use uuid::Uuid;
+use crate::api::auth::{Edit, Read, TokenAuth};
+use crate::models::dtos::TranscriptionVersion;
+use crate::service::events::EventBus;
+use crate::service::versions::{save_transcription, VersionSource};
+
+// Handler to list the transcription versions of a recording, newest first
+pub async fn list_versions(
+    State(pool): State<PgPool>,
+    user: TokenAuth<Read>,
+    Path(recording_id): Path<Uuid>
+) -> impl IntoResponse {
+    match list_versions_inner(pool, user.id, recording_id).await {
+        Ok(versions) => AxumJson(versions).into_response(),
+        Err(e) => {
+            eprintln!("Database error: {}", e);
+            StatusCode::INTERNAL_SERVER_ERROR.into_response()
+        }
+    }
+}
+
+pub async fn list_versions_inner(pool: PgPool, owner_id: Uuid, recording_id: Uuid) -> Result<Vec<TranscriptionVersion>, sqlx::Error> {
+    sqlx::query_as!(
+        TranscriptionVersion,
+        r#"
+        SELECT
+            id,
+            recording_id,
+            source,
+            model,
+            prompt_hash,
+            restored_from,
+            diff,
+            created_at,
+            transcription_text as "transcription"
+        FROM transcription_versions
+        WHERE recording_id = $1
+          AND recording_id IN (SELECT id FROM recordings WHERE owner_id = $2)
+        ORDER BY created_at DESC
+        "#,
+        recording_id,
+        owner_id
+    )
+    .fetch_all(&pool)
+    .await
+}
+
+// Handler to view a single version
+pub async fn get_version(
+    State(pool): State<PgPool>,
+    user: TokenAuth<Read>,
+    Path((recording_id, version_id)): Path<(Uuid, Uuid)>
+) -> impl IntoResponse {
+    match get_version_inner(pool, user.id, recording_id, version_id).await {
+        Ok(version) => AxumJson(version).into_response(),
+        Err(sqlx::Error::RowNotFound) => StatusCode::NOT_FOUND.into_response(),
+        Err(e) => {
+            eprintln!("Database error: {}", e);
+            StatusCode::INTERNAL_SERVER_ERROR.into_response()
+        }
+    }
+}
+
+pub async fn get_version_inner(pool: PgPool, owner_id: Uuid, recording_id: Uuid, version_id: Uuid) -> Result<TranscriptionVersion, sqlx::Error> {
+    sqlx::query_as!(
+        TranscriptionVersion,
+        r#"
+        SELECT
+            id,
+            recording_id,
+            source,
+            model,
+            prompt_hash,
+            restored_from,
+            diff,
+            created_at,
+            transcription_text as "transcription"
+        FROM transcription_versions
+        WHERE id = $1 AND recording_id = $2
+          AND recording_id IN (SELECT id FROM recordings WHERE owner_id = $3)
+        "#,
+        version_id,
+        recording_id,
+        owner_id
+    )
+    .fetch_one(&pool)
+    .await
+}
+
+// Handler to make an earlier version current again
+pub async fn restore_version(
+    State(pool): State<PgPool>,
+    State(events): State<EventBus>,
+    user: TokenAuth<Edit>,
+    Path((recording_id, version_id)): Path<(Uuid, Uuid)>
+) -> impl IntoResponse {
+    match restore_version_inner(pool, events, user.id, recording_id, version_id).await {
+        Ok(_) => StatusCode::OK.into_response(),
+        Err(sqlx::Error::RowNotFound) => StatusCode::NOT_FOUND.into_response(),
+        Err(e) => {
+            eprintln!("Database error on restore: {}", e);
+            StatusCode::INTERNAL_SERVER_ERROR.into_response()
+        }
+    }
+}
+
+// Restoring adds a new version, so the restore itself can be undone
+pub async fn restore_version_inner(pool: PgPool, events: EventBus, owner_id: Uuid, recording_id: Uuid, version_id: Uuid) -> Result<(), sqlx::Error> {
+    let mut tx = pool.begin().await?;
+
+    let version = sqlx::query!(
+        r#"
+        SELECT transcription_text FROM transcription_versions
+        WHERE id = $1 AND recording_id = $2
+          AND recording_id IN (SELECT id FROM recordings WHERE owner_id = $3)
+        "#,
+        version_id,
+        recording_id,
+        owner_id
+    )
+    .fetch_one(&mut *tx)
+    .await?;
+
+    save_transcription(
+        &mut tx,
+        recording_id,
+        &version.transcription_text,
+        VersionSource::Restore { from: version_id }
+    ).await?;
+
+    tx.commit().await?;
+    events.recording_changed(&pool, recording_id).await;
+    Ok(())
+}
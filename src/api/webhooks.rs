@@ -0,0 +1,47 @@
+use axum::{
+    extract::{Json, Path, State},
+    http::StatusCode,
+};
+use sqlx::PgPool;
+use uuid::Uuid;
+use crate::api::error::ApiError;
+use crate::models::dtos::{CreateWebhookRequest, CreatedWebhook, Webhook, WebhookDelivery};
+use crate::service::accounts::AuthUser;
+use crate::service::webhooks::{create_webhook, delete_webhook, list_deliveries, list_webhooks, validate_webhook};
+
+// Handler to list the user's webhooks
+pub async fn get_webhooks(
+    State(pool): State<PgPool>,
+    user: AuthUser
+) -> Result<Json<Vec<Webhook>>, ApiError> {
+    Ok(Json(list_webhooks(&pool, user.id).await?))
+}
+
+// Handler to subscribe a URL to recording events; the secret is only in this response
+pub async fn post_webhook(
+    State(pool): State<PgPool>,
+    user: AuthUser,
+    Json(payload): Json<CreateWebhookRequest>
+) -> Result<(StatusCode, Json<CreatedWebhook>), ApiError> {
+    let request = validate_webhook(&payload).await.map_err(ApiError::BadRequest)?;
+    Ok((StatusCode::CREATED, Json(create_webhook(&pool, user.id, &request).await?)))
+}
+
+// Handler to remove a webhook; queued deliveries are dropped with it
+pub async fn remove_webhook(
+    State(pool): State<PgPool>,
+    user: AuthUser,
+    Path(id): Path<Uuid>
+) -> Result<StatusCode, ApiError> {
+    delete_webhook(&pool, user.id, id).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+// Handler to list a webhook's latest deliveries, to see why a receiver isn't getting them
+pub async fn get_deliveries(
+    State(pool): State<PgPool>,
+    user: AuthUser,
+    Path(id): Path<Uuid>
+) -> Result<Json<Vec<WebhookDelivery>>, ApiError> {
+    Ok(Json(list_deliveries(&pool, user.id, id).await?))
+}
@@ -1,59 +1,390 @@
 use leptos::*;
 use leptos_meta::*;
 use leptos_router::*;
-#[cfg(not(feature = "ssr"))]
-use wasm_bindgen::prelude::*;
 use crate::components::*;
-use crate::models::dtos::{RecordingFile, TaskGroup};
+use crate::events::use_recording_events;
+use crate::timezone::{local_date, today, use_timezone, Tz, TimezoneResource};
+use crate::models::dtos::{Account, ApiToken, CreateShareRequest, CreateTokenRequest, CreateWebhookRequest, CreatedToken, CreatedWebhook, DateSpan, Digest, GenerateDigestRequest, RecordingPage, RecordingQuery, RecordingShare, SearchResult, SharedRecording, Task, TaskGroup, TaskQuery, TranscriptionVersion, TrashedRecording, Webhook};
 use uuid::Uuid;
 
+// The history view loads a whole day or week in one page
+const HISTORY_PAGE_SIZE: i64 = 500;
+
+// The signed-in user a server function acts for
+#[cfg(feature = "ssr")]
+async fn auth_user(pool: &sqlx::PgPool) -> Result<crate::service::accounts::AuthUser, ServerFnError> {
+    use crate::api::auth::user_from_headers;
+    use crate::service::accounts::AuthUser;
+    let parts = use_context::<axum::http::request::Parts>()
+        .ok_or_else(|| ServerFnError::new("Request not found"))?;
+
+    // Pages have already been through `require_login`
+    if let Some(user) = parts.extensions.get::<AuthUser>() {
+        return Ok(user.clone());
+    }
+    user_from_headers(pool, &parts.headers).await
+        .map_err(|e| ServerFnError::new(e.to_string()))?
+        .ok_or_else(|| ServerFnError::new("Sign in required"))
+}
+
 #[server(GetRecordings, "/api")]
-pub async fn get_recordings(date: Option<String>) -> Result<Vec<RecordingFile>, ServerFnError> {
+pub async fn get_recordings(query: RecordingQuery) -> Result<RecordingPage, ServerFnError> {
     use crate::api::recordings::list_recordings_inner;
     let pool = use_context::<sqlx::PgPool>()
         .ok_or_else(|| ServerFnError::new("Database pool not found"))?;
+    let user = auth_user(&pool).await?;
 
-    list_recordings_inner(pool, date).await
+    list_recordings_inner(pool, &user, query).await
         .map_err(|e| ServerFnError::new(e.to_string()))
 }
 
 #[server(GetGroups, "/api")]
-pub async fn get_groups() -> Result<Vec<TaskGroup>, ServerFnError> {
+pub async fn get_groups(include_archived: bool) -> Result<Vec<TaskGroup>, ServerFnError> {
     use crate::api::groups::get_groups_inner;
     let pool = use_context::<sqlx::PgPool>()
         .ok_or_else(|| ServerFnError::new("Database pool not found"))?;
+    let user = auth_user(&pool).await?;
+
+    get_groups_inner(pool, user.id, include_archived).await
+        .map_err(|e| ServerFnError::new(e.to_string()))
+}
+
+#[server(CreateGroup, "/api")]
+pub async fn create_group(name: String, description: Option<String>) -> Result<TaskGroup, ServerFnError> {
+    use crate::api::groups::create_group_inner;
+    let pool = use_context::<sqlx::PgPool>()
+        .ok_or_else(|| ServerFnError::new("Database pool not found"))?;
+    let user = auth_user(&pool).await?;
+
+    create_group_inner(pool, user.id, name, description).await
+        .map_err(|e| ServerFnError::new(e.to_string()))
+}
+
+#[server(UpdateGroup, "/api")]
+pub async fn update_group(id: Uuid, name: Option<String>, description: Option<String>) -> Result<TaskGroup, ServerFnError> {
+    use crate::api::groups::update_group_inner;
+    let pool = use_context::<sqlx::PgPool>()
+        .ok_or_else(|| ServerFnError::new("Database pool not found"))?;
+    let user = auth_user(&pool).await?;
+
+    update_group_inner(pool, user.id, id, name, description).await
+        .map_err(|e| ServerFnError::new(e.to_string()))
+}
+
+#[server(SetGroupArchived, "/api")]
+pub async fn set_group_archived(id: Uuid, archived: bool) -> Result<TaskGroup, ServerFnError> {
+    use crate::api::groups::set_group_archived_inner;
+    let pool = use_context::<sqlx::PgPool>()
+        .ok_or_else(|| ServerFnError::new("Database pool not found"))?;
+    let user = auth_user(&pool).await?;
 
-    get_groups_inner(pool).await
+    set_group_archived_inner(pool, user.id, id, archived).await
+        .map_err(|e| ServerFnError::new(e.to_string()))
+}
+
+#[server(ReorderGroups, "/api")]
+pub async fn reorder_groups(ids: Vec<Uuid>) -> Result<Vec<TaskGroup>, ServerFnError> {
+    use crate::api::groups::reorder_groups_inner;
+    let pool = use_context::<sqlx::PgPool>()
+        .ok_or_else(|| ServerFnError::new("Database pool not found"))?;
+    let user = auth_user(&pool).await?;
+
+    reorder_groups_inner(pool, user.id, ids).await
+        .map_err(|e| ServerFnError::new(e.to_string()))
+}
+
+#[server(DeleteGroup, "/api")]
+pub async fn delete_group(id: Uuid, reassign_to: Option<Uuid>) -> Result<(), ServerFnError> {
+    use crate::api::groups::delete_group_inner;
+    use crate::service::events::EventBus;
+    let pool = use_context::<sqlx::PgPool>()
+        .ok_or_else(|| ServerFnError::new("Database pool not found"))?;
+    let user = auth_user(&pool).await?;
+    let events = use_context::<EventBus>()
+        .ok_or_else(|| ServerFnError::new("Event bus not found"))?;
+
+    delete_group_inner(pool, events, user.id, id, reassign_to).await
         .map_err(|e| ServerFnError::new(e.to_string()))
 }
 
 #[server(UpdateRecordingGroup, "/api")]
 pub async fn update_recording_group(id: Uuid, group_id: Option<Uuid>) -> Result<(), ServerFnError> {
     use crate::api::recordings::update_recording_inner;
+    use crate::service::events::EventBus;
     let pool = use_context::<sqlx::PgPool>()
         .ok_or_else(|| ServerFnError::new("Database pool not found"))?;
+    let user = auth_user(&pool).await?;
+    let events = use_context::<EventBus>()
+        .ok_or_else(|| ServerFnError::new("Event bus not found"))?;
 
-    update_recording_inner(pool, id, group_id).await
+    update_recording_inner(pool, events, user.id, id, group_id).await
         .map_err(|e| ServerFnError::new(e.to_string()))
 }
 
 #[server(UpdateRecordingTitle, "/api")]
 pub async fn update_recording_title(id: Uuid, title: String) -> Result<(), ServerFnError> {
     use crate::api::recordings::update_recording_title_inner;
+    use crate::service::events::EventBus;
+    let pool = use_context::<sqlx::PgPool>()
+        .ok_or_else(|| ServerFnError::new("Database pool not found"))?;
+    let user = auth_user(&pool).await?;
+    let events = use_context::<EventBus>()
+        .ok_or_else(|| ServerFnError::new("Event bus not found"))?;
+
+    update_recording_title_inner(pool, events, user.id, id, title).await
+        .map_err(|e| ServerFnError::new(e.to_string()))
+}
+
+#[server(RetranscribeRecording, "/api")]
+pub async fn retranscribe_recording(id: Uuid) -> Result<(), ServerFnError> {
+    use crate::api::recordings::retranscribe_recording_inner;
+    use crate::service::jobs::JobQueue;
+    let pool = use_context::<sqlx::PgPool>()
+        .ok_or_else(|| ServerFnError::new("Database pool not found"))?;
+    let user = auth_user(&pool).await?;
+    let jobs = use_context::<JobQueue>()
+        .ok_or_else(|| ServerFnError::new("Job queue not found"))?;
+
+    retranscribe_recording_inner(pool, jobs, user.id, id).await
+        .map_err(|e| ServerFnError::new(e.to_string()))
+}
+
+#[server(CreateRecordingShare, "/api")]
+pub async fn create_recording_share(id: Uuid, request: CreateShareRequest) -> Result<RecordingShare, ServerFnError> {
+    use crate::service::shares::{create_share, share_lifetime};
     let pool = use_context::<sqlx::PgPool>()
         .ok_or_else(|| ServerFnError::new("Database pool not found"))?;
+    let user = auth_user(&pool).await?;
+    let lifetime = share_lifetime(request.expires_in_days).map_err(ServerFnError::new)?;
 
-    update_recording_title_inner(pool, id, title).await
+    create_share(&pool, user.id, id, lifetime).await
+        .map_err(|e| ServerFnError::new(e.to_string()))
+}
+
+// Public: the token is the only credential a share page has, so `require_login` lets it through
+#[server(GetSharedRecording, "/api")]
+pub async fn get_shared_recording(token: String) -> Result<Option<SharedRecording>, ServerFnError> {
+    use crate::service::shares::shared_recording;
+    let pool = use_context::<sqlx::PgPool>()
+        .ok_or_else(|| ServerFnError::new("Database pool not found"))?;
+
+    let shared = shared_recording(&pool, &token).await
+        .map_err(|e| ServerFnError::new(e.to_string()))?;
+    Ok(shared.map(|shared| shared.recording))
+}
+
+#[server(GetTranscriptionVersions, "/api")]
+pub async fn get_transcription_versions(recording_id: Uuid) -> Result<Vec<TranscriptionVersion>, ServerFnError> {
+    use crate::api::versions::list_versions_inner;
+    let pool = use_context::<sqlx::PgPool>()
+        .ok_or_else(|| ServerFnError::new("Database pool not found"))?;
+    let user = auth_user(&pool).await?;
+
+    list_versions_inner(pool, user.id, recording_id).await
+        .map_err(|e| ServerFnError::new(e.to_string()))
+}
+
+#[server(GetTranscriptionVersion, "/api")]
+pub async fn get_transcription_version(recording_id: Uuid, version_id: Uuid) -> Result<TranscriptionVersion, ServerFnError> {
+    use crate::api::versions::get_version_inner;
+    let pool = use_context::<sqlx::PgPool>()
+        .ok_or_else(|| ServerFnError::new("Database pool not found"))?;
+    let user = auth_user(&pool).await?;
+
+    get_version_inner(pool, user.id, recording_id, version_id).await
+        .map_err(|e| ServerFnError::new(e.to_string()))
+}
+
+#[server(RestoreTranscriptionVersion, "/api")]
+pub async fn restore_transcription_version(recording_id: Uuid, version_id: Uuid) -> Result<(), ServerFnError> {
+    use crate::api::versions::restore_version_inner;
+    use crate::service::events::EventBus;
+    let pool = use_context::<sqlx::PgPool>()
+        .ok_or_else(|| ServerFnError::new("Database pool not found"))?;
+    let user = auth_user(&pool).await?;
+    let events = use_context::<EventBus>()
+        .ok_or_else(|| ServerFnError::new("Event bus not found"))?;
+
+    restore_version_inner(pool, events, user.id, recording_id, version_id).await
+        .map_err(|e| ServerFnError::new(e.to_string()))
+}
+
+#[server(SearchRecordings, "/api")]
+pub async fn search_recordings(q: String) -> Result<Vec<SearchResult>, ServerFnError> {
+    use crate::api::search::search_recordings_inner;
+    let pool = use_context::<sqlx::PgPool>()
+        .ok_or_else(|| ServerFnError::new("Database pool not found"))?;
+    let user = auth_user(&pool).await?;
+
+    search_recordings_inner(pool, user.id, q, None).await
+        .map_err(|e| ServerFnError::new(e.to_string()))
+}
+
+#[server(GetTasks, "/api")]
+pub async fn get_tasks(query: TaskQuery) -> Result<Vec<Task>, ServerFnError> {
+    use crate::api::tasks::list_tasks_inner;
+    let pool = use_context::<sqlx::PgPool>()
+        .ok_or_else(|| ServerFnError::new("Database pool not found"))?;
+    let user = auth_user(&pool).await?;
+
+    list_tasks_inner(pool, user.id, query).await
+        .map_err(|e| ServerFnError::new(e.to_string()))
+}
+
+#[server(SetTaskDone, "/api")]
+pub async fn set_task_done(id: Uuid, done: bool) -> Result<Task, ServerFnError> {
+    use crate::api::tasks::set_task_done_inner;
+    let pool = use_context::<sqlx::PgPool>()
+        .ok_or_else(|| ServerFnError::new("Database pool not found"))?;
+    let user = auth_user(&pool).await?;
+
+    set_task_done_inner(pool, user.id, id, done).await
+        .map_err(|e| ServerFnError::new(e.to_string()))
+}
+
+#[server(GetDigests, "/api")]
+pub async fn get_digests(kind: Option<String>) -> Result<Vec<Digest>, ServerFnError> {
+    use crate::api::digests::list_digests_inner;
+    let pool = use_context::<sqlx::PgPool>()
+        .ok_or_else(|| ServerFnError::new("Database pool not found"))?;
+    let user = auth_user(&pool).await?;
+
+    list_digests_inner(pool, user.id, kind, None).await
+        .map_err(|e| ServerFnError::new(e.to_string()))
+}
+
+#[server(GenerateDigest, "/api")]
+pub async fn generate_digest(request: GenerateDigestRequest) -> Result<Digest, ServerFnError> {
+    use crate::api::digests::generate_digest_inner;
+    let pool = use_context::<sqlx::PgPool>()
+        .ok_or_else(|| ServerFnError::new("Database pool not found"))?;
+    let user = auth_user(&pool).await?;
+
+    generate_digest_inner(pool, &user, request).await
         .map_err(|e| ServerFnError::new(e.to_string()))
 }
 
 #[server(DeleteRecording, "/api")]
 pub async fn delete_recording(id: Uuid) -> Result<(), ServerFnError> {
     use crate::api::recordings::delete_recording_by_id_inner;
+    use crate::service::events::EventBus;
     let pool = use_context::<sqlx::PgPool>()
         .ok_or_else(|| ServerFnError::new("Database pool not found"))?;
+    let user = auth_user(&pool).await?;
+    let events = use_context::<EventBus>()
+        .ok_or_else(|| ServerFnError::new("Event bus not found"))?;
 
-    delete_recording_by_id_inner(pool, id).await
+    delete_recording_by_id_inner(pool, events, user.id, id).await
+        .map_err(|e| ServerFnError::new(e.to_string()))
+}
+
+#[server(GetTrash, "/api")]
+pub async fn get_trash() -> Result<Vec<TrashedRecording>, ServerFnError> {
+    use crate::api::recordings::list_trash_inner;
+    let pool = use_context::<sqlx::PgPool>()
+        .ok_or_else(|| ServerFnError::new("Database pool not found"))?;
+    let user = auth_user(&pool).await?;
+
+    list_trash_inner(pool, user.id).await
+        .map_err(|e| ServerFnError::new(e.to_string()))
+}
+
+#[server(RestoreRecording, "/api")]
+pub async fn restore_recording(id: Uuid) -> Result<(), ServerFnError> {
+    use crate::api::recordings::restore_recording_inner;
+    use crate::service::events::EventBus;
+    let pool = use_context::<sqlx::PgPool>()
+        .ok_or_else(|| ServerFnError::new("Database pool not found"))?;
+    let user = auth_user(&pool).await?;
+    let events = use_context::<EventBus>()
+        .ok_or_else(|| ServerFnError::new("Event bus not found"))?;
+
+    restore_recording_inner(pool, events, user.id, id).await
+        .map_err(|e| ServerFnError::new(e.to_string()))
+}
+
+#[server(GetTimezone, "/api")]
+pub async fn get_timezone() -> Result<String, ServerFnError> {
+    let pool = use_context::<sqlx::PgPool>()
+        .ok_or_else(|| ServerFnError::new("Database pool not found"))?;
+    let user = auth_user(&pool).await?;
+
+    Ok(user.timezone.name().to_string())
+}
+
+#[server(GetAccount, "/api")]
+pub async fn get_account() -> Result<Account, ServerFnError> {
+    let pool = use_context::<sqlx::PgPool>()
+        .ok_or_else(|| ServerFnError::new("Database pool not found"))?;
+    let user = auth_user(&pool).await?;
+
+    Ok(user.account())
+}
+
+#[server(GetApiTokens, "/api")]
+pub async fn get_api_tokens() -> Result<Vec<ApiToken>, ServerFnError> {
+    use crate::service::api_tokens::list_tokens;
+    let pool = use_context::<sqlx::PgPool>()
+        .ok_or_else(|| ServerFnError::new("Database pool not found"))?;
+    let user = auth_user(&pool).await?;
+
+    list_tokens(&pool, user.id).await
+        .map_err(|e| ServerFnError::new(e.to_string()))
+}
+
+#[server(CreateApiToken, "/api")]
+pub async fn create_api_token(request: CreateTokenRequest) -> Result<CreatedToken, ServerFnError> {
+    use crate::service::api_tokens::create_token;
+    let pool = use_context::<sqlx::PgPool>()
+        .ok_or_else(|| ServerFnError::new("Database pool not found"))?;
+    let user = auth_user(&pool).await?;
+
+    create_token(&pool, user.id, &request).await
+        .map_err(|e| ServerFnError::new(e.to_string()))
+}
+
+#[server(RevokeApiToken, "/api")]
+pub async fn revoke_api_token(id: Uuid) -> Result<(), ServerFnError> {
+    use crate::service::api_tokens::revoke_token;
+    let pool = use_context::<sqlx::PgPool>()
+        .ok_or_else(|| ServerFnError::new("Database pool not found"))?;
+    let user = auth_user(&pool).await?;
+
+    revoke_token(&pool, user.id, id).await
+        .map_err(|e| ServerFnError::new(e.to_string()))
+}
+
+#[server(GetWebhooks, "/api")]
+pub async fn get_webhooks() -> Result<Vec<Webhook>, ServerFnError> {
+    use crate::service::webhooks::list_webhooks;
+    let pool = use_context::<sqlx::PgPool>()
+        .ok_or_else(|| ServerFnError::new("Database pool not found"))?;
+    let user = auth_user(&pool).await?;
+
+    list_webhooks(&pool, user.id).await
+        .map_err(|e| ServerFnError::new(e.to_string()))
+}
+
+#[server(CreateWebhook, "/api")]
+pub async fn create_webhook(request: CreateWebhookRequest) -> Result<CreatedWebhook, ServerFnError> {
+    use crate::service::webhooks::{create_webhook, validate_webhook};
+    let pool = use_context::<sqlx::PgPool>()
+        .ok_or_else(|| ServerFnError::new("Database pool not found"))?;
+    let user = auth_user(&pool).await?;
+
+    let request = validate_webhook(&request).await.map_err(ServerFnError::new)?;
+    create_webhook(&pool, user.id, &request).await
+        .map_err(|e| ServerFnError::new(e.to_string()))
+}
+
+#[server(DeleteWebhook, "/api")]
+pub async fn delete_webhook(id: Uuid) -> Result<(), ServerFnError> {
+    use crate::service::webhooks::delete_webhook;
+    let pool = use_context::<sqlx::PgPool>()
+        .ok_or_else(|| ServerFnError::new("Database pool not found"))?;
+    let user = auth_user(&pool).await?;
+
+    delete_webhook(&pool, user.id, id).await
         .map_err(|e| ServerFnError::new(e.to_string()))
 }
 
@@ -61,6 +392,10 @@ pub async fn delete_recording(id: Uuid) -> Result<(), ServerFnError> {
 pub fn App() -> impl IntoView {
   provide_meta_context();
 
+  // Days and times are shown in the user's configured timezone, not the browser's
+  let timezone: TimezoneResource = create_resource(|| (), |_| async move { get_timezone().await });
+  provide_context(timezone);
+
   view! {
     <Stylesheet id="leptos" href="/pkg/workflow.css"/>
     <Title text="Workflow"/>
@@ -70,7 +405,15 @@ pub fn App() -> impl IntoView {
         <Sidebar/>
         <main class="main-content">
           <Routes>
+            <Route path="/login" view=LoginPage/>
             <Route path="" view=HomePage/>
+            <Route path="/search" view=SearchPage/>
+            <Route path="/action-items" view=TaskList/>
+            <Route path="/review" view=DigestPage/>
+            <Route path="/task-groups" view=GroupManager/>
+            <Route path="/trash" view=TrashPage/>
+            <Route path="/settings" view=SettingsPage/>
+            <Route path="/share/:token" view=SharePage/>
             <Route path="/*any" view=NotFound/>
           </Routes>
         </main>
@@ -81,23 +424,76 @@ pub fn App() -> impl IntoView {
 
 #[component]
 fn Sidebar() -> impl IntoView {
+  // Nothing to navigate to before signing in, or for visitors of a shared recording
+  let location = use_location();
+  let signed_out = move || location.pathname.with(|path| path == "/login" || path.starts_with("/share/"));
+
   view! {
-    <div class="sidebar">
-      <div class="sidebar-icon" title="Explorer">
-        <svg xmlns="http://www.w3.org/2000/svg" width="24" height="24" viewBox="0 0 24 24" fill="none"
-          stroke="currentColor" stroke-width="2" stroke-linecap="round" stroke-linejoin="round">
-          <path d="M13 2H6a2 2 0 0 0-2 2v16a2 2 0 0 0 2 2h12a2 2 0 0 0 2-2V9z"></path>
-          <polyline points="13 2 13 9 20 9"></polyline>
-        </svg>
+    <Show when=move || !signed_out()>
+      <div class="sidebar">
+        <a href="/" class="sidebar-icon" title="Explorer">
+          <svg xmlns="http://www.w3.org/2000/svg" width="24" height="24" viewBox="0 0 24 24" fill="none"
+            stroke="currentColor" stroke-width="2" stroke-linecap="round" stroke-linejoin="round">
+            <path d="M13 2H6a2 2 0 0 0-2 2v16a2 2 0 0 0 2 2h12a2 2 0 0 0 2-2V9z"></path>
+            <polyline points="13 2 13 9 20 9"></polyline>
+          </svg>
+        </a>
+        <a href="/search" class="sidebar-icon" title="Search">
+          <svg xmlns="http://www.w3.org/2000/svg" width="24" height="24" viewBox="0 0 24 24" fill="none"
+            stroke="currentColor" stroke-width="2" stroke-linecap="round" stroke-linejoin="round">
+            <circle cx="11" cy="11" r="8"></circle>
+            <line x1="21" y1="21" x2="16.65" y2="16.65"></line>
+          </svg>
+        </a>
+        <a href="/action-items" class="sidebar-icon" title="Action Items">
+          <svg xmlns="http://www.w3.org/2000/svg" width="24" height="24" viewBox="0 0 24 24" fill="none"
+            stroke="currentColor" stroke-width="2" stroke-linecap="round" stroke-linejoin="round">
+            <polyline points="9 11 12 14 22 4"></polyline>
+            <path d="M21 12v7a2 2 0 0 1-2 2H5a2 2 0 0 1-2-2V5a2 2 0 0 1 2-2h11"></path>
+          </svg>
+        </a>
+        <a href="/review" class="sidebar-icon" title="Digests">
+          <svg xmlns="http://www.w3.org/2000/svg" width="24" height="24" viewBox="0 0 24 24" fill="none"
+            stroke="currentColor" stroke-width="2" stroke-linecap="round" stroke-linejoin="round">
+            <line x1="18" y1="20" x2="18" y2="10"></line>
+            <line x1="12" y1="20" x2="12" y2="4"></line>
+            <line x1="6" y1="20" x2="6" y2="14"></line>
+          </svg>
+        </a>
+        <a href="/task-groups" class="sidebar-icon" title="Task Groups">
+          <svg xmlns="http://www.w3.org/2000/svg" width="24" height="24" viewBox="0 0 24 24" fill="none"
+            stroke="currentColor" stroke-width="2" stroke-linecap="round" stroke-linejoin="round">
+            <polygon points="12 2 2 7 12 12 22 7 12 2"></polygon>
+            <polyline points="2 17 12 22 22 17"></polyline>
+            <polyline points="2 12 12 17 22 12"></polyline>
+          </svg>
+        </a>
+        <a href="/trash" class="sidebar-icon" title="Trash">
+          <svg xmlns="http://www.w3.org/2000/svg" width="24" height="24" viewBox="0 0 24 24" fill="none"
+            stroke="currentColor" stroke-width="2" stroke-linecap="round" stroke-linejoin="round">
+            <polyline points="3 6 5 6 21 6"></polyline>
+            <path d="M19 6v14a2 2 0 0 1-2 2H7a2 2 0 0 1-2-2V6m3 0V4a2 2 0 0 1 2-2h4a2 2 0 0 1 2 2v2"></path>
+          </svg>
+        </a>
+        <a href="/settings" class="sidebar-icon" title="Settings">
+          <svg xmlns="http://www.w3.org/2000/svg" width="24" height="24" viewBox="0 0 24 24" fill="none"
+            stroke="currentColor" stroke-width="2" stroke-linecap="round" stroke-linejoin="round">
+            <circle cx="12" cy="12" r="3"></circle>
+            <path d="M19.4 15a1.65 1.65 0 0 0 .33 1.82l.06.06a2 2 0 0 1-2.83 2.83l-.06-.06a1.65 1.65 0 0 0-1.82-.33 1.65 1.65 0 0 0-1 1.51V21a2 2 0 0 1-4 0v-.09A1.65 1.65 0 0 0 9 19.4a1.65 1.65 0 0 0-1.82.33l-.06.06a2 2 0 0 1-2.83-2.83l.06-.06a1.65 1.65 0 0 0 .33-1.82 1.65 1.65 0 0 0-1.51-1H3a2 2 0 0 1 0-4h.09A1.65 1.65 0 0 0 4.6 9a1.65 1.65 0 0 0-.33-1.82l-.06-.06a2 2 0 0 1 2.83-2.83l.06.06a1.65 1.65 0 0 0 1.82.33H9a1.65 1.65 0 0 0 1-1.51V3a2 2 0 0 1 4 0v.09a1.65 1.65 0 0 0 1 1.51 1.65 1.65 0 0 0 1.82-.33l.06-.06a2 2 0 0 1 2.83 2.83l-.06.06a1.65 1.65 0 0 0-.33 1.82V9a1.65 1.65 0 0 0 1.51 1H21a2 2 0 0 1 0 4h-.09a1.65 1.65 0 0 0-1.51 1z"></path>
+          </svg>
+        </a>
+        <form method="post" action="/logout" class="sidebar-signout">
+          <button type="submit" class="sidebar-icon" title="Sign out">
+            <svg xmlns="http://www.w3.org/2000/svg" width="24" height="24" viewBox="0 0 24 24" fill="none"
+              stroke="currentColor" stroke-width="2" stroke-linecap="round" stroke-linejoin="round">
+              <path d="M9 21H5a2 2 0 0 1-2-2V5a2 2 0 0 1 2-2h4"></path>
+              <polyline points="16 17 21 12 16 7"></polyline>
+              <line x1="21" y1="12" x2="9" y2="12"></line>
+            </svg>
+          </button>
+        </form>
       </div>
-      <div class="sidebar-icon" title="Search">
-        <svg xmlns="http://www.w3.org/2000/svg" width="24" height="24" viewBox="0 0 24 24" fill="none"
-          stroke="currentColor" stroke-width="2" stroke-linecap="round" stroke-linejoin="round">
-          <circle cx="11" cy="11" r="8"></circle>
-          <line x1="21" y1="21" x2="16.65" y2="16.65"></line>
-        </svg>
-      </div>
-    </div>
+    </Show>
   }
 }
 
@@ -105,19 +501,22 @@ fn Sidebar() -> impl IntoView {
 fn HomePage() -> impl IntoView {
   let (view_history, set_view_history) = create_signal(false);
   let (selected_date, set_selected_date) = create_signal(None::<String>);
+  let (selected_span, set_selected_span) = create_signal(DateSpan::Day);
+
+  let timezone = use_timezone();
 
   let recordings_resource = create_resource(
-    move || selected_date.get(),
-    |date| async move { get_recordings(date).await }
+    move || (selected_date.get(), selected_span.get(), timezone.get()),
+    |(date, span, tz)| async move { get_recordings(history_query(date, span, tz)).await }
   );
 
   let groups_resource = create_resource(
     || (),
-    |_| async move { get_groups().await }
+    |_| async move { get_groups(true).await }
   );
 
   let recordings = Signal::derive(move || {
-    recordings_resource.get().and_then(|res| res.ok()).unwrap_or_default()
+    recordings_resource.get().and_then(|res| res.ok()).map(|page| page.items).unwrap_or_default()
   });
   let groups = Signal::derive(move || {
     groups_resource.get().and_then(|res| res.ok()).unwrap_or_default()
@@ -126,48 +525,24 @@ fn HomePage() -> impl IntoView {
   let update_group_action = create_server_action::<UpdateRecordingGroup>();
   let update_title_action = create_server_action::<UpdateRecordingTitle>();
   let delete_rec_action = create_server_action::<DeleteRecording>();
+  let retranscribe_action = create_server_action::<RetranscribeRecording>();
+  let share_action = create_server_action::<CreateRecordingShare>();
 
   // Refresh resources when actions complete
   create_effect(move |_| {
     if update_group_action.version().get() > 0
       || delete_rec_action.version().get() > 0
       || update_title_action.version().get() > 0
+      || retranscribe_action.version().get() > 0
     {
       recordings_resource.refetch();
     }
   });
 
-  // Polling for pending transcriptions
-  create_effect(move |prev_handle: Option<Option<i32>>| {
-    // Clear previous interval if valid
-    if let Some(_handle) = prev_handle.flatten() {
-        #[cfg(not(feature = "ssr"))]
-        web_sys::window().unwrap().clear_interval_with_handle(_handle);
-    }
-
-    let has_pending = recordings.get().iter().any(|r| r.status == "PENDING");
-
-    if has_pending {
-        #[cfg(not(feature = "ssr"))]
-        {
-            let callback = Closure::wrap(Box::new(move || {
-                recordings_resource.refetch();
-            }) as Box<dyn FnMut()>);
-
-            let handle = web_sys::window().unwrap()
-                .set_interval_with_callback_and_timeout_and_arguments_0(
-                    callback.as_ref().unchecked_ref(),
-                    3000
-                );
-
-            callback.forget(); // Leak closure so it persists for interval
-
-            if let Ok(h) = handle {
-                return Some(h);
-            }
-        }
-    }
-    None
+  // Status changes, uploads and deletions, also those made in other tabs
+  use_recording_events(recordings_resource, move |rec| {
+    let (from, to) = history_days(selected_date.get_untracked(), selected_span.get_untracked(), timezone.get_untracked());
+    (from..=to).contains(&local_date(rec.created_at, timezone.get_untracked()))
   });
 
   view! {
@@ -211,10 +586,23 @@ fn HomePage() -> impl IntoView {
               on_success=Callback::new(move |_| recordings_resource.refetch())
             />
           </div>
-          <DateFilter on_change=move |date| {
-            set_selected_date.set(Some(date));
-          }/>
+          <DateFilter
+            on_change=move |date| set_selected_date.set(Some(date))
+            on_span_change=move |span| set_selected_span.set(span)
+          />
         </div>
+        {move || share_action.value().get().map(|res| match res {
+          Err(e) => view! { <p class="text-red-600 mb-2">{e.to_string()}</p> }.into_view(),
+          Ok(share) => {
+            let expires = move || share.expires_at.with_timezone(&timezone.get()).format("%b %-d, %Y").to_string();
+            view! {
+              <div class="p-2 border rounded-md mb-2 text-sm">
+                "Read-only link, valid until " {expires} ": "
+                <a href=share.url.clone() class="select-all">{share.url.clone()}</a>
+              </div>
+            }.into_view()
+          }
+        })}
         <div id="recordingsList">
           <Transition fallback=move || view! { <p>"Loading recordings..."</p> }>
             <RecordingList
@@ -226,10 +614,16 @@ fn HomePage() -> impl IntoView {
               on_title_change=Callback::new(move |(rec_id, title)| {
                 update_title_action.dispatch(UpdateRecordingTitle { id: rec_id, title });
               })
+              on_retranscribe=Callback::new(move |id| {
+                retranscribe_action.dispatch(RetranscribeRecording { id });
+              })
+              on_share=Callback::new(move |id| {
+                share_action.dispatch(CreateRecordingShare { id, request: CreateShareRequest::default() });
+              })
               on_delete=Callback::new(move |id| {
                 #[cfg(not(feature = "ssr"))]
                 {
-                  if web_sys::window().unwrap().confirm_with_message("Delete this recording?").unwrap() {
+                  if web_sys::window().unwrap().confirm_with_message("Move this recording to the trash?").unwrap() {
                     delete_rec_action.dispatch(DeleteRecording { id });
                   }
                 }
@@ -246,6 +640,27 @@ fn HomePage() -> impl IntoView {
   }
 }
 
+// The page of recordings the history view loads for the picked days
+fn history_query(date: Option<String>, span: DateSpan, tz: Tz) -> RecordingQuery {
+  let (from, to) = history_days(date, span, tz);
+
+  RecordingQuery {
+    from: Some(from.format("%Y-%m-%d").to_string()),
+    to: Some(to.format("%Y-%m-%d").to_string()),
+    limit: Some(HISTORY_PAGE_SIZE),
+    ..Default::default()
+  }
+}
+
+// One day, or the Monday-to-Sunday week around the picked date (today in `tz` by default)
+fn history_days(date: Option<String>, span: DateSpan, tz: Tz) -> (chrono::NaiveDate, chrono::NaiveDate) {
+  let day = date
+    .as_deref()
+    .and_then(|d| chrono::NaiveDate::parse_from_str(d, "%Y-%m-%d").ok())
+    .unwrap_or_else(|| today(tz));
+  span.bounds(day)
+}
+
 #[component]
 fn NotFound() -> impl IntoView {
     #[cfg(feature = "ssr")]
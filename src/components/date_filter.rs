@@ -1,37 +1,38 @@
 use leptos::*;
+use crate::models::dtos::DateSpan;
+use crate::timezone::{today, use_timezone};
 
 #[component]
 pub fn DateFilter(
-    #[prop(into)] on_change: Callback<String>
+    #[prop(into)] on_change: Callback<String>,
+    #[prop(optional, into)] on_span_change: Option<Callback<DateSpan>>
 ) -> impl IntoView {
-    let today = || {
-        #[cfg(not(feature = "ssr"))]
-        {
-            js_sys::Date::new_0()
-                .to_iso_string()
-                .as_string()
-                .unwrap_or_default()
-                .split('T')
-                .next()
-                .unwrap_or_default()
-                .to_string()
-        }
-        #[cfg(feature = "ssr")]
-        {
-            "".to_string()
-        }
-    };
-
-    let (date, set_date) = create_signal(today());
+    // Shows today in the configured timezone until a date is picked
+    let timezone = use_timezone();
+    let (picked, set_picked) = create_signal(None::<String>);
+    let date = move || picked.get().unwrap_or_else(|| today(timezone.get()).format("%Y-%m-%d").to_string());
 
     let handle_change = move |ev| {
         let val = event_target_value(&ev);
-        set_date.set(val.clone());
+        set_picked.set(Some(val.clone()));
         on_change.call(val);
     };
 
+    let handle_span_change = move |ev| {
+        let span = if event_target_value(&ev) == "week" { DateSpan::Week } else { DateSpan::Day };
+        if let Some(on_span_change) = on_span_change {
+            on_span_change.call(span);
+        }
+    };
+
     view! {
         <div class="flex items-center text-sm gap-2">
+            <Show when=move || on_span_change.is_some()>
+                <select class="p-2 border rounded-md" on:change=handle_span_change>
+                    <option value="day" selected=true>"Day"</option>
+                    <option value="week">"Week"</option>
+                </select>
+            </Show>
             <label for="dateFilter">"Date:"</label>
             <input
                 type="date"
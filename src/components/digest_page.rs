@@ -0,0 +1,193 @@
+use leptos::*;
+use uuid::Uuid;
+use crate::app::{get_digests, GenerateDigest};
+use crate::models::dtos::{Digest, GenerateDigestRequest};
+use crate::timezone::use_timezone;
+
+#[component]
+pub fn DigestPage() -> impl IntoView {
+  let (kind, set_kind) = create_signal("day".to_string());
+  let (date, set_date) = create_signal(String::new());
+  let (selected, set_selected) = create_signal(None::<Uuid>);
+  let generate_action = create_server_action::<GenerateDigest>();
+
+  let digests_resource = create_resource(
+    move || generate_action.version().get(),
+    |_| async move { get_digests(None).await }
+  );
+
+  // Show a freshly generated digest right away
+  create_effect(move |_| {
+    if let Some(Ok(digest)) = generate_action.value().get() {
+      set_selected.set(Some(digest.id));
+    }
+  });
+
+  let digests = Signal::derive(move || {
+    digests_resource.get().and_then(|res| res.ok()).unwrap_or_default()
+  });
+  let current = move || {
+    let digests = digests.get();
+    match selected.get() {
+      Some(id) => digests.into_iter().find(|d| d.id == id),
+      None => digests.into_iter().next(),
+    }
+  };
+
+  let error = move || generate_action.value().get().and_then(|r| r.err()).map(|e| e.to_string());
+
+  view! {
+    <div id="digestSection">
+      <h2 class="text-lg mb-2">"Digests"</h2>
+      <div class="flex items-center gap-2 mb-2 pb-2 border-b">
+        <select class="p-2 border rounded-md" on:change=move |ev| set_kind.set(event_target_value(&ev))>
+          <option value="day" selected=true>"Day"</option>
+          <option value="week">"Week"</option>
+        </select>
+        <input
+          type="date"
+          class="p-2 border rounded-md"
+          prop:value=date
+          on:change=move |ev| set_date.set(event_target_value(&ev))
+        />
+        <button
+          class="btn rounded-md"
+          disabled=move || generate_action.pending().get()
+          on:click=move |_| {
+            let date = Some(date.get()).filter(|d| !d.is_empty());
+            generate_action.dispatch(GenerateDigest { request: GenerateDigestRequest { kind: kind.get(), date } });
+          }
+        >
+          "Generate"
+        </button>
+      </div>
+      <Show when=move || error().is_some()>
+        <p class="text-red-600 mb-2">{error}</p>
+      </Show>
+      <div class="flex gap-4">
+        <ul class="text-sm">
+          <For
+            each=move || digests.get()
+            key=|d| (d.id, d.generated_at)
+            children=move |d| {
+              let id = d.id;
+              view! {
+                <li>
+                  <button
+                    class="btn-icon"
+                    class:font-bold=move || current().is_some_and(|c| c.id == id)
+                    on:click=move |_| set_selected.set(Some(id))
+                  >
+                    {period_label(&d)}
+                  </button>
+                </li>
+              }
+            }
+          />
+        </ul>
+        <Transition fallback=move || view! { <p>"Loading digests..."</p> }>
+          {move || match current() {
+            Some(digest) => view! { <DigestView digest=digest/> }.into_view(),
+            None => view! {
+              <p class="text-sm text-gray-600">"No digests yet. Pick a day or week and generate one."</p>
+            }.into_view(),
+          }}
+        </Transition>
+      </div>
+    </div>
+  }
+}
+
+#[component]
+fn DigestView(digest: Digest) -> impl IntoView {
+  let title = period_label(&digest);
+  let export_href = format!("/digests/{}/markdown", digest.id);
+  let timezone = use_timezone();
+  let generated_at = digest.generated_at;
+  let generated = move || generated_at.with_timezone(&timezone.get()).format("%b %-d, %I:%M %p").to_string();
+  let content = digest.content;
+
+  view! {
+    <article class="flex-1">
+      <div class="flex items-center justify-between mb-2">
+        <h3 class="font-bold">{title}</h3>
+        <a href=export_href rel="external" download class="btn rounded-md">"Export Markdown"</a>
+      </div>
+      <p class="text-sm text-gray-600 mb-2">
+        {content.recording_count} " recordings · generated " {generated}
+      </p>
+      <table class="data-table mb-4">
+        <thead>
+          <tr>
+            <th>"Group"</th>
+            <th>"Recordings"</th>
+            <th>"Time"</th>
+          </tr>
+        </thead>
+        <tbody>
+          {content.groups.iter().map(|group| view! {
+            <tr>
+              <td>{group.name.clone()}</td>
+              <td>{group.recordings.len()}</td>
+              <td>{group.time_spent()}</td>
+            </tr>
+          }).collect_view()}
+        </tbody>
+      </table>
+      {content.groups.into_iter().map(|group| view! {
+        <section class="mb-4">
+          <h4 class="text-sm font-bold mb-1">{group.name}</h4>
+          <ul class="search-results">
+            {group.recordings.into_iter().map(|entry| {
+              let created_at = entry.created_at;
+              let time = move || created_at.with_timezone(&timezone.get()).format("%a %I:%M %p").to_string();
+              view! {
+                <li class="mb-2">
+                  <div class="flex items-center justify-between">
+                    <span class="font-bold">{entry.title.unwrap_or_else(|| "Untitled".to_string())}</span>
+                    <span class="text-sm text-gray-600">{time}</span>
+                  </div>
+                  <p class="text-sm">{entry.excerpt.unwrap_or_default()}</p>
+                </li>
+              }
+            }).collect_view()}
+          </ul>
+        </section>
+      }).collect_view()}
+      <h4 class="text-sm font-bold mb-1">"Open Action Items"</h4>
+      <Show
+        when={
+          let empty = content.open_tasks.is_empty();
+          move || !empty
+        }
+        fallback=|| view! { <p class="text-sm text-gray-600">"None."</p> }
+      >
+        <ul class="text-sm">
+          {content.open_tasks.iter().map(|task| {
+            let details = [
+              task.assignee.clone(),
+              task.due_date.map(|d| format!("due {}", d.format("%b %-d"))),
+            ].into_iter().flatten().collect::<Vec<_>>().join(", ");
+            view! {
+              <li>
+                {task.description.clone()}
+                <span class="text-gray-600">{if details.is_empty() { String::new() } else { format!(" ({})", details) }}</span>
+              </li>
+            }
+          }).collect_view()}
+        </ul>
+      </Show>
+    </article>
+  }
+}
+
+fn period_label(digest: &Digest) -> String {
+  match digest.kind.as_str() {
+    "week" => format!(
+      "Week of {} – {}",
+      digest.period_start.format("%b %-d"),
+      digest.period_end.format("%b %-d, %Y")
+    ),
+    _ => digest.period_start.format("%A, %b %-d, %Y").to_string(),
+  }
+}
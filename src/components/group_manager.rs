@@ -0,0 +1,185 @@
+use leptos::*;
+use leptos_router::ActionForm;
+use uuid::Uuid;
+use crate::app::{
+    get_groups, CreateGroup, UpdateGroup, SetGroupArchived, ReorderGroups, DeleteGroup
+};
+use crate::models::dtos::TaskGroup;
+
+#[component]
+pub fn GroupManager() -> impl IntoView {
+  let create_action = create_server_action::<CreateGroup>();
+  let update_action = create_server_action::<UpdateGroup>();
+  let archive_action = create_server_action::<SetGroupArchived>();
+  let reorder_action = create_server_action::<ReorderGroups>();
+  let delete_action = create_server_action::<DeleteGroup>();
+
+  // Refetch whenever any action completes
+  let groups_resource = create_resource(
+    move || (
+      create_action.version().get(),
+      update_action.version().get(),
+      archive_action.version().get(),
+      reorder_action.version().get(),
+      delete_action.version().get(),
+    ),
+    |_| async move { get_groups(true).await }
+  );
+
+  let groups = Signal::derive(move || {
+    groups_resource.get().and_then(|res| res.ok()).unwrap_or_default()
+  });
+
+  let error = move || {
+    let create_err = create_action.value().get().and_then(|r| r.err());
+    let update_err = update_action.value().get().and_then(|r| r.err());
+    let archive_err = archive_action.value().get().and_then(|r| r.err());
+    let reorder_err = reorder_action.value().get().and_then(|r| r.err());
+    let delete_err = delete_action.value().get().and_then(|r| r.err());
+    create_err.or(update_err).or(archive_err).or(reorder_err).or(delete_err)
+      .map(|e| e.to_string())
+  };
+
+  let on_move = Callback::new(move |(index, up): (usize, bool)| {
+    let mut ids: Vec<Uuid> = groups.get_untracked().iter().map(|g| g.id).collect();
+    let target = if up { index.checked_sub(1) } else { Some(index + 1) };
+    if let Some(target) = target.filter(|t| *t < ids.len()) {
+      ids.swap(index, target);
+      reorder_action.dispatch(ReorderGroups { ids });
+    }
+  });
+
+  view! {
+    <div id="groupsSection">
+      <h2 class="text-lg mb-2">"Task Groups"</h2>
+      <Show when=move || error().is_some()>
+        <p class="text-red-600 mb-2">{error}</p>
+      </Show>
+      <Transition fallback=move || view! { <p>"Loading groups..."</p> }>
+        <table class="data-table">
+          <thead>
+            <tr>
+              <th>"Order"</th>
+              <th>"Name"</th>
+              <th>"Description"</th>
+              <th>"Status"</th>
+              <th>"Action"</th>
+            </tr>
+          </thead>
+          <tbody>
+            <For
+              each=move || groups.get().into_iter().enumerate()
+              key=|(idx, group)| (group.id, *idx, group.name.clone(), group.description.clone(), group.archived)
+              children=move |(index, group)| {
+                view! {
+                  <GroupRow
+                    index=index
+                    group=group
+                    groups=groups
+                    on_move=on_move
+                    on_save=Callback::new(move |(id, name, description)| {
+                      update_action.dispatch(UpdateGroup { id, name: Some(name), description: Some(description) });
+                    })
+                    on_archive=Callback::new(move |(id, archived)| {
+                      archive_action.dispatch(SetGroupArchived { id, archived });
+                    })
+                    on_delete=Callback::new(move |(id, reassign_to)| {
+                      delete_action.dispatch(DeleteGroup { id, reassign_to });
+                    })
+                  />
+                }
+              }
+            />
+          </tbody>
+        </table>
+      </Transition>
+      <h3 class="text-sm font-bold mt-5 mb-2">"New Group"</h3>
+      <ActionForm action=create_action class="flex items-center gap-2">
+        <input type="text" name="name" class="p-2 border rounded-md" placeholder="Name" required/>
+        <input type="text" name="description" class="p-2 border rounded-md flex-1" placeholder="Description"/>
+        <button type="submit" class="btn rounded-md">"Add Group"</button>
+      </ActionForm>
+    </div>
+  }
+}
+
+#[component]
+fn GroupRow(
+  index: usize,
+  group: TaskGroup,
+  #[prop(into)] groups: Signal<Vec<TaskGroup>>,
+  on_move: Callback<(usize, bool)>,
+  on_save: Callback<(Uuid, String, String)>,
+  on_archive: Callback<(Uuid, bool)>,
+  on_delete: Callback<(Uuid, Option<Uuid>)>
+) -> impl IntoView {
+  let id = group.id;
+  let archived = group.archived;
+  let (name, set_name) = create_signal(group.name.clone());
+  let (description, set_description) = create_signal(group.description.clone().unwrap_or_default());
+  let (reassign_to, set_reassign_to) = create_signal(None::<Uuid>);
+
+  let is_last = move || index + 1 >= groups.get().len();
+
+  view! {
+    <tr class:text-gray-600=archived>
+      <td class="col-no">
+        <button class="btn-icon" disabled=index == 0 on:click=move |_| on_move.call((index, true)) title="Move up">"↑"</button>
+        <button class="btn-icon" disabled=is_last on:click=move |_| on_move.call((index, false)) title="Move down">"↓"</button>
+      </td>
+      <td>
+        <input
+          type="text"
+          class="border rounded px-1 py-0.5 text-sm w-full"
+          prop:value=move || name.get()
+          on:input=move |ev| set_name.set(event_target_value(&ev))
+        />
+      </td>
+      <td>
+        <input
+          type="text"
+          class="border rounded px-1 py-0.5 text-sm w-full"
+          prop:value=move || description.get()
+          on:input=move |ev| set_description.set(event_target_value(&ev))
+        />
+      </td>
+      <td>{if archived { "Archived" } else { "Active" }}</td>
+      <td class="col-action">
+        <div class="flex items-center gap-1">
+          <button class="btn-icon" on:click=move |_| on_save.call((id, name.get(), description.get()))>"Save"</button>
+          <button class="btn-icon" on:click=move |_| on_archive.call((id, !archived))>
+            {if archived { "Unarchive" } else { "Archive" }}
+          </button>
+          <select
+            class="p-1 border rounded text-sm"
+            title="What happens to this group's recordings when it is deleted"
+            on:change=move |ev| set_reassign_to.set(Uuid::parse_str(&event_target_value(&ev)).ok())
+          >
+            <option value="" selected=true>"Ungroup recordings"</option>
+            <For
+              each=move || groups.get().into_iter().filter(move |g| g.id != id)
+              key=|g| g.id
+              children=move |g| view! { <option value=g.id.to_string()>"Move to " {g.name.clone()}</option> }
+            />
+          </select>
+          <button
+            class="btn-icon delete-btn"
+            on:click=move |_| {
+              #[cfg(not(feature = "ssr"))]
+              {
+                if web_sys::window().unwrap().confirm_with_message("Delete this group?").unwrap() {
+                  on_delete.call((id, reassign_to.get()));
+                }
+              }
+              #[cfg(feature = "ssr")]
+              {
+                let _ = (on_delete, reassign_to);
+              }
+            }>
+            "Delete"
+          </button>
+        </div>
+      </td>
+    </tr>
+  }
+}
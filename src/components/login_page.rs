@@ -0,0 +1,23 @@
+use leptos::*;
+use leptos_router::*;
+
+#[component]
+pub fn LoginPage() -> impl IntoView {
+  // The form posts to the server, which sends failed attempts back here with `?error`
+  let query = use_query_map();
+  let failed = move || query.with(|params| params.get("error").is_some());
+
+  view! {
+    <div id="loginSection" class="max-w-sm">
+      <h2 class="text-lg mb-2">"Sign in"</h2>
+      <Show when=failed>
+        <p class="text-red-600 mb-2">"Wrong username or password."</p>
+      </Show>
+      <form method="post" action="/login" class="flex flex-col gap-2">
+        <input type="text" name="username" class="p-2 border rounded-md" placeholder="Username" autocomplete="username" required/>
+        <input type="password" name="password" class="p-2 border rounded-md" placeholder="Password" autocomplete="current-password" required/>
+        <button type="submit" class="btn rounded-md">"Sign in"</button>
+      </form>
+    </div>
+  }
+}
@@ -2,8 +2,24 @@ pub mod record_button;
 pub mod date_filter;
 pub mod recording_list;
 pub mod task_group_selector;
+pub mod search_page;
+pub mod group_manager;
+pub mod task_list;
+pub mod digest_page;
+pub mod trash_page;
+pub mod login_page;
+pub mod settings_page;
+pub mod share_page;
 
 pub use record_button::*;
 pub use date_filter::*;
 pub use recording_list::*;
 pub use task_group_selector::*;
+pub use search_page::*;
+pub use group_manager::*;
+pub use task_list::*;
+pub use digest_page::*;
+pub use trash_page::*;
+pub use login_page::*;
+pub use settings_page::*;
+pub use share_page::*;
@@ -1,8 +1,35 @@
 use leptos::*;
 use wasm_bindgen::prelude::*;
-use web_sys::{MediaRecorder, MediaStream, MediaRecorderOptions, BlobEvent, Blob, FormData};
+use wasm_bindgen_futures::JsFuture;
+use web_sys::{MediaRecorder, MediaStream, MediaRecorderOptions, BlobEvent, Blob, Headers, Response};
+use std::collections::VecDeque;
 use std::rc::Rc;
 use std::cell::RefCell;
+use chrono::Utc;
+use crate::models::dtos::{CaptureTimes, CreateUploadRequest, UploadStatus};
+
+// Preferred recording formats; Safari only supports mp4
+const RECORDING_MIME_TYPES: [&str; 4] = [
+    "audio/webm;codecs=opus",
+    "audio/webm",
+    "audio/mp4",
+    "audio/ogg;codecs=opus",
+];
+
+// Chunks are uploaded while recording, so a dropped connection only delays the upload
+const CHUNK_INTERVAL_MS: i32 = 5000;
+const CHUNK_RETRIES: u32 = 5;
+
+// Resumable upload of one recording
+struct UploadState {
+    id: uuid::Uuid,
+    offset: i64,
+    queue: VecDeque<Blob>,
+    sending: bool,
+    stopped: bool,
+    // Sent with the finalize request so the recording is dated by capture, not upload
+    capture: CaptureTimes,
+}
 
 #[component]
 pub fn RecordButton(
@@ -12,94 +39,87 @@ pub fn RecordButton(
     #[prop(optional, into)] date: Option<MaybeSignal<Option<String>>>
 ) -> impl IntoView {
     let (is_recording, set_is_recording) = create_signal(false);
-    
-    // Store references to recorder and chunks in Rc<RefCell> to share across closures
+
+    // Store a reference to the recorder in Rc<RefCell> to share across closures
     let media_recorder = Rc::new(RefCell::new(None::<MediaRecorder>));
-    let audio_chunks = Rc::new(RefCell::new(Vec::<Blob>::new()));
 
     let on_click = {
         let media_recorder = media_recorder.clone();
-        let audio_chunks = audio_chunks.clone();
         let date_signal = date;
-        
+
         move |_| {
             if !is_recording.get() {
                 // START RECORDING
                 let media_recorder = media_recorder.clone();
-                let audio_chunks = audio_chunks.clone();
                 let date_val = date_signal.as_ref().and_then(|d| d.get());
-                
+
                 spawn_local(async move {
                     let window = web_sys::window().unwrap();
                     let navigator = window.navigator();
                     let media_devices = navigator.media_devices().unwrap();
-                    
+
                     let constraints = web_sys::MediaStreamConstraints::new();
                     constraints.set_audio(&JsValue::from_bool(true));
-                    
+
                     let stream_promise = media_devices.get_user_media_with_constraints(&constraints).unwrap();
-                    let stream_js = wasm_bindgen_futures::JsFuture::from(stream_promise).await.unwrap();
+                    let stream_js = JsFuture::from(stream_promise).await.unwrap();
                     let stream: MediaStream = stream_js.unchecked_into();
-                    
+
                     let options = MediaRecorderOptions::new();
-                    options.set_mime_type("audio/webm");
-                    
+                    if let Some(mime_type) = RECORDING_MIME_TYPES.iter().find(|t| MediaRecorder::is_type_supported(t)) {
+                        options.set_mime_type(mime_type);
+                    }
+
                     let recorder = MediaRecorder::new_with_media_stream_and_media_recorder_options(&stream, &options).unwrap();
-                    
+                    let recorded_type = recorder.mime_type();
+
+                    // Open the upload session before any data is produced
+                    let request = CreateUploadRequest {
+                        date: date_val,
+                        filename: Some(format!("recording.{}", extension_for(&recorded_type))),
+                    };
+                    let session = match send_json("POST", "/uploads", Some(serde_json::to_string(&request).unwrap())).await {
+                        Ok(session) => session,
+                        Err(e) => {
+                            web_sys::console::error_2(&"Failed to start upload".into(), &e);
+                            return;
+                        }
+                    };
+                    let upload = Rc::new(RefCell::new(UploadState {
+                        id: session.id,
+                        offset: session.offset,
+                        queue: VecDeque::new(),
+                        sending: false,
+                        stopped: false,
+                        capture: CaptureTimes::default(),
+                    }));
+
                     // ondataavailable
-                    let audio_chunks_inner = audio_chunks.clone();
+                    let upload_data = upload.clone();
                     let on_data_callback = Closure::wrap(Box::new(move |ev: BlobEvent| {
-                        audio_chunks_inner.borrow_mut().push(ev.data().unwrap());
+                        if let Some(blob) = ev.data().filter(|b| b.size() > 0.0) {
+                            upload_data.borrow_mut().queue.push_back(blob);
+                        }
+                        pump_upload(upload_data.clone(), on_success);
                     }) as Box<dyn FnMut(BlobEvent)>);
                     recorder.set_ondataavailable(Some(on_data_callback.as_ref().unchecked_ref()));
                     on_data_callback.forget(); // Keep closure alive
-                    
-                    // onstop
-                    let audio_chunks_stop = audio_chunks.clone();
-                    let date_val_stop = date_val.clone();
+
+                    // onstop fires after the last ondataavailable
+                    let upload_stop = upload.clone();
                     let on_stop_callback = Closure::wrap(Box::new(move |_| {
-                        let chunks = audio_chunks_stop.borrow().clone();
-                        let array = js_sys::Array::new();
-                        for chunk in chunks {
-                            array.push(&chunk);
+                        {
+                            let mut state = upload_stop.borrow_mut();
+                            state.stopped = true;
+                            state.capture.ended_at = Some(Utc::now());
                         }
-                        let property_bag = web_sys::BlobPropertyBag::new();
-                        property_bag.set_type("audio/webm");
-                        let blob = Blob::new_with_blob_sequence_and_options(
-                            &array,
-                            &property_bag
-                        ).unwrap();
-                        
-                        // Upload
-                        let date_val_upload = date_val_stop.clone();
-                        spawn_local(async move {
-                            let form_data = FormData::new().unwrap();
-                            form_data.append_with_blob_and_filename("file", &blob, "recording.webm").unwrap();
-                            
-                            let window = web_sys::window().unwrap();
-                            let init = web_sys::RequestInit::new();
-                            init.set_method("POST");
-                            init.set_body(&form_data);
-                            
-                            let url = if let Some(d) = &date_val_upload {
-                                format!("/upload?date={}", d)
-                            } else {
-                                "/upload".to_string()
-                            };
-
-                            let _ = wasm_bindgen_futures::JsFuture::from(
-                                window.fetch_with_str_and_init(&url, &init)
-                            ).await;
-
-                            if let Some(on_success) = on_success {
-                                on_success.call(());
-                            }
-                        });
+                        pump_upload(upload_stop.clone(), on_success);
                     }) as Box<dyn FnMut(JsValue)>);
                     recorder.set_onstop(Some(on_stop_callback.as_ref().unchecked_ref()));
                     on_stop_callback.forget();
 
-                    recorder.start().unwrap();
+                    recorder.start_with_time_slice(CHUNK_INTERVAL_MS).unwrap();
+                    upload.borrow_mut().capture.started_at = Some(Utc::now());
                     media_recorder.replace(Some(recorder));
                     set_is_recording.set(true);
                 });
@@ -108,7 +128,6 @@ pub fn RecordButton(
                 if let Some(recorder) = media_recorder.borrow().as_ref() {
                     recorder.stop().unwrap();
                 }
-                audio_chunks.borrow_mut().clear();
                 set_is_recording.set(false);
             }
         }
@@ -131,4 +150,118 @@ pub fn RecordButton(
         {btn_label}
       </button>
     }
-}
\ No newline at end of file
+}
+
+// Sends queued chunks in order and finalizes once recording has stopped.
+// A chunk that keeps failing stays queued and is retried with the next one.
+fn pump_upload(upload: Rc<RefCell<UploadState>>, on_success: Option<Callback<()>>) {
+    if upload.borrow().sending {
+        return;
+    }
+    upload.borrow_mut().sending = true;
+
+    spawn_local(async move {
+        loop {
+            let next = upload.borrow().queue.front().cloned();
+            let (id, offset) = {
+                let state = upload.borrow();
+                (state.id, state.offset)
+            };
+
+            match next {
+                Some(chunk) => match send_chunk(id, offset, &chunk).await {
+                    Some(new_offset) => {
+                        let mut state = upload.borrow_mut();
+                        state.offset = new_offset;
+                        state.queue.pop_front();
+                    }
+                    None => break,
+                },
+                None => {
+                    if upload.borrow().stopped {
+                        let url = format!("/uploads/{}/finalize", id);
+                        let capture = upload.borrow().capture;
+                        let capture = CaptureTimes {
+                            duration_ms: capture.started_at.zip(capture.ended_at)
+                                .map(|(started_at, ended_at)| (ended_at - started_at).num_milliseconds()),
+                            ..capture
+                        };
+                        if send_json("POST", &url, Some(serde_json::to_string(&capture).unwrap())).await.is_ok() {
+                            if let Some(on_success) = on_success {
+                                on_success.call(());
+                            }
+                        }
+                    }
+                    break;
+                }
+            }
+        }
+        upload.borrow_mut().sending = false;
+    });
+}
+
+// Returns the new offset once the server has the chunk
+async fn send_chunk(id: uuid::Uuid, offset: i64, chunk: &Blob) -> Option<i64> {
+    let end = offset + chunk.size() as i64;
+    let url = format!("/uploads/{}?offset={}", id, offset);
+
+    for attempt in 0..CHUNK_RETRIES {
+        if let Ok(status) = send("PUT", &url, Some(chunk.as_ref()), None).await {
+            return Some(status.offset);
+        }
+        sleep_ms(1000 * 2_i32.pow(attempt)).await;
+
+        // The chunk may have arrived even though the response didn't
+        if let Ok(status) = send("GET", &format!("/uploads/{}", id), None, None).await {
+            if status.offset >= end {
+                return Some(status.offset);
+            }
+        }
+    }
+    None
+}
+
+async fn send_json(method: &str, url: &str, body: Option<String>) -> Result<UploadStatus, JsValue> {
+    let body = body.map(|b| JsValue::from_str(&b));
+    send(method, url, body.as_ref(), Some("application/json")).await
+}
+
+async fn send(method: &str, url: &str, body: Option<&JsValue>, content_type: Option<&str>) -> Result<UploadStatus, JsValue> {
+    let window = web_sys::window().unwrap();
+    let init = web_sys::RequestInit::new();
+    init.set_method(method);
+    if let Some(body) = body {
+        init.set_body(body);
+    }
+    if let Some(content_type) = content_type {
+        let headers = Headers::new()?;
+        headers.set("Content-Type", content_type)?;
+        init.set_headers(&headers);
+    }
+
+    let response: Response = JsFuture::from(window.fetch_with_str_and_init(url, &init)).await?.unchecked_into();
+    let text = JsFuture::from(response.text()?).await?.as_string().unwrap_or_default();
+    if !response.ok() {
+        return Err(JsValue::from_str(&text));
+    }
+    serde_json::from_str(&text).map_err(|e| JsValue::from_str(&e.to_string()))
+}
+
+async fn sleep_ms(ms: i32) {
+    let promise = js_sys::Promise::new(&mut |resolve, _| {
+        let _ = web_sys::window()
+            .unwrap()
+            .set_timeout_with_callback_and_timeout_and_arguments_0(&resolve, ms);
+    });
+    let _ = JsFuture::from(promise).await;
+}
+
+fn extension_for(mime_type: &str) -> &'static str {
+    if mime_type.starts_with("audio/mp4") {
+        "m4a"
+    } else if mime_type.starts_with("audio/ogg") {
+        "ogg"
+    } else {
+        "webm"
+    }
+}
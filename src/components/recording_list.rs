@@ -1,6 +1,7 @@
 use leptos::*;
 use crate::models::dtos::{RecordingFile, TaskGroup};
 use crate::components::task_group_selector::TaskGroupSelector;
+use crate::timezone::use_timezone;
 use uuid::Uuid;
 
 #[component]
@@ -9,6 +10,8 @@ pub fn RecordingList(
   groups: MaybeSignal<Vec<TaskGroup>>,
   on_group_change: Callback<(Uuid, Option<Uuid>)>,
   on_title_change: Callback<(Uuid, String)>,
+  on_retranscribe: Callback<Uuid>,
+  on_share: Callback<Uuid>,
   on_delete: Callback<Uuid>
 ) -> impl IntoView {
   view! {
@@ -27,12 +30,9 @@ pub fn RecordingList(
       <tbody>
         <For
           each=move || recordings.get().into_iter().enumerate()
-          key=|(idx, rec)| (rec.id, rec.status.clone(), *idx)
+          key=|(idx, rec)| (rec.id, rec.status.clone(), rec.group_id, *idx)
           children={
             let groups = groups.clone();
-            let on_group_change = on_group_change.clone();
-            let on_title_change = on_title_change.clone();
-            let on_delete = on_delete.clone();
             move |(index, rec)| {
               view! {
                 <RecordingRow
@@ -41,6 +41,8 @@ pub fn RecordingList(
                   groups=groups.clone()
                   on_group_change=on_group_change
                   on_title_change=on_title_change
+                  on_retranscribe=on_retranscribe
+                  on_share=on_share
                   on_delete=on_delete
                 />
               }
@@ -59,13 +61,21 @@ fn RecordingRow(
   groups: MaybeSignal<Vec<TaskGroup>>,
   on_group_change: Callback<(Uuid, Option<Uuid>)>,
   on_title_change: Callback<(Uuid, String)>,
+  on_retranscribe: Callback<Uuid>,
+  on_share: Callback<Uuid>,
   on_delete: Callback<Uuid>
 ) -> impl IntoView {
   let id = rec.id;
   let rec_path = rec.path.clone();
-  let rec_name = rec.name.clone();
   let rec_status = rec.status.clone();
+  let rec_error = rec.error.clone().unwrap_or_default();
+  let is_failed = rec.status == "FAILED";
+  let can_retry = is_failed || rec.status == "COMPLETED";
   let rec_group_id = rec.group_id;
+  // Only offer the model's suggestion while the recording has no group
+  let suggestion = rec.suggested_group_id
+    .filter(|_| rec_group_id.is_none())
+    .map(|group_id| (group_id, rec.suggestion_confidence.unwrap_or_default()));
 
   // Extract title and transcript from JSON
   let (title, full_text) = match &rec.transcription {
@@ -87,23 +97,19 @@ fn RecordingRow(
   let (is_editing, set_is_editing) = create_signal(false);
   let (edit_title, set_edit_title) = create_signal(title.clone());
 
-  // Extract time from filename
-  let time_str = {
-    let match_res = rec_name.match_indices('_').collect::<Vec<_>>();
-    if let Some(&(idx, _)) = match_res.last() {
-      if let Some(dot_idx) = rec_name.find('.') {
-        if idx < dot_idx {
-          let ts_str = &rec_name[idx+1..dot_idx];
-          if let Ok(ts) = ts_str.parse::<i64>() {
-            use chrono::{TimeZone, Local};
-            let datetime = Local.timestamp_opt(ts, 0).unwrap();
-            datetime.format("%I:%M %p").to_string()
-          } else {
-            "".to_string()
-          }
-        } else { "".to_string() }
-      } else { "".to_string() }
-    } else { "".to_string() }
+  // Capture start in the configured timezone, with the length when the client reported it
+  let timezone = use_timezone();
+  let started_at = rec.started_at;
+  let duration_ms = rec.duration_ms;
+  let time_str = move || {
+    let started = started_at.with_timezone(&timezone.get()).format("%I:%M %p").to_string();
+    match duration_ms {
+      Some(ms) => {
+        let secs = ms / 1000;
+        format!("{} ({}:{:02})", started, secs / 60, secs % 60)
+      }
+      None => started,
+    }
   };
 
   view! {
@@ -168,19 +174,42 @@ fn RecordingRow(
           </div>
         </Show>
       </td>
-      <td class="col-status">{rec_status}</td>
+      <td class="col-status" class:text-red-600=is_failed title=rec_error>{rec_status}</td>
       <td class="col-group">
         <TaskGroupSelector
-          groups=groups
+          groups=groups.clone()
           selected_id=rec_group_id
           on_change=Callback::new(move |new_group| on_group_change.call((id, new_group)))
         />
+        {suggestion.map(|(suggested_id, confidence)| {
+          let suggested_name = move || groups.get()
+            .into_iter()
+            .find(|g| g.id == suggested_id)
+            .map(|g| g.name)
+            .unwrap_or_default();
+          view! {
+            <div class="flex items-center gap-1 text-sm text-gray-600 mt-1">
+              <span>"Suggested: " {suggested_name} {format!(" ({:.0}%)", confidence * 100.0)}</span>
+              <button
+                class="btn-icon"
+                on:click=move |_| on_group_change.call((id, Some(suggested_id)))
+                title="Use the suggested group">
+                "Accept"
+              </button>
+            </div>
+          }
+        })}
       </td>
       <td class="col-audio">
         <audio controls style="height: 30px;" src=rec_path></audio>
+        {rec.integrity_error.map(|problem| view! { <div class="text-sm text-red-600">{problem}</div> })}
       </td>
       <td class="col-time">{time_str}</td>
       <td class="col-action">
+        <Show when=move || can_retry>
+          <button class="btn-icon mr-2" on:click=move |_| on_retranscribe.call(id) title="Transcribe again">"Retry"</button>
+        </Show>
+        <button class="btn-icon mr-2" on:click=move |_| on_share.call(id) title="Create a read-only link">"Share"</button>
         <button class="btn-icon delete-btn" on:click=move |_| on_delete.call(id)>"Delete"</button>
       </td>
     </tr>
@@ -0,0 +1,105 @@
+use std::collections::BTreeMap;
+use leptos::*;
+use leptos_router::*;
+use chrono::NaiveDate;
+use crate::app::search_recordings;
+use crate::models::dtos::SearchResult;
+use crate::timezone::{local_date, use_timezone, Tz};
+
+#[component]
+pub fn SearchPage() -> impl IntoView {
+  let query = use_query_map();
+  let q = move || query.with(|params| params.get("q").cloned().unwrap_or_default());
+
+  let timezone = use_timezone();
+
+  let results_resource = create_resource(q, |q| async move {
+    if q.trim().is_empty() {
+      Ok(Vec::new())
+    } else {
+      search_recordings(q).await
+    }
+  });
+
+  view! {
+    <div id="searchSection">
+      <h2 class="text-lg mb-2">"Search Recordings"</h2>
+      <Form method="GET" action="/search" class="flex items-center gap-2 mb-2 pb-2 border-b">
+        <input
+          type="search"
+          name="q"
+          class="p-2 border rounded-md flex-1"
+          placeholder="Search titles and transcripts"
+          prop:value=q
+        />
+        <button type="submit" class="btn rounded-md">"Search"</button>
+      </Form>
+      <Transition fallback=move || view! { <p>"Searching..."</p> }>
+        {move || results_resource.get().map(|res| match res {
+          Err(e) => view! { <p class="text-red-600">{e.to_string()}</p> }.into_view(),
+          Ok(results) if results.is_empty() => {
+            if q().trim().is_empty() {
+              view! { <p class="text-sm text-gray-600">"Type a word or phrase to search all recordings."</p> }.into_view()
+            } else {
+              view! { <p class="text-sm text-gray-600">"No recordings match your search."</p> }.into_view()
+            }
+          },
+          Ok(results) => {
+            let total = results.len();
+            let tz = timezone.get();
+            view! {
+              <p class="text-sm text-gray-600 mb-2">{total} " matching recordings"</p>
+              {group_by_day(results, tz)
+                .into_iter()
+                .map(|(day, results)| view! { <SearchDay day=day results=results tz=tz/> })
+                .collect_view()}
+            }.into_view()
+          }
+        })}
+      </Transition>
+    </div>
+  }
+}
+
+#[component]
+fn SearchDay(day: NaiveDate, results: Vec<SearchResult>, tz: Tz) -> impl IntoView {
+  view! {
+    <section class="mb-4">
+      <h3 class="text-sm font-bold mb-1">{day.format("%A, %B %-d, %Y").to_string()}</h3>
+      <ul class="search-results">
+        {results.into_iter().map(|result| {
+          let title = result.title.clone().unwrap_or_else(|| result.name.clone());
+          let time = result.created_at.with_timezone(&tz).format("%I:%M %p").to_string();
+          view! {
+            <li class="search-result mb-2">
+              <div class="flex items-center justify-between">
+                <span class="font-bold">{title}</span>
+                <span class="text-sm text-gray-600">{time}</span>
+              </div>
+              <p class="text-sm">
+                {result.snippet.into_iter().map(|part| {
+                  if part.highlight {
+                    view! { <mark>{part.text}</mark> }.into_view()
+                  } else {
+                    part.text.into_view()
+                  }
+                }).collect_view()}
+              </p>
+              <audio controls style="height: 30px;" src=result.path></audio>
+            </li>
+          }
+        }).collect_view()}
+      </ul>
+    </section>
+  }
+}
+
+// Newest day first; results keep their rank order within a day
+fn group_by_day(results: Vec<SearchResult>, tz: Tz) -> Vec<(NaiveDate, Vec<SearchResult>)> {
+  let mut days: BTreeMap<NaiveDate, Vec<SearchResult>> = BTreeMap::new();
+  for result in results {
+    let day = local_date(result.created_at, tz);
+    days.entry(day).or_default().push(result);
+  }
+  days.into_iter().rev().collect()
+}
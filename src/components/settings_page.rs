@@ -0,0 +1,281 @@
+use leptos::*;
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+use crate::app::{get_account, get_api_tokens, get_webhooks, CreateApiToken, CreateWebhook, DeleteWebhook, RevokeApiToken};
+use crate::models::dtos::{ApiToken, CreateTokenRequest, CreateWebhookRequest, TokenScope, Webhook, WebhookEvent};
+use crate::timezone::{use_timezone, Tz};
+
+#[component]
+pub fn SettingsPage() -> impl IntoView {
+  let create_action = create_server_action::<CreateApiToken>();
+  let revoke_action = create_server_action::<RevokeApiToken>();
+
+  let account_resource = create_resource(|| (), |_| async move { get_account().await });
+  let tokens_resource = create_resource(
+    move || (create_action.version().get(), revoke_action.version().get()),
+    |_| async move { get_api_tokens().await }
+  );
+
+  let (name, set_name) = create_signal(String::new());
+  let (scopes, set_scopes) = create_signal(vec![TokenScope::Read, TokenScope::Upload]);
+
+  // The secret can't be looked up again, so it is shown until the page is left
+  let created = move || create_action.value().get().and_then(|r| r.ok());
+  let error = move || {
+    let create_err = create_action.value().get().and_then(|r| r.err());
+    let revoke_err = revoke_action.value().get().and_then(|r| r.err());
+    create_err.or(revoke_err).map(|e| e.to_string())
+  };
+
+  let on_create = move |ev: ev::SubmitEvent| {
+    ev.prevent_default();
+    create_action.dispatch(CreateApiToken {
+      request: CreateTokenRequest { name: name.get(), scopes: scopes.get() },
+    });
+    set_name.set(String::new());
+  };
+
+  let on_revoke = Callback::new(move |id: Uuid| {
+    revoke_action.dispatch(RevokeApiToken { id });
+  });
+
+  view! {
+    <div id="settingsSection">
+      <h2 class="text-lg mb-2">"Settings"</h2>
+      <Transition fallback=move || view! { <p>"Loading account..."</p> }>
+        {move || account_resource.get().map(|res| match res {
+          Err(e) => view! { <p class="text-red-600">{e.to_string()}</p> }.into_view(),
+          Ok(account) => view! {
+            <p class="text-sm text-gray-600 mb-2">
+              "Signed in as " {account.username} " · times shown in " {account.timezone}
+            </p>
+          }.into_view(),
+        })}
+      </Transition>
+
+      <h3 class="text-sm font-bold mt-5 mb-2">"API Tokens"</h3>
+      <p class="text-sm text-gray-600 mb-2">
+        "Scripts send a token as " <code>"Authorization: Bearer <token>"</code>
+        ". Read allows GET requests, upload adds recordings and changes recordings and groups, delete removes them."
+      </p>
+      <Show when=move || error().is_some()>
+        <p class="text-red-600 mb-2">{error}</p>
+      </Show>
+      {move || created().map(|created| view! {
+        <div class="p-2 border rounded-md mb-2">
+          <p class="text-sm mb-1">"Copy the token for " {created.token.name} " now, it won't be shown again:"</p>
+          <code class="select-all">{created.secret}</code>
+        </div>
+      })}
+      <Transition fallback=move || view! { <p>"Loading tokens..."</p> }>
+        {move || tokens_resource.get().map(|res| match res {
+          Err(e) => view! { <p class="text-red-600">{e.to_string()}</p> }.into_view(),
+          Ok(tokens) if tokens.is_empty() => {
+            view! { <p class="text-sm text-gray-600">"No tokens yet."</p> }.into_view()
+          },
+          Ok(tokens) => view! {
+            <table class="data-table">
+              <thead>
+                <tr>
+                  <th>"Name"</th>
+                  <th>"Scopes"</th>
+                  <th>"Created"</th>
+                  <th>"Last used"</th>
+                  <th></th>
+                </tr>
+              </thead>
+              <tbody>
+                {tokens.into_iter().map(|token| view! {
+                  <TokenRow token=token on_revoke=on_revoke/>
+                }).collect_view()}
+              </tbody>
+            </table>
+          }.into_view()
+        })}
+      </Transition>
+
+      <form class="flex items-center gap-2 mt-2" on:submit=on_create>
+        <input
+          type="text"
+          class="p-2 border rounded-md"
+          placeholder="Name, e.g. phone shortcut"
+          required
+          prop:value=name
+          on:input=move |ev| set_name.set(event_target_value(&ev))
+        />
+        {TokenScope::ALL.into_iter().map(|scope| view! {
+          <label class="flex items-center gap-1 text-sm">
+            <input
+              type="checkbox"
+              prop:checked=move || scopes.with(|s| s.contains(&scope))
+              on:change=move |ev| {
+                let checked = event_target_checked(&ev);
+                set_scopes.update(|s| {
+                  s.retain(|other| *other != scope);
+                  if checked {
+                    s.push(scope);
+                  }
+                });
+              }
+            />
+            {scope.as_str()}
+          </label>
+        }).collect_view()}
+        <button type="submit" class="btn rounded-md">"Create Token"</button>
+      </form>
+
+      <WebhookSettings/>
+    </div>
+  }
+}
+
+#[component]
+fn WebhookSettings() -> impl IntoView {
+  let create_action = create_server_action::<CreateWebhook>();
+  let delete_action = create_server_action::<DeleteWebhook>();
+
+  let webhooks_resource = create_resource(
+    move || (create_action.version().get(), delete_action.version().get()),
+    |_| async move { get_webhooks().await }
+  );
+
+  let (url, set_url) = create_signal(String::new());
+  let (events, set_events) = create_signal(WebhookEvent::ALL.to_vec());
+
+  // Like token secrets, the generated signing secret is only shown once
+  let created = move || create_action.value().get().and_then(|r| r.ok());
+  let error = move || {
+    let create_err = create_action.value().get().and_then(|r| r.err());
+    let delete_err = delete_action.value().get().and_then(|r| r.err());
+    create_err.or(delete_err).map(|e| e.to_string())
+  };
+
+  let on_create = move |ev: ev::SubmitEvent| {
+    ev.prevent_default();
+    create_action.dispatch(CreateWebhook {
+      request: CreateWebhookRequest { url: url.get(), secret: None, events: events.get() },
+    });
+    set_url.set(String::new());
+  };
+
+  let on_delete = Callback::new(move |id: Uuid| {
+    delete_action.dispatch(DeleteWebhook { id });
+  });
+
+  view! {
+    <h3 class="text-sm font-bold mt-5 mb-2">"Webhooks"</h3>
+    <p class="text-sm text-gray-600 mb-2">
+      "Recording events are posted as JSON to each URL, signed in the "
+      <code>"X-Workflow-Signature"</code>
+      " header. Failed deliveries are retried with increasing delays."
+    </p>
+    <Show when=move || error().is_some()>
+      <p class="text-red-600 mb-2">{error}</p>
+    </Show>
+    {move || created().map(|created| view! {
+      <div class="p-2 border rounded-md mb-2">
+        <p class="text-sm mb-1">"Copy the signing secret for " {created.webhook.url} " now, it won't be shown again:"</p>
+        <code class="select-all">{created.secret}</code>
+      </div>
+    })}
+    <Transition fallback=move || view! { <p>"Loading webhooks..."</p> }>
+      {move || webhooks_resource.get().map(|res| match res {
+        Err(e) => view! { <p class="text-red-600">{e.to_string()}</p> }.into_view(),
+        Ok(webhooks) if webhooks.is_empty() => {
+          view! { <p class="text-sm text-gray-600">"No webhooks yet."</p> }.into_view()
+        },
+        Ok(webhooks) => view! {
+          <table class="data-table">
+            <thead>
+              <tr>
+                <th>"URL"</th>
+                <th>"Events"</th>
+                <th>"Created"</th>
+                <th></th>
+              </tr>
+            </thead>
+            <tbody>
+              {webhooks.into_iter().map(|webhook| view! {
+                <WebhookRow webhook=webhook on_delete=on_delete/>
+              }).collect_view()}
+            </tbody>
+          </table>
+        }.into_view()
+      })}
+    </Transition>
+
+    <form class="flex items-center gap-2 mt-2" on:submit=on_create>
+      <input
+        type="url"
+        class="p-2 border rounded-md"
+        placeholder="https://example.com/hooks/workflow"
+        required
+        prop:value=url
+        on:input=move |ev| set_url.set(event_target_value(&ev))
+      />
+      {WebhookEvent::ALL.into_iter().map(|event| view! {
+        <label class="flex items-center gap-1 text-sm">
+          <input
+            type="checkbox"
+            prop:checked=move || events.with(|e| e.contains(&event))
+            on:change=move |ev| {
+              let checked = event_target_checked(&ev);
+              set_events.update(|e| {
+                e.retain(|other| *other != event);
+                if checked {
+                  e.push(event);
+                }
+              });
+            }
+          />
+          {event.as_str()}
+        </label>
+      }).collect_view()}
+      <button type="submit" class="btn rounded-md">"Add Webhook"</button>
+    </form>
+  }
+}
+
+#[component]
+fn WebhookRow(webhook: Webhook, on_delete: Callback<Uuid>) -> impl IntoView {
+  let id = webhook.id;
+  let events = webhook.events.iter().map(|e| e.as_str()).collect::<Vec<_>>().join(", ");
+  let timezone = use_timezone();
+  let created_at = webhook.created_at;
+
+  view! {
+    <tr>
+      <td>{webhook.url}</td>
+      <td>{events}</td>
+      <td>{move || format_time(created_at, timezone.get())}</td>
+      <td>
+        <button class="btn-icon delete-btn" on:click=move |_| on_delete.call(id)>"Remove"</button>
+      </td>
+    </tr>
+  }
+}
+
+#[component]
+fn TokenRow(token: ApiToken, on_revoke: Callback<Uuid>) -> impl IntoView {
+  let id = token.id;
+  let scopes = token.scopes.iter().map(|s| s.as_str()).collect::<Vec<_>>().join(", ");
+  let timezone = use_timezone();
+  let created_at = token.created_at;
+  let last_used_at = token.last_used_at;
+
+  view! {
+    <tr>
+      <td>{token.name}</td>
+      <td>{scopes}</td>
+      <td>{move || format_time(created_at, timezone.get())}</td>
+      <td>{move || last_used_at.map(|t| format_time(t, timezone.get())).unwrap_or_else(|| "Never".to_string())}</td>
+      <td>
+        <button class="btn-icon delete-btn" on:click=move |_| on_revoke.call(id)>"Revoke"</button>
+      </td>
+    </tr>
+  }
+}
+
+fn format_time(time: DateTime<Utc>, tz: Tz) -> String {
+  time.with_timezone(&tz).format("%b %-d, %I:%M %p").to_string()
+}
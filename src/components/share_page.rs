@@ -0,0 +1,43 @@
+use leptos::*;
+use leptos_router::*;
+use crate::app::get_shared_recording;
+use crate::models::dtos::SharedRecording;
+use crate::timezone::{parse_timezone, DEFAULT_TIMEZONE};
+
+#[component]
+pub fn SharePage() -> impl IntoView {
+  let params = use_params_map();
+  let token = move || params.with(|p| p.get("token").cloned().unwrap_or_default());
+  let shared_resource = create_resource(token, |token| async move { get_shared_recording(token).await });
+
+  view! {
+    <div id="shareSection" class="container">
+      <Suspense fallback=move || view! { <p>"Loading recording..."</p> }>
+        {move || shared_resource.get().map(|res| match res {
+          Err(e) => view! { <p class="text-red-600">{e.to_string()}</p> }.into_view(),
+          Ok(None) => view! {
+            <p class="text-sm text-gray-600">"This link has expired or was revoked."</p>
+          }.into_view(),
+          Ok(Some(shared)) => view! { <SharedRecordingView shared=shared/> }.into_view(),
+        })}
+      </Suspense>
+    </div>
+  }
+}
+
+#[component]
+fn SharedRecordingView(shared: SharedRecording) -> impl IntoView {
+  // Shown in the owner's timezone, since visitors have no account to take one from
+  let tz = parse_timezone(&shared.timezone).unwrap_or(DEFAULT_TIMEZONE);
+  let recorded = shared.created_at.with_timezone(&tz).format("%b %-d, %Y, %I:%M %p").to_string();
+  let expires = shared.expires_at.with_timezone(&tz).format("%b %-d, %Y").to_string();
+
+  view! {
+    <h2 class="text-lg mb-2">{shared.title.unwrap_or_else(|| "Untitled recording".to_string())}</h2>
+    <p class="text-sm text-gray-600 mb-2">"Recorded " {recorded} " · link valid until " {expires}</p>
+    <audio controls src=shared.audio_url></audio>
+    <p class="mt-5 whitespace-pre-wrap">
+      {shared.transcript.unwrap_or_else(|| "No transcript yet.".to_string())}
+    </p>
+  }
+}
@@ -25,6 +25,8 @@ pub fn TaskGroupSelector(
             <option value="" selected=selected_id.is_none()>"Select Group"</option>
             <For
                 each=move || groups.get()
+                    .into_iter()
+                    .filter(move |group| !group.archived || selected_id == Some(group.id))
                 key=|group| group.id
                 children=move |group| {
                     let group_id = group.id;
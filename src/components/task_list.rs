@@ -0,0 +1,116 @@
+use leptos::*;
+use chrono::NaiveDate;
+use uuid::Uuid;
+use crate::app::{get_groups, get_tasks, SetTaskDone};
+use crate::models::dtos::{Task, TaskGroup, TaskQuery};
+use crate::timezone::{today, use_timezone};
+
+#[component]
+pub fn TaskList() -> impl IntoView {
+  let (status, set_status) = create_signal("open".to_string());
+  let (group_id, set_group_id) = create_signal(None::<Uuid>);
+  let done_action = create_server_action::<SetTaskDone>();
+
+  let groups_resource = create_resource(|| (), |_| async move { get_groups(true).await });
+  let groups = Signal::derive(move || {
+    groups_resource.get().and_then(|res| res.ok()).unwrap_or_default()
+  });
+
+  let tasks_resource = create_resource(
+    move || (status.get(), group_id.get(), done_action.version().get()),
+    |(status, group_id, _)| async move {
+      get_tasks(TaskQuery { status: Some(status), group_id, ..Default::default() }).await
+    }
+  );
+
+  let on_toggle = Callback::new(move |(id, done): (Uuid, bool)| {
+    done_action.dispatch(SetTaskDone { id, done });
+  });
+
+  view! {
+    <div id="tasksSection">
+      <h2 class="text-lg mb-2">"Action Items"</h2>
+      <div class="flex items-center gap-2 mb-2 pb-2 border-b">
+        <select class="p-2 border rounded-md" on:change=move |ev| set_status.set(event_target_value(&ev))>
+          <option value="open" selected=true>"Open"</option>
+          <option value="done">"Done"</option>
+          <option value="all">"All"</option>
+        </select>
+        <select
+          class="p-2 border rounded-md"
+          on:change=move |ev| set_group_id.set(Uuid::parse_str(&event_target_value(&ev)).ok())
+        >
+          <option value="" selected=true>"All groups"</option>
+          <For
+            each=move || groups.get()
+            key=|g| g.id
+            children=move |g| view! { <option value=g.id.to_string()>{g.name.clone()}</option> }
+          />
+        </select>
+      </div>
+      <Transition fallback=move || view! { <p>"Loading action items..."</p> }>
+        {move || tasks_resource.get().map(|res| match res {
+          Err(e) => view! { <p class="text-red-600">{e.to_string()}</p> }.into_view(),
+          Ok(tasks) if tasks.is_empty() => {
+            view! { <p class="text-sm text-gray-600">"No action items."</p> }.into_view()
+          },
+          Ok(tasks) => view! {
+            <table class="data-table">
+              <thead>
+                <tr>
+                  <th>"Done"</th>
+                  <th>"Action Item"</th>
+                  <th>"Assignee"</th>
+                  <th>"Due"</th>
+                  <th>"Recording"</th>
+                  <th>"Group"</th>
+                </tr>
+              </thead>
+              <tbody>
+                {tasks.into_iter().map(|task| view! {
+                  <TaskRow task=task groups=groups on_toggle=on_toggle/>
+                }).collect_view()}
+              </tbody>
+            </table>
+          }.into_view()
+        })}
+      </Transition>
+    </div>
+  }
+}
+
+#[component]
+fn TaskRow(
+  task: Task,
+  #[prop(into)] groups: Signal<Vec<TaskGroup>>,
+  on_toggle: Callback<(Uuid, bool)>
+) -> impl IntoView {
+  let id = task.id;
+  let done = task.done_at.is_some();
+  let timezone = use_timezone();
+  let due_date = task.due_date;
+  let overdue = move || !done && due_date.is_some_and(|d| d < today(timezone.get()));
+  let group_name = move || {
+    task.group_id
+      .and_then(|gid| groups.get().into_iter().find(|g| g.id == gid))
+      .map(|g| g.name)
+      .unwrap_or_default()
+  };
+
+  view! {
+    <tr class:text-gray-600=done>
+      <td class="col-no">
+        <input type="checkbox" checked=done on:change=move |_| on_toggle.call((id, !done))/>
+      </td>
+      <td>{task.description}</td>
+      <td>{task.assignee.unwrap_or_default()}</td>
+      <td class:text-red-600=overdue>{task.due_date.map(format_due).unwrap_or_default()}</td>
+      <td>{task.recording_title.unwrap_or_default()}</td>
+      <td>{group_name}</td>
+    </tr>
+  }
+}
+
+fn format_due(date: NaiveDate) -> String {
+  date.format("%b %-d, %Y").to_string()
+}
@@ -0,0 +1,77 @@
+use leptos::*;
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+use crate::app::{get_trash, RestoreRecording};
+use crate::models::dtos::TrashedRecording;
+use crate::timezone::{use_timezone, Tz};
+
+#[component]
+pub fn TrashPage() -> impl IntoView {
+  let restore_action = create_server_action::<RestoreRecording>();
+
+  let trash_resource = create_resource(
+    move || restore_action.version().get(),
+    |_| async move { get_trash().await }
+  );
+
+  let on_restore = Callback::new(move |id: Uuid| {
+    restore_action.dispatch(RestoreRecording { id });
+  });
+
+  view! {
+    <div id="trashSection">
+      <h2 class="text-lg mb-2">"Trash"</h2>
+      <p class="text-sm text-gray-600 mb-2">"Deleted recordings are removed for good once their purge date has passed."</p>
+      <Transition fallback=move || view! { <p>"Loading trash..."</p> }>
+        {move || trash_resource.get().map(|res| match res {
+          Err(e) => view! { <p class="text-red-600">{e.to_string()}</p> }.into_view(),
+          Ok(items) if items.is_empty() => {
+            view! { <p class="text-sm text-gray-600">"The trash is empty."</p> }.into_view()
+          },
+          Ok(items) => view! {
+            <table class="data-table">
+              <thead>
+                <tr>
+                  <th>"Recording"</th>
+                  <th>"Recorded"</th>
+                  <th>"Deleted"</th>
+                  <th>"Purged after"</th>
+                  <th></th>
+                </tr>
+              </thead>
+              <tbody>
+                {items.into_iter().map(|item| view! {
+                  <TrashRow item=item on_restore=on_restore/>
+                }).collect_view()}
+              </tbody>
+            </table>
+          }.into_view()
+        })}
+      </Transition>
+    </div>
+  }
+}
+
+#[component]
+fn TrashRow(item: TrashedRecording, on_restore: Callback<Uuid>) -> impl IntoView {
+  let id = item.id;
+  let title = item.title.filter(|t| !t.is_empty()).unwrap_or(item.name);
+  let timezone = use_timezone();
+  let time = move |time: DateTime<Utc>| move || format_time(time, timezone.get());
+
+  view! {
+    <tr>
+      <td>{title}</td>
+      <td>{time(item.created_at)}</td>
+      <td>{time(item.deleted_at)}</td>
+      <td>{time(item.purge_at)}</td>
+      <td>
+        <button class="btn-icon" on:click=move |_| on_restore.call(id)>"Restore"</button>
+      </td>
+    </tr>
+  }
+}
+
+fn format_time(time: DateTime<Utc>, tz: Tz) -> String {
+  time.with_timezone(&tz).format("%b %-d, %I:%M %p").to_string()
+}
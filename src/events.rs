@@ -0,0 +1,92 @@
+use leptos::*;
+use crate::models::dtos::{RecordingFile, RecordingPage};
+#[cfg(not(feature = "ssr"))]
+use crate::models::dtos::RecordingEvent;
+
+/// Keeps a loaded list of recordings current with the changes the server pushes over
+/// `/events`. Changed rows are replaced in place, new ones are added when `in_view` says they
+/// belong to the list, and removed ones disappear. The stream is closed when the calling
+/// component is unmounted.
+pub fn use_recording_events<S>(
+    recordings: Resource<S, Result<RecordingPage, ServerFnError>>,
+    in_view: impl Fn(&RecordingFile) -> bool + 'static
+) where
+    S: Clone + 'static,
+{
+    #[cfg(not(feature = "ssr"))]
+    {
+        use std::cell::Cell;
+        use std::rc::Rc;
+        use wasm_bindgen::prelude::*;
+        use web_sys::{EventSource, MessageEvent};
+
+        let source = match EventSource::new("/events") {
+            Ok(source) => source,
+            Err(e) => {
+                web_sys::console::error_1(&e);
+                return;
+            }
+        };
+
+        let on_recording = Closure::wrap(Box::new(move |ev: MessageEvent| {
+            let Some(data) = ev.data().as_string() else {
+                return;
+            };
+            match serde_json::from_str::<RecordingEvent>(&data) {
+                Ok(RecordingEvent::Resync) => recordings.refetch(),
+                Ok(event) => recordings.update(|page| {
+                    if let Some(Ok(page)) = page {
+                        apply_event(&mut page.items, event, &in_view);
+                    }
+                }),
+                Err(e) => web_sys::console::error_1(&format!("Unreadable recording event: {}", e).into()),
+            }
+        }) as Box<dyn FnMut(MessageEvent)>);
+        let _ = source.add_event_listener_with_callback("recording", on_recording.as_ref().unchecked_ref());
+
+        // The browser reconnects by itself; whatever changed in between is fetched again
+        let reconnecting = Rc::new(Cell::new(false));
+        let on_error = Closure::wrap(Box::new({
+            let reconnecting = reconnecting.clone();
+            move || reconnecting.set(true)
+        }) as Box<dyn FnMut()>);
+        let on_open = Closure::wrap(Box::new(move || {
+            if reconnecting.replace(false) {
+                recordings.refetch();
+            }
+        }) as Box<dyn FnMut()>);
+        source.set_onerror(Some(on_error.as_ref().unchecked_ref()));
+        source.set_onopen(Some(on_open.as_ref().unchecked_ref()));
+
+        // The closures live as long as the stream they listen to
+        on_cleanup(move || {
+            source.close();
+            drop((on_recording, on_error, on_open));
+        });
+    }
+
+    #[cfg(feature = "ssr")]
+    {
+        let _ = (recordings, in_view);
+    }
+}
+
+// Lists are sorted newest first, like `GET /recordings` returns them
+#[cfg(not(feature = "ssr"))]
+fn apply_event(items: &mut Vec<RecordingFile>, event: RecordingEvent, in_view: &impl Fn(&RecordingFile) -> bool) {
+    match event {
+        RecordingEvent::Changed { recording } => {
+            if let Some(existing) = items.iter_mut().find(|item| item.id == recording.id) {
+                *existing = *recording;
+            } else if in_view(&recording) {
+                let position = items
+                    .iter()
+                    .position(|item| (item.created_at, item.id) < (recording.created_at, recording.id))
+                    .unwrap_or(items.len());
+                items.insert(position, *recording);
+            }
+        }
+        RecordingEvent::Removed { id } => items.retain(|item| item.id != id),
+        RecordingEvent::Resync => {}
+    }
+}
@@ -1,6 +1,8 @@
 pub mod app;
 pub mod models;
 pub mod components;
+pub mod timezone;
+pub mod events;
 
 #[cfg(feature = "ssr")]
 pub mod api;
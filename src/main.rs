@@ -1,8 +1,8 @@
 use axum::{
-    routing::{get, post, patch},
+    routing::{delete, get, post, patch, put},
     Router,
-    extract::{FromRef, State},
-    response::IntoResponse,
+    extract::{DefaultBodyLimit, FromRef, FromRequestParts, Query, State},
+    response::{IntoResponse, Redirect},
 };
 use tower_http::services::ServeDir;
 use tower::util::ServiceExt; // For oneshot
@@ -15,15 +15,65 @@ use workflow::api::recordings::{
     upload_handler,
     list_recordings,
     delete_recording,
-    update_recording
+    update_recording,
+    retranscribe_recording,
+    list_trash,
+    restore_recording
 };
 
-use workflow::api::groups::get_groups;
+use workflow::api::auth::{login, login_form, logout, logout_form, current_account, require_login, Read, TokenAuth, LOGIN_PATH};
+use workflow::api::files::serve_file;
+use workflow::api::groups::{
+    get_groups,
+    create_group,
+    update_group,
+    archive_group,
+    unarchive_group,
+    reorder_groups,
+    delete_group
+};
+use workflow::api::versions::{list_versions, get_version, restore_version};
+use workflow::api::search::search_recordings;
+use workflow::api::shares::{get_shares, post_share, delete_share, shared_audio};
+use workflow::api::digests::{list_digests, create_digest, get_digest, export_digest};
+use workflow::api::events::recording_events;
+use workflow::api::uploads::{create_upload, get_upload, put_upload_chunk, finalize_upload};
+use workflow::api::tasks::{list_tasks, complete_task, reopen_task};
+use workflow::api::tokens::{get_tokens, post_token, delete_token};
+use workflow::api::webhooks::{get_webhooks, post_webhook, remove_webhook, get_deliveries};
+use workflow::api::maintenance::{check_integrity, repair_integrity};
+use workflow::models::dtos::{IntegrityOptions, SearchQuery};
+use workflow::service::accounts::{claim_unowned, create_user, find_user, AuthUser, NewUser};
+use workflow::service::digest;
+use workflow::service::events::EventBus;
+use workflow::service::import::{import_directory, ImportOptions};
+use workflow::service::integrity::check_storage;
+use workflow::service::shares::share_secret_configured;
+use workflow::service::jobs::JobQueue;
+use workflow::service::storage::{storage_from_env, SharedStorage};
+use workflow::service::uploads::max_upload_bytes;
+use workflow::service::webhooks;
+use workflow::service::transcription::transcriber_from_env;
+use workflow::service::trash;
+use workflow::timezone::timezone_from_env;
 
 #[derive(Clone)]
 pub struct AppState {
     pub db: PgPool,
     pub leptos_options: LeptosOptions,
+    pub jobs: JobQueue,
+    pub events: EventBus,
+    pub storage: SharedStorage,
+}
+
+impl AppState {
+    // Makes shared server state available to `#[server]` functions
+    fn provide_contexts(&self) {
+        provide_context(self.db.clone());
+        provide_context(self.jobs.clone());
+        provide_context(self.events.clone());
+        provide_context(self.storage.clone());
+    }
 }
 
 impl FromRef<AppState> for PgPool {
@@ -38,6 +88,24 @@ impl FromRef<AppState> for LeptosOptions {
     }
 }
 
+impl FromRef<AppState> for JobQueue {
+    fn from_ref(state: &AppState) -> Self {
+        state.jobs.clone()
+    }
+}
+
+impl FromRef<AppState> for EventBus {
+    fn from_ref(state: &AppState) -> Self {
+        state.events.clone()
+    }
+}
+
+impl FromRef<AppState> for SharedStorage {
+    fn from_ref(state: &AppState) -> Self {
+        state.storage.clone()
+    }
+}
+
 #[tokio::main]
 async fn main() {
     dotenv::dotenv().ok();
@@ -46,36 +114,126 @@ async fn main() {
     let db_url = std::env::var("DATABASE_URL").expect("DATABASE_URL must be set");
     let pool = PgPool::connect(&db_url).await.expect("Failed to connect to Postgres");
 
+    // Recording storage
+    let storage = storage_from_env().expect("Failed to configure storage");
+
+    // Timezone that decides which day a recording belongs to
+    let timezone = timezone_from_env().expect("Failed to configure timezone");
+
+    // Maintenance commands run instead of the server
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if let Some(command) = args.first() {
+        let code = run_command(command, &args[1..], &pool, storage).await;
+        std::process::exit(code);
+    }
+    println!("storing recordings in {}", storage.name());
+    println!("using days in {}", timezone);
+    if !share_secret_configured() {
+        println!("SHARE_SECRET is not set; share links stop working when the server restarts");
+    }
+
+    // Transcription provider
+    let transcriber = transcriber_from_env().expect("Failed to configure transcription provider");
+    println!("transcribing with {}", transcriber.name());
+
+    // Transcription queue workers
+    let jobs = JobQueue::from_env();
+    jobs.start(pool.clone(), transcriber, storage.clone()).await.expect("Failed to start transcription queue");
+
+    // Daily and weekly digests
+    digest::start_scheduler(pool.clone());
+
+    // Signed webhook deliveries, retried until the receiver accepts them
+    webhooks::start_delivery(pool.clone()).await.expect("Failed to start webhook delivery");
+
+    // Permanently remove recordings that have been in the trash too long
+    trash::start_purge(pool.clone(), storage.clone());
+
     // Leptos Config
     let conf = get_configuration(None).await.unwrap();
     let leptos_options = conf.leptos_options;
     let addr = leptos_options.site_addr;
-    let routes = generate_route_list(App);
+    // `/search` is routed by hand so the page and the JSON API can share the path
+    let routes = generate_route_list(App)
+        .into_iter()
+        .filter(|route| route.path() != "/search")
+        .collect::<Vec<_>>();
 
     let site_root = leptos_options.site_root.clone();
     let pkg_dir = leptos_options.site_pkg_dir.clone();
     let pkg_path = format!("{}/{}", site_root, pkg_dir);
 
+    // Room for the multipart framing around the file itself
+    let upload_body_limit = usize::try_from(max_upload_bytes()).unwrap_or(usize::MAX).saturating_add(64 * 1024);
+
     let state = AppState {
         db: pool.clone(),
         leptos_options: leptos_options.clone(),
+        events: jobs.events().clone(),
+        jobs,
+        storage,
     };
 
+    // Pages need a signed-in user; the sign-in page is the exception
+    let pages = Router::new()
+        .leptos_routes_with_context(&state, routes, {
+            let state = state.clone();
+            move || state.provide_contexts()
+        }, App)
+        .route_layer(axum::middleware::from_fn_with_state(state.db.clone(), require_login));
+
     let app = Router::new()
+        // Accounts
+        .route("/auth/login", post(login))
+        .route("/auth/logout", post(logout))
+        .route("/auth/me", get(current_account))
+        .route("/auth/tokens", get(get_tokens).post(post_token))
+        .route("/auth/tokens/:id", delete(delete_token))
+        .route("/login", post(login_form))
+        .route("/logout", post(logout_form))
+
         // API Routes
-        .route("/upload", post(upload_handler))
+        .route("/upload", post(upload_handler).layer(DefaultBodyLimit::max(upload_body_limit)))
+        .route("/uploads", post(create_upload))
+        .route("/uploads/:id", get(get_upload).put(put_upload_chunk))
+        .route("/uploads/:id/finalize", post(finalize_upload))
         .route("/recordings", get(list_recordings).delete(delete_recording))
+        .route("/recordings/trash", get(list_trash))
         .route("/recordings/:id", patch(update_recording))
-        .route("/groups", get(get_groups))
+        .route("/recordings/:id/restore", post(restore_recording))
+        .route("/recordings/:id/retranscribe", post(retranscribe_recording))
+        .route("/recordings/:id/versions", get(list_versions))
+        .route("/recordings/:id/versions/:version_id", get(get_version))
+        .route("/recordings/:id/versions/:version_id/restore", post(restore_version))
+        .route("/recordings/:id/shares", get(get_shares).post(post_share))
+        .route("/recordings/:id/shares/:share_id", delete(delete_share))
+        .route("/groups", get(get_groups).post(create_group))
+        .route("/groups/order", put(reorder_groups))
+        .route("/groups/:id", patch(update_group).delete(delete_group))
+        .route("/groups/:id/archive", post(archive_group))
+        .route("/groups/:id/unarchive", post(unarchive_group))
+        .route("/tasks", get(list_tasks))
+        .route("/tasks/:id/done", post(complete_task))
+        .route("/tasks/:id/reopen", post(reopen_task))
+        .route("/digests", get(list_digests).post(create_digest))
+        .route("/digests/:id", get(get_digest))
+        .route("/digests/:id/markdown", get(export_digest))
+        .route("/search", get(search_or_page))
+        .route("/events", get(recording_events))
+        .route("/maintenance/integrity", get(check_integrity).post(repair_integrity))
+        .route("/webhooks", get(get_webhooks).post(post_webhook))
+        .route("/webhooks/:id", delete(remove_webhook))
+        .route("/webhooks/:id/deliveries", get(get_deliveries))
 
-        // Static file serving for recordings
-        .nest_service("/files", ServeDir::new("recordings"))
+        // Recordings, served from the configured storage
+        .route("/files/*key", get(serve_file))
+        .route("/share/:token/audio", get(shared_audio))
 
         // Serve Leptos pkg assets explicitly
         .nest_service(&format!("/{}", pkg_dir), ServeDir::new(pkg_path))
 
         // Leptos
-        .leptos_routes_with_context(&state, routes, move || provide_context(pool.clone()), App)
+        .merge(pages)
         .fallback(file_and_error_handler)
         .with_state(state);
 
@@ -84,19 +242,300 @@ async fn main() {
     axum::serve(listener, app).await.unwrap();
 }
 
-async fn file_and_error_handler(uri: axum::http::Uri, State(options): State<LeptosOptions>, State(pool): State<PgPool>, req: axum::http::Request<axum::body::Body>) -> axum::response::Response {
+const USAGE: &str = "Usage:
+  workflow                  run the server
+  workflow create-user <username> [--admin] [--timezone <IANA name>] [--claim-unowned]
+                            (reads the password from standard input)
+  workflow claim-unowned <username>
+  workflow check-storage [--reimport-orphans --user <username>] [--mark-broken] [--verify-checksums]
+  workflow import <directory> --user <username> [--dry-run] [--rate <transcriptions per hour>]";
+
+// Returns the process exit code: 1 if the command found problems or failed, 2 for bad usage
+async fn run_command(command: &str, flags: &[String], pool: &PgPool, storage: SharedStorage) -> i32 {
+    match command {
+        "check-storage" => {
+            let mut options = IntegrityOptions::default();
+            let mut username = None;
+            let mut flags = flags.iter();
+            while let Some(flag) = flags.next() {
+                match flag.as_str() {
+                    "--reimport-orphans" => options.reimport_orphans = true,
+                    "--mark-broken" => options.mark_broken = true,
+                    "--verify-checksums" => options.verify_checksums = true,
+                    "--user" => match flags.next() {
+                        Some(name) => username = Some(name.as_str()),
+                        None => {
+                            eprintln!("--user needs a username\n{}", USAGE);
+                            return 2;
+                        }
+                    },
+                    other => {
+                        eprintln!("Unknown option: {}\n{}", other, USAGE);
+                        return 2;
+                    }
+                }
+            }
+
+            // Reimported recordings need someone to belong to
+            let owner = match (options.reimport_orphans, username) {
+                (true, None) => {
+                    eprintln!("--reimport-orphans needs --user\n{}", USAGE);
+                    return 2;
+                }
+                (_, Some(username)) => match user_or_exit(pool, username).await {
+                    Ok(user) => Some(user),
+                    Err(code) => return code,
+                },
+                (false, None) => None,
+            };
+
+            // Reimported recordings are queued and transcribed once the server runs
+            let jobs = JobQueue::from_env();
+            match check_storage(pool, &jobs, storage.as_ref(), &options, owner.as_ref()).await {
+                Ok(report) => {
+                    for issue in &report.issues {
+                        println!("{:<14} {}  {}", issue.kind, issue.file_path, issue.detail);
+                    }
+                    println!(
+                        "Checked {} files and {} recordings in {}: {} problems, {} reimported, {} marked",
+                        report.checked_files,
+                        report.checked_rows,
+                        storage.name(),
+                        report.issues.len(),
+                        report.reimported,
+                        report.marked
+                    );
+                    if report.issues.is_empty() { 0 } else { 1 }
+                }
+                Err(e) => {
+                    eprintln!("Storage check failed: {}", e);
+                    1
+                }
+            }
+        }
+        "import" => {
+            let mut options = ImportOptions::default();
+            let mut directory = None;
+            let mut username = None;
+            let mut flags = flags.iter();
+            while let Some(flag) = flags.next() {
+                match flag.as_str() {
+                    "--dry-run" => options.dry_run = true,
+                    "--user" => match flags.next() {
+                        Some(name) => username = Some(name.as_str()),
+                        None => {
+                            eprintln!("--user needs a username\n{}", USAGE);
+                            return 2;
+                        }
+                    },
+                    "--rate" => match flags.next().and_then(|v| v.parse().ok()).filter(|n: &u32| *n > 0) {
+                        Some(rate) => options.jobs_per_hour = rate,
+                        None => {
+                            eprintln!("--rate needs a positive number of transcriptions per hour\n{}", USAGE);
+                            return 2;
+                        }
+                    },
+                    other if other.starts_with("--") || directory.is_some() => {
+                        eprintln!("Unknown option: {}\n{}", other, USAGE);
+                        return 2;
+                    }
+                    other => directory = Some(std::path::PathBuf::from(other)),
+                }
+            }
+            let Some(directory) = directory else {
+                eprintln!("import needs a directory\n{}", USAGE);
+                return 2;
+            };
+            let Some(username) = username else {
+                eprintln!("import needs --user to own the recordings\n{}", USAGE);
+                return 2;
+            };
+            let owner = match user_or_exit(pool, username).await {
+                Ok(user) => user,
+                Err(code) => return code,
+            };
+
+            // Imported recordings are transcribed by the server's workers at the given rate
+            let jobs = JobQueue::from_env();
+            match import_directory(pool, &jobs, storage.as_ref(), &directory, &owner, &options).await {
+                Ok(summary) => {
+                    let verb = if options.dry_run { "Would import" } else { "Imported" };
+                    println!(
+                        "{} {} recordings into {}: {} duplicates, {} skipped, {} failed",
+                        verb,
+                        summary.imported,
+                        storage.name(),
+                        summary.duplicates,
+                        summary.skipped,
+                        summary.failed
+                    );
+                    if let Some(last) = summary.last_transcription {
+                        println!("Transcription is queued at {} per hour until {}", options.jobs_per_hour, last.with_timezone(&owner.timezone).format("%Y-%m-%d %H:%M"));
+                    }
+                    if summary.failed == 0 { 0 } else { 1 }
+                }
+                Err(e) => {
+                    eprintln!("Import failed: {}", e);
+                    1
+                }
+            }
+        }
+        "create-user" => {
+            let mut username = None;
+            let mut is_admin = false;
+            let mut timezone = None;
+            let mut claim = false;
+            let mut flags = flags.iter();
+            while let Some(flag) = flags.next() {
+                match flag.as_str() {
+                    "--admin" => is_admin = true,
+                    "--claim-unowned" => claim = true,
+                    "--timezone" => match flags.next() {
+                        Some(name) => timezone = Some(name.as_str()),
+                        None => {
+                            eprintln!("--timezone needs an IANA timezone name\n{}", USAGE);
+                            return 2;
+                        }
+                    },
+                    other if other.starts_with("--") || username.is_some() => {
+                        eprintln!("Unknown option: {}\n{}", other, USAGE);
+                        return 2;
+                    }
+                    other => username = Some(other),
+                }
+            }
+            let Some(username) = username else {
+                eprintln!("create-user needs a username\n{}", USAGE);
+                return 2;
+            };
+
+            let password = match read_password() {
+                Ok(password) => password,
+                Err(e) => {
+                    eprintln!("Failed to read the password: {}", e);
+                    return 1;
+                }
+            };
+            let user = NewUser { username, password: &password, is_admin, timezone };
+            let id = match create_user(pool, &user).await {
+                Ok(id) => id,
+                Err(e) => {
+                    eprintln!("Failed to create {}: {}", username, e);
+                    return 1;
+                }
+            };
+            println!("Created {}{}", username, if is_admin { " (admin)" } else { "" });
+
+            if claim { claim_and_report(pool, id, username).await } else { 0 }
+        }
+        "claim-unowned" => {
+            let [username] = flags else {
+                eprintln!("claim-unowned needs a username\n{}", USAGE);
+                return 2;
+            };
+            match user_or_exit(pool, username).await {
+                Ok(user) => claim_and_report(pool, user.id, &user.username).await,
+                Err(code) => code,
+            }
+        }
+        "help" | "--help" | "-h" => {
+            println!("{}", USAGE);
+            0
+        }
+        other => {
+            eprintln!("Unknown command: {}\n{}", other, USAGE);
+            2
+        }
+    }
+}
+
+// The account a command acts for; the error is the exit code
+async fn user_or_exit(pool: &PgPool, username: &str) -> Result<AuthUser, i32> {
+    match find_user(pool, username).await {
+        Ok(Some(user)) => Ok(user),
+        Ok(None) => {
+            eprintln!("No user named {}", username);
+            Err(1)
+        }
+        Err(e) => {
+            eprintln!("Failed to look up {}: {}", username, e);
+            Err(1)
+        }
+    }
+}
+
+async fn claim_and_report(pool: &PgPool, user_id: uuid::Uuid, username: &str) -> i32 {
+    match claim_unowned(pool, user_id).await {
+        Ok(report) => {
+            println!(
+                "{} now owns {} recordings, {} task groups and {} digests from before accounts",
+                username, report.recordings, report.groups, report.digests
+            );
+            0
+        }
+        Err(e) => {
+            eprintln!("Failed to claim unowned data: {}", e);
+            1
+        }
+    }
+}
+
+// One line from standard input, so passwords can be piped in instead of showing up in `ps`
+fn read_password() -> std::io::Result<String> {
+    use std::io::{BufRead, IsTerminal, Write};
+    if std::io::stdin().is_terminal() {
+        eprint!("Password: ");
+        std::io::stderr().flush()?;
+    }
+    let mut password = String::new();
+    std::io::stdin().lock().read_line(&mut password)?;
+    Ok(password.trim_end_matches(['\r', '\n']).to_string())
+}
+
+async fn file_and_error_handler(uri: axum::http::Uri, State(state): State<AppState>, req: axum::http::Request<axum::body::Body>) -> axum::response::Response {
+    let options = state.leptos_options.clone();
     let root = options.site_root.clone();
     let res = get_static_file(uri.clone(), &root).await.unwrap();
 
     if res.status() == axum::http::StatusCode::OK {
         res.into_response()
     } else {
-        let handler = leptos_axum::render_app_to_stream_with_context(
-            options.clone(),
-            move || provide_context(pool.clone()),
-            App
-        );
-        handler(req).await.into_response()
+        render_app(state, req).await
+    }
+}
+
+async fn render_app(state: AppState, req: axum::http::Request<axum::body::Body>) -> axum::response::Response {
+    let handler = leptos_axum::render_app_to_stream_with_context(
+        state.leptos_options.clone(),
+        move || state.provide_contexts(),
+        App
+    );
+    handler(req).await.into_response()
+}
+
+// Browsers asking for HTML get the Search page, everything else gets JSON results.
+// Both need a signed-in user, like the routes they stand in for.
+async fn search_or_page(State(state): State<AppState>, req: axum::http::Request<axum::body::Body>) -> axum::response::Response {
+    let wants_html = req.headers()
+        .get(axum::http::header::ACCEPT)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|v| v.contains("text/html"));
+
+    let (mut parts, body) = req.into_parts();
+    let user = match TokenAuth::<Read>::from_request_parts(&mut parts, &state).await {
+        Ok(user) => user,
+        Err(_) if wants_html => return Redirect::to(LOGIN_PATH).into_response(),
+        Err(rejection) => return rejection.into_response(),
+    };
+
+    if wants_html {
+        parts.extensions.insert(user.0);
+        return render_app(state, axum::http::Request::from_parts(parts, body)).await;
+    }
+
+    match Query::<SearchQuery>::try_from_uri(&parts.uri) {
+        Ok(query) => search_recordings(State(state.db.clone()), user, query).await.into_response(),
+        Err(rejection) => rejection.into_response(),
     }
 }
 
@@ -1,3 +1,4 @@
+use chrono::{DateTime, Datelike, Duration, NaiveDate, Utc};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
@@ -8,6 +9,37 @@ pub struct TaskGroup {
     pub name: String,
     pub description: Option<String>,
     pub ordering: i32,
+    pub archived: bool,
+}
+
+#[derive(Deserialize)]
+pub struct GroupListQuery {
+    pub include_archived: Option<bool>,
+}
+
+#[derive(Deserialize)]
+pub struct CreateGroupRequest {
+    pub name: String,
+    pub description: Option<String>,
+}
+
+/// Fields left out are unchanged; an empty description clears it.
+#[derive(Deserialize)]
+pub struct UpdateGroupRequest {
+    pub name: Option<String>,
+    pub description: Option<String>,
+}
+
+/// Every group id, in the new order.
+#[derive(Deserialize)]
+pub struct ReorderGroupsRequest {
+    pub ids: Vec<Uuid>,
+}
+
+/// Where the recordings of a deleted group go; `None` leaves them ungrouped.
+#[derive(Deserialize)]
+pub struct DeleteGroupQuery {
+    pub reassign_to: Option<Uuid>,
 }
 
 #[derive(Serialize, Deserialize, Clone)]
@@ -16,9 +48,247 @@ pub struct RecordingFile {
     pub id: Uuid,
     pub path: String,
     pub name: String,
+    pub mime_type: String,
     pub status: String,
     pub transcription: Option<serde_json::Value>,
+    pub error: Option<String>,
+    pub group_id: Option<Uuid>,
+    pub suggested_group_id: Option<Uuid>,
+    pub suggestion_confidence: Option<f32>,
+    pub integrity_error: Option<String>,
+    /// When capture started; `created_at` for recordings without client capture times
+    pub started_at: DateTime<Utc>,
+    pub ended_at: Option<DateTime<Utc>>,
+    pub duration_ms: Option<i64>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Change to one of the user's recordings, pushed to open pages over `/events`.
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum RecordingEvent {
+    /// A recording was uploaded, restored or changed status; carries the row as listed
+    Changed { recording: Box<RecordingFile> },
+    /// A recording was moved to the trash
+    Removed { id: Uuid },
+    /// The stream fell behind and dropped events, so the list should be fetched again
+    Resync,
+}
+
+/// Recording in the trash; `purge_at` is when the background purge removes it.
+#[derive(Serialize, Deserialize, Clone)]
+#[cfg_attr(feature = "ssr", derive(sqlx::FromRow))]
+pub struct TrashedRecording {
+    pub id: Uuid,
+    pub name: String,
+    pub title: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub deleted_at: DateTime<Utc>,
+    pub purge_at: DateTime<Utc>,
+}
+
+/// Repairs to make while checking storage against the `recordings` table.
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct IntegrityOptions {
+    /// Create recordings (and transcription jobs) for audio files that have no row
+    #[serde(default)]
+    pub reimport_orphans: bool,
+    /// Store each problem in `recordings.integrity_error` and clear it on healthy rows
+    #[serde(default)]
+    pub mark_broken: bool,
+    /// Read every file and compare its SHA-256 with `recordings.sha256`
+    #[serde(default)]
+    pub verify_checksums: bool,
+}
+
+/// One mismatch between storage and the database. `kind` is `orphan_file`,
+/// `missing_file`, `empty_file`, `size_mismatch`, `checksum_mismatch` or `duplicate_audio`.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct IntegrityIssue {
+    pub kind: String,
+    pub file_path: String,
+    pub recording_id: Option<Uuid>,
+    pub detail: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct IntegrityReport {
+    pub checked_files: usize,
+    pub checked_rows: usize,
+    pub issues: Vec<IntegrityIssue>,
+    /// Orphans turned into recordings
+    pub reimported: usize,
+    /// Rows whose `integrity_error` was set or cleared
+    pub marked: usize,
+}
+
+/// Filters for listing recordings. Dates are `YYYY-MM-DD` and inclusive;
+/// `date` is shorthand for `from = to = date`. Without any date the list covers today.
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct RecordingQuery {
+    pub date: Option<String>,
+    pub from: Option<String>,
+    pub to: Option<String>,
+    pub group_id: Option<Uuid>,
+    pub status: Option<String>,
+    /// Full-text match on title and transcripts
+    pub q: Option<String>,
+    /// Case-insensitive substring match on the title
+    pub title: Option<String>,
+    /// Opaque `next_cursor` from the previous page
+    pub cursor: Option<String>,
+    pub limit: Option<i64>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Default)]
+pub struct RecordingPage {
+    pub items: Vec<RecordingFile>,
+    pub next_cursor: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+#[cfg_attr(feature = "ssr", derive(sqlx::FromRow))]
+pub struct TranscriptionVersion {
+    pub id: Uuid,
+    pub recording_id: Uuid,
+    pub source: String,
+    pub model: Option<String>,
+    pub prompt_hash: Option<String>,
+    pub restored_from: Option<Uuid>,
+    pub diff: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub transcription: serde_json::Value,
+}
+
+/// An action item extracted from a recording.
+#[derive(Serialize, Deserialize, Clone)]
+#[cfg_attr(feature = "ssr", derive(sqlx::FromRow))]
+pub struct Task {
+    pub id: Uuid,
+    pub recording_id: Uuid,
+    pub description: String,
+    pub assignee: Option<String>,
+    pub due_date: Option<NaiveDate>,
+    pub done_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    pub recording_title: Option<String>,
+    pub group_id: Option<Uuid>,
+}
+
+/// Filters for listing tasks. `status` is `open` (default), `done` or `all`.
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct TaskQuery {
+    pub status: Option<String>,
+    pub recording_id: Option<Uuid>,
+    pub group_id: Option<Uuid>,
+    /// Case-insensitive exact match on the assignee
+    pub assignee: Option<String>,
+}
+
+/// How much of the calendar the history view shows around the picked date.
+#[derive(Clone, Copy, PartialEq, Eq, Default)]
+pub enum DateSpan {
+    #[default]
+    Day,
+    Week,
+}
+
+impl DateSpan {
+    /// First and last day (inclusive) of the span containing `date`; weeks run Monday to Sunday.
+    pub fn bounds(self, date: NaiveDate) -> (NaiveDate, NaiveDate) {
+        match self {
+            DateSpan::Day => (date, date),
+            DateSpan::Week => {
+                let start = date - Duration::days(date.weekday().num_days_from_monday() as i64);
+                (start, start + Duration::days(6))
+            }
+        }
+    }
+}
+
+/// A stored review of one day or ISO week. `period_end` is inclusive.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct Digest {
+    pub id: Uuid,
+    pub kind: String,
+    pub period_start: NaiveDate,
+    pub period_end: NaiveDate,
+    pub content: DigestContent,
+    pub generated_at: DateTime<Utc>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Default)]
+pub struct DigestContent {
+    pub recording_count: i64,
+    pub groups: Vec<DigestGroup>,
+    pub open_tasks: Vec<Task>,
+}
+
+/// Recordings of one task group in the period. `group_id` is `None` for ungrouped recordings.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct DigestGroup {
     pub group_id: Option<Uuid>,
+    pub name: String,
+    pub minutes_spent: i64,
+    pub recordings: Vec<DigestEntry>,
+}
+
+impl DigestGroup {
+    /// Formats the time spent as e.g. `1h 25m`.
+    pub fn time_spent(&self) -> String {
+        match (self.minutes_spent / 60, self.minutes_spent % 60) {
+            (0, m) => format!("{}m", m),
+            (h, 0) => format!("{}h", h),
+            (h, m) => format!("{}h {}m", h, m),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct DigestEntry {
+    pub recording_id: Uuid,
+    pub created_at: DateTime<Utc>,
+    pub title: Option<String>,
+    pub excerpt: Option<String>,
+}
+
+/// `kind` is `day` or `week`; `date` is any day in the period and defaults to today.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct GenerateDigestRequest {
+    pub kind: String,
+    pub date: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct DigestListQuery {
+    pub kind: Option<String>,
+    pub limit: Option<i64>,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct SearchResult {
+    pub id: Uuid,
+    pub path: String,
+    pub name: String,
+    pub status: String,
+    pub title: Option<String>,
+    pub group_id: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+    pub rank: f32,
+    pub snippet: Vec<SnippetPart>,
+}
+
+/// A piece of a search snippet; `highlight` marks words that matched the query.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct SnippetPart {
+    pub text: String,
+    pub highlight: bool,
+}
+
+#[derive(Deserialize)]
+pub struct SearchQuery {
+    pub q: String,
+    pub limit: Option<i64>,
 }
 
 #[derive(Deserialize)]
@@ -26,6 +296,44 @@ pub struct DeleteRequest {
     pub path: String,
 }
 
+/// Capture times reported by the recording client, sent as multipart fields to `/upload`
+/// or as the body of `/uploads/:id/finalize`. Timestamps are RFC 3339; every field is optional.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default)]
+pub struct CaptureTimes {
+    pub started_at: Option<DateTime<Utc>>,
+    pub ended_at: Option<DateTime<Utc>>,
+    pub duration_ms: Option<i64>,
+}
+
+/// Recording created by `/upload`; `duplicate` means the same audio was already stored
+/// and `id` is the existing recording.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct UploadedRecording {
+    pub id: Uuid,
+    pub duplicate: bool,
+}
+
+/// Starts a resumable upload. `date` files the recording under that day, like `/upload?date=`.
+#[derive(Serialize, Deserialize, Clone, Default)]
+pub struct CreateUploadRequest {
+    pub date: Option<String>,
+    pub filename: Option<String>,
+}
+
+/// Progress of a resumable upload; the next chunk must be sent at `offset`.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct UploadStatus {
+    pub id: Uuid,
+    pub offset: i64,
+    pub status: String,
+    pub recording_id: Option<Uuid>,
+}
+
+#[derive(Deserialize)]
+pub struct ChunkQuery {
+    pub offset: i64,
+}
+
 #[derive(Deserialize)]
 pub struct DateFilter {
     pub date: Option<String>,
@@ -36,45 +344,207 @@ pub struct UpdateRecordingRequest {
     pub group_id: Option<Uuid>,
 }
 
-#[derive(Serialize)]
-pub struct GenerateContentRequest {
-    pub contents: Vec<Content>,
+/// Username and password for `/auth/login` and the sign-in form.
+#[derive(Deserialize)]
+pub struct LoginRequest {
+    pub username: String,
+    pub password: String,
 }
 
-#[derive(Serialize)]
-pub struct Content {
-    pub parts: Vec<Part>,
+/// The signed-in user, as returned by `/auth/me`.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Account {
+    pub id: Uuid,
+    pub username: String,
+    pub is_admin: bool,
+    pub timezone: String,
 }
 
-#[derive(Serialize)]
-#[serde(untagged)]
-pub enum Part {
-    Text { text: String },
-    InlineData { inline_data: InlineData },
+/// What an API token may do. `read` fetches recordings, groups, tasks, digests and events,
+/// `upload` adds recordings, `edit` changes recordings, transcripts, groups, tasks and digests,
+/// and `delete` moves recordings to and from the trash and deletes groups. Accounts, tokens,
+/// shares, webhooks and maintenance need a signed-in user.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum TokenScope {
+    Read,
+    Upload,
+    Edit,
+    Delete,
 }
 
-#[derive(Serialize)]
-pub struct InlineData {
-    pub mime_type: String,
-    pub data: String,
+impl TokenScope {
+    pub const ALL: [TokenScope; 4] = [TokenScope::Read, TokenScope::Upload, TokenScope::Edit, TokenScope::Delete];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            TokenScope::Read => "read",
+            TokenScope::Upload => "upload",
+            TokenScope::Edit => "edit",
+            TokenScope::Delete => "delete",
+        }
+    }
+
+    pub fn parse(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|scope| scope.as_str() == name)
+    }
 }
 
-#[derive(Deserialize)]
-pub struct GenerateContentResponse {
-    pub candidates: Option<Vec<Candidate>>,
+/// A personal API token as listed in the settings; the secret itself is only shown once.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct ApiToken {
+    pub id: Uuid,
+    pub name: String,
+    pub scopes: Vec<TokenScope>,
+    pub created_at: DateTime<Utc>,
+    pub last_used_at: Option<DateTime<Utc>>,
 }
 
-#[derive(Deserialize)]
-pub struct Candidate {
-    pub content: CandidateContent,
+/// Body for creating an API token.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct CreateTokenRequest {
+    pub name: String,
+    pub scopes: Vec<TokenScope>,
 }
 
-#[derive(Deserialize)]
-pub struct CandidateContent {
-    pub parts: Vec<CandidatePart>,
+/// A newly created token with the secret to send as `Authorization: Bearer <secret>`.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct CreatedToken {
+    pub token: ApiToken,
+    pub secret: String,
 }
 
-#[derive(Deserialize)]
-pub struct CandidatePart {
-    pub text: Option<String>,
-}
\ No newline at end of file
+/// Body for sharing a recording; links last 7 days unless asked otherwise.
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct CreateShareRequest {
+    pub expires_in_days: Option<i64>,
+}
+
+/// A share link of a recording, as listed to its owner.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct RecordingShare {
+    pub id: Uuid,
+    pub recording_id: Uuid,
+    /// Path of the public page, e.g. `/share/<token>`
+    pub url: String,
+    pub created_at: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
+}
+
+/// What a share link shows to someone without an account.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct SharedRecording {
+    pub title: Option<String>,
+    pub transcript: Option<String>,
+    pub audio_url: String,
+    pub created_at: DateTime<Utc>,
+    /// The owner's timezone, for showing `created_at`
+    pub timezone: String,
+    pub expires_at: DateTime<Utc>,
+}
+
+/// Recording events a webhook can be sent for.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum WebhookEvent {
+    Uploaded,
+    Transcribed,
+    Failed,
+    Regrouped,
+    Deleted,
+}
+
+impl WebhookEvent {
+    pub const ALL: [WebhookEvent; 5] = [
+        WebhookEvent::Uploaded,
+        WebhookEvent::Transcribed,
+        WebhookEvent::Failed,
+        WebhookEvent::Regrouped,
+        WebhookEvent::Deleted,
+    ];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            WebhookEvent::Uploaded => "uploaded",
+            WebhookEvent::Transcribed => "transcribed",
+            WebhookEvent::Failed => "failed",
+            WebhookEvent::Regrouped => "regrouped",
+            WebhookEvent::Deleted => "deleted",
+        }
+    }
+
+    pub fn parse(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|event| event.as_str() == name)
+    }
+}
+
+/// A webhook subscription as listed in the settings; the secret is only shown once.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct Webhook {
+    pub id: Uuid,
+    pub url: String,
+    pub events: Vec<WebhookEvent>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Body for creating a webhook; a secret is generated when none is given.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct CreateWebhookRequest {
+    pub url: String,
+    pub secret: Option<String>,
+    pub events: Vec<WebhookEvent>,
+}
+
+/// A newly created webhook with the secret its payloads are signed with.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct CreatedWebhook {
+    pub webhook: Webhook,
+    pub secret: String,
+}
+
+/// A payload queued for a webhook and how sending it went.
+#[derive(Serialize, Deserialize, Clone)]
+#[cfg_attr(feature = "ssr", derive(sqlx::FromRow))]
+pub struct WebhookDelivery {
+    pub id: Uuid,
+    pub event: String,
+    /// `QUEUED`, `RUNNING`, `DELIVERED` or `FAILED`
+    pub status: String,
+    pub attempts: i32,
+    pub response_status: Option<i32>,
+    pub last_error: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn day(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    #[test]
+    fn day_span_is_the_date_itself() {
+        assert_eq!(DateSpan::Day.bounds(day(2026, 10, 18)), (day(2026, 10, 18), day(2026, 10, 18)));
+    }
+
+    #[test]
+    fn week_span_runs_monday_to_sunday() {
+        // 2026-10-18 is a Sunday
+        assert_eq!(DateSpan::Week.bounds(day(2026, 10, 18)), (day(2026, 10, 12), day(2026, 10, 18)));
+        assert_eq!(DateSpan::Week.bounds(day(2026, 10, 12)), (day(2026, 10, 12), day(2026, 10, 18)));
+        // Weeks may straddle a year
+        assert_eq!(DateSpan::Week.bounds(day(2027, 1, 1)), (day(2026, 12, 28), day(2027, 1, 3)));
+    }
+
+    #[test]
+    fn time_spent_is_formatted_in_hours_and_minutes() {
+        let group = |minutes_spent| DigestGroup { group_id: None, name: String::new(), minutes_spent, recordings: Vec::new() };
+        assert_eq!(group(0).time_spent(), "0m");
+        assert_eq!(group(45).time_spent(), "45m");
+        assert_eq!(group(120).time_spent(), "2h");
+        assert_eq!(group(85).time_spent(), "1h 25m");
+    }
+}
@@ -0,0 +1,452 @@
+use std::fmt;
+use argon2::password_hash::rand_core::{OsRng, RngCore};
+use argon2::password_hash::{PasswordHash, PasswordHasher, PasswordVerifier, SaltString};
+use argon2::Argon2;
+use base64::{Engine as _, engine::general_purpose};
+use chrono::{DateTime, Utc};
+use sha2::{Digest, Sha256};
+use sqlx::PgPool;
+use uuid::Uuid;
+use crate::models::dtos::{Account, TokenScope};
+use crate::timezone::{app_timezone, parse_timezone, Tz};
+
+const DEFAULT_SESSION_DAYS: i64 = 30;
+const MIN_PASSWORD_LEN: usize = 8;
+/// Unique index on `lower(username)`; other unique violations are not about the name.
+const USERNAME_INDEX: &str = "idx_users_username";
+
+// Task groups every new account starts with, matching the ones seeded before accounts existed
+const DEFAULT_GROUPS: [(&str, &str); 3] = [
+    ("Day Part 1", "Delegation: Can I delegate this task?"),
+    ("Day Part 2", "Implementation: Doing/Implementing the task"),
+    ("Day Part 3", "Recurring: Recurring tasks, emails, etc."),
+];
+
+/// Days a sign-in lasts, from `SESSION_DAYS` (default 30).
+pub fn session_days_from_env() -> i64 {
+    std::env::var("SESSION_DAYS")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .filter(|days: &i64| *days > 0)
+        .unwrap_or(DEFAULT_SESSION_DAYS)
+}
+
+/// The signed-in user a request acts for.
+#[derive(Clone, Debug)]
+pub struct AuthUser {
+    pub id: Uuid,
+    pub username: String,
+    pub is_admin: bool,
+    /// The user's own timezone, or `APP_TIMEZONE` when they haven't set one
+    pub timezone: Tz,
+    /// What the API token the request came with may do; `None` for a signed-in session,
+    /// which may do everything
+    pub scopes: Option<Vec<TokenScope>>,
+}
+
+impl AuthUser {
+    pub fn allows(&self, scope: TokenScope) -> bool {
+        self.scopes.as_ref().is_none_or(|scopes| scopes.contains(&scope))
+    }
+
+    pub fn account(&self) -> Account {
+        Account {
+            id: self.id,
+            username: self.username.clone(),
+            is_admin: self.is_admin,
+            timezone: self.timezone.name().to_string(),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum AccountError {
+    Invalid(String),
+    UsernameTaken,
+    Database(sqlx::Error),
+}
+
+impl fmt::Display for AccountError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AccountError::Invalid(message) => write!(f, "{}", message),
+            AccountError::UsernameTaken => write!(f, "That username is already taken"),
+            AccountError::Database(e) => write!(f, "Database error: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for AccountError {}
+
+impl From<sqlx::Error> for AccountError {
+    fn from(e: sqlx::Error) -> Self {
+        match e {
+            sqlx::Error::Database(db) if db.is_unique_violation() && db.constraint() == Some(USERNAME_INDEX) => {
+                AccountError::UsernameTaken
+            }
+            e => AccountError::Database(e),
+        }
+    }
+}
+
+pub struct NewUser<'a> {
+    pub username: &'a str,
+    pub password: &'a str,
+    pub is_admin: bool,
+    pub timezone: Option<&'a str>,
+}
+
+/// Creates an account with the default task groups.
+pub async fn create_user(pool: &PgPool, user: &NewUser<'_>) -> Result<Uuid, AccountError> {
+    let username = user.username.trim();
+    if username.is_empty() || username.chars().any(char::is_whitespace) {
+        return Err(AccountError::Invalid("Usernames can't be empty or contain spaces".to_string()));
+    }
+    if user.password.chars().count() < MIN_PASSWORD_LEN {
+        return Err(AccountError::Invalid(format!("Passwords need at least {} characters", MIN_PASSWORD_LEN)));
+    }
+    if let Some(timezone) = user.timezone {
+        parse_timezone(timezone)
+            .ok_or_else(|| AccountError::Invalid(format!("{} is not an IANA timezone name", timezone)))?;
+    }
+    let password_hash = hash_password(user.password.to_string()).await?;
+
+    let mut tx = pool.begin().await?;
+    let id = sqlx::query_scalar!(
+        "INSERT INTO users (username, password_hash, is_admin, timezone) VALUES ($1, $2, $3, $4) RETURNING id",
+        username,
+        password_hash,
+        user.is_admin,
+        user.timezone.map(str::trim)
+    )
+    .fetch_one(&mut *tx)
+    .await?;
+
+    for (ordering, (name, description)) in DEFAULT_GROUPS.iter().enumerate() {
+        sqlx::query!(
+            "INSERT INTO task_groups (name, description, ordering, owner_id) VALUES ($1, $2, $3, $4)",
+            name,
+            description,
+            ordering as i32 + 1,
+            id
+        )
+        .execute(&mut *tx)
+        .await?;
+    }
+    tx.commit().await?;
+
+    Ok(id)
+}
+
+/// What `claim_unowned` handed over.
+#[derive(Default)]
+pub struct ClaimReport {
+    pub recordings: u64,
+    pub groups: u64,
+    pub digests: u64,
+}
+
+/// Gives recordings, task groups and digests from before accounts existed to `user_id`.
+/// Groups named like one of the user's own are merged into it and the rest are ordered after
+/// theirs; recordings whose audio the user already has and digests of periods they already
+/// have are left alone.
+pub async fn claim_unowned(pool: &PgPool, user_id: Uuid) -> Result<ClaimReport, sqlx::Error> {
+    let mut tx = pool.begin().await?;
+
+    // Every account starts with the groups that were seeded before accounts existed
+    let merges = sqlx::query!(
+        r#"
+        SELECT DISTINCT ON (legacy.id) legacy.id as "legacy_id", own.id as "own_id"
+        FROM task_groups legacy
+        JOIN task_groups own ON own.owner_id = $1 AND lower(own.name) = lower(legacy.name)
+        WHERE legacy.owner_id IS NULL
+        ORDER BY legacy.id, own.ordering
+        "#,
+        user_id
+    )
+    .fetch_all(&mut *tx)
+    .await?;
+
+    for merge in merges {
+        sqlx::query!(
+            r#"
+            UPDATE recordings
+            SET group_id = CASE WHEN group_id = $1 THEN $2 ELSE group_id END,
+                suggested_group_id = CASE WHEN suggested_group_id = $1 THEN $2 ELSE suggested_group_id END
+            WHERE group_id = $1 OR suggested_group_id = $1
+            "#,
+            merge.legacy_id,
+            merge.own_id
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        sqlx::query!("DELETE FROM task_groups WHERE id = $1", merge.legacy_id)
+            .execute(&mut *tx)
+            .await?;
+    }
+
+    let groups = sqlx::query!(
+        r#"
+        UPDATE task_groups
+        SET owner_id = $1,
+            ordering = ordering + (SELECT COALESCE(MAX(ordering), 0) FROM task_groups WHERE owner_id = $1)
+        WHERE owner_id IS NULL
+        "#,
+        user_id
+    )
+    .execute(&mut *tx)
+    .await?
+    .rows_affected();
+
+    let recordings = sqlx::query!(
+        r#"
+        UPDATE recordings r
+        SET owner_id = $1
+        WHERE r.owner_id IS NULL
+          AND NOT (r.deleted_at IS NULL AND EXISTS (
+              SELECT 1 FROM recordings o
+              WHERE o.owner_id = $1 AND o.sha256 = r.sha256 AND o.deleted_at IS NULL
+          ))
+        "#,
+        user_id
+    )
+    .execute(&mut *tx)
+    .await?
+    .rows_affected();
+
+    let digests = sqlx::query!(
+        r#"
+        UPDATE digests d
+        SET owner_id = $1
+        WHERE d.owner_id IS NULL
+          AND NOT EXISTS (
+              SELECT 1 FROM digests o
+              WHERE o.owner_id = $1 AND o.kind = d.kind AND o.period_start = d.period_start
+          )
+        "#,
+        user_id
+    )
+    .execute(&mut *tx)
+    .await?
+    .rows_affected();
+
+    sqlx::query!("UPDATE upload_sessions SET owner_id = $1 WHERE owner_id IS NULL", user_id)
+        .execute(&mut *tx)
+        .await?;
+
+    tx.commit().await?;
+    Ok(ClaimReport { recordings, groups, digests })
+}
+
+/// Looks up an account by name, case-insensitively.
+pub async fn find_user(pool: &PgPool, username: &str) -> Result<Option<AuthUser>, sqlx::Error> {
+    let row = sqlx::query!(
+        "SELECT id, username, is_admin, timezone FROM users WHERE lower(username) = lower($1)",
+        username.trim()
+    )
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(row.map(|row| AuthUser {
+        id: row.id,
+        username: row.username,
+        is_admin: row.is_admin,
+        timezone: user_timezone(row.timezone.as_deref()),
+        scopes: None,
+    }))
+}
+
+/// Every account, e.g. for background work done per user.
+pub async fn list_users(pool: &PgPool) -> Result<Vec<AuthUser>, sqlx::Error> {
+    let rows = sqlx::query!("SELECT id, username, is_admin, timezone FROM users ORDER BY created_at")
+        .fetch_all(pool)
+        .await?;
+
+    Ok(rows
+        .into_iter()
+        .map(|row| AuthUser {
+            id: row.id,
+            username: row.username,
+            is_admin: row.is_admin,
+            timezone: user_timezone(row.timezone.as_deref()),
+            scopes: None,
+        })
+        .collect())
+}
+
+/// Checks a username and password, returning the account's id when they match.
+pub async fn authenticate(pool: &PgPool, username: &str, password: &str) -> Result<Option<Uuid>, sqlx::Error> {
+    let row = sqlx::query!(
+        "SELECT id, password_hash FROM users WHERE lower(username) = lower($1)",
+        username.trim()
+    )
+    .fetch_optional(pool)
+    .await?;
+
+    // Unknown names still pay for a hash so response times don't reveal which accounts exist
+    let Some(row) = row else {
+        let _ = hash_password(password.to_string()).await;
+        return Ok(None);
+    };
+
+    let password = password.to_string();
+    let matches = tokio::task::spawn_blocking(move || {
+        PasswordHash::new(&row.password_hash)
+            .is_ok_and(|hash| Argon2::default().verify_password(password.as_bytes(), &hash).is_ok())
+    })
+    .await
+    .unwrap_or(false);
+
+    Ok(matches.then_some(row.id))
+}
+
+/// Starts a session for `user_id` and returns the token for the cookie with its expiry.
+/// Expired sessions are cleared out along the way.
+pub async fn create_session(pool: &PgPool, user_id: Uuid) -> Result<(String, DateTime<Utc>), sqlx::Error> {
+    sqlx::query!("DELETE FROM sessions WHERE expires_at < NOW()")
+        .execute(pool)
+        .await?;
+
+    let token = new_token();
+
+    let expires_at = sqlx::query_scalar!(
+        r#"
+        INSERT INTO sessions (user_id, token_hash, expires_at)
+        VALUES ($1, $2, NOW() + make_interval(days => $3))
+        RETURNING expires_at
+        "#,
+        user_id,
+        token_hash(&token),
+        session_days_from_env() as i32
+    )
+    .fetch_one(pool)
+    .await?;
+
+    Ok((token, expires_at))
+}
+
+/// The user a session token belongs to, if the session is still valid.
+pub async fn session_user(pool: &PgPool, token: &str) -> Result<Option<AuthUser>, sqlx::Error> {
+    let row = sqlx::query!(
+        r#"
+        SELECT u.id, u.username, u.is_admin, u.timezone
+        FROM sessions s
+        JOIN users u ON u.id = s.user_id
+        WHERE s.token_hash = $1 AND s.expires_at > NOW()
+        "#,
+        token_hash(token)
+    )
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(row.map(|row| AuthUser {
+        id: row.id,
+        username: row.username,
+        is_admin: row.is_admin,
+        timezone: user_timezone(row.timezone.as_deref()),
+        scopes: None,
+    }))
+}
+
+/// Signs a session out.
+pub async fn end_session(pool: &PgPool, token: &str) -> Result<(), sqlx::Error> {
+    sqlx::query!("DELETE FROM sessions WHERE token_hash = $1", token_hash(token))
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+/// A user's stored timezone, falling back to `APP_TIMEZONE` when they haven't set one.
+pub fn user_timezone(name: Option<&str>) -> Tz {
+    name.and_then(parse_timezone).unwrap_or_else(app_timezone)
+}
+
+// Argon2 is deliberately slow, so it runs off the async workers
+async fn hash_password(password: String) -> Result<String, AccountError> {
+    tokio::task::spawn_blocking(move || {
+        let salt = SaltString::generate(&mut OsRng);
+        Argon2::default()
+            .hash_password(password.as_bytes(), &salt)
+            .map(|hash| hash.to_string())
+            .map_err(|e| AccountError::Invalid(e.to_string()))
+    })
+    .await
+    .map_err(|e| AccountError::Invalid(e.to_string()))?
+}
+
+/// 32 random bytes, URL-safe base64 encoded.
+pub fn new_token() -> String {
+    let mut bytes = [0u8; 32];
+    OsRng.fill_bytes(&mut bytes);
+    general_purpose::URL_SAFE_NO_PAD.encode(bytes)
+}
+
+/// Only a digest of a token is stored, so a leaked table can't be replayed.
+pub fn token_hash(token: &str) -> String {
+    format!("{:x}", Sha256::digest(token.as_bytes()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::borrow::Cow;
+    use sqlx::error::{DatabaseError, ErrorKind};
+
+    #[derive(Debug)]
+    struct UniqueViolation(&'static str);
+
+    impl fmt::Display for UniqueViolation {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            write!(f, "duplicate key value violates unique constraint \"{}\"", self.0)
+        }
+    }
+
+    impl std::error::Error for UniqueViolation {}
+
+    impl DatabaseError for UniqueViolation {
+        fn message(&self) -> &str {
+            "duplicate key value violates unique constraint"
+        }
+
+        fn code(&self) -> Option<Cow<'_, str>> {
+            Some(Cow::Borrowed("23505"))
+        }
+
+        fn as_error(&self) -> &(dyn std::error::Error + Send + Sync + 'static) {
+            self
+        }
+
+        fn as_error_mut(&mut self) -> &mut (dyn std::error::Error + Send + Sync + 'static) {
+            self
+        }
+
+        fn into_error(self: Box<Self>) -> Box<dyn std::error::Error + Send + Sync + 'static> {
+            self
+        }
+
+        fn constraint(&self) -> Option<&str> {
+            Some(self.0)
+        }
+
+        fn kind(&self) -> ErrorKind {
+            ErrorKind::UniqueViolation
+        }
+    }
+
+    fn violation(constraint: &'static str) -> AccountError {
+        sqlx::Error::Database(Box::new(UniqueViolation(constraint))).into()
+    }
+
+    #[test]
+    fn only_the_username_index_means_the_name_is_taken() {
+        assert!(matches!(violation(USERNAME_INDEX), AccountError::UsernameTaken));
+        assert!(matches!(violation("task_groups_ordering_key"), AccountError::Database(_)));
+        assert!(matches!(violation("api_tokens_token_hash_key"), AccountError::Database(_)));
+    }
+
+    #[test]
+    fn other_errors_pass_through() {
+        assert!(matches!(AccountError::from(sqlx::Error::RowNotFound), AccountError::Database(_)));
+    }
+}
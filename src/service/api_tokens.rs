@@ -0,0 +1,121 @@
+use sqlx::PgPool;
+use uuid::Uuid;
+use crate::models::dtos::{ApiToken, CreateTokenRequest, CreatedToken, TokenScope};
+use crate::service::accounts::{new_token, token_hash, user_timezone, AccountError, AuthUser};
+
+/// Prefix of every token secret, so a leaked one is easy to recognise.
+pub const TOKEN_PREFIX: &str = "wf_";
+
+/// Creates a token for `user_id` and returns it with its secret, which is not stored.
+pub async fn create_token(pool: &PgPool, user_id: Uuid, request: &CreateTokenRequest) -> Result<CreatedToken, AccountError> {
+    let name = request.name.trim();
+    if name.is_empty() {
+        return Err(AccountError::Invalid("Tokens need a name".to_string()));
+    }
+    // Kept in the canonical order, without repeats
+    let scopes: Vec<TokenScope> = TokenScope::ALL
+        .into_iter()
+        .filter(|scope| request.scopes.contains(scope))
+        .collect();
+    if scopes.is_empty() {
+        return Err(AccountError::Invalid("Tokens need at least one scope".to_string()));
+    }
+
+    let secret = format!("{}{}", TOKEN_PREFIX, new_token());
+    let scope_names: Vec<String> = scopes.iter().map(|scope| scope.as_str().to_string()).collect();
+    let row = sqlx::query!(
+        r#"
+        INSERT INTO api_tokens (user_id, name, token_hash, scopes)
+        VALUES ($1, $2, $3, $4)
+        RETURNING id, created_at
+        "#,
+        user_id,
+        name,
+        token_hash(&secret),
+        &scope_names
+    )
+    .fetch_one(pool)
+    .await?;
+
+    Ok(CreatedToken {
+        token: ApiToken {
+            id: row.id,
+            name: name.to_string(),
+            scopes,
+            created_at: row.created_at,
+            last_used_at: None,
+        },
+        secret,
+    })
+}
+
+/// The user's tokens, newest first.
+pub async fn list_tokens(pool: &PgPool, user_id: Uuid) -> Result<Vec<ApiToken>, sqlx::Error> {
+    let rows = sqlx::query!(
+        r#"
+        SELECT id, name, scopes, created_at, last_used_at
+        FROM api_tokens
+        WHERE user_id = $1
+        ORDER BY created_at DESC, id DESC
+        "#,
+        user_id
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows
+        .into_iter()
+        .map(|row| ApiToken {
+            id: row.id,
+            name: row.name,
+            scopes: parse_scopes(&row.scopes),
+            created_at: row.created_at,
+            last_used_at: row.last_used_at,
+        })
+        .collect())
+}
+
+/// Revokes one of the user's tokens; requests with it are refused from then on.
+pub async fn revoke_token(pool: &PgPool, user_id: Uuid, id: Uuid) -> Result<(), sqlx::Error> {
+    sqlx::query!(
+        "DELETE FROM api_tokens WHERE id = $1 AND user_id = $2 RETURNING id",
+        id,
+        user_id
+    )
+    .fetch_optional(pool)
+    .await?
+    .ok_or(sqlx::Error::RowNotFound)?;
+    Ok(())
+}
+
+/// The user a token secret belongs to, limited to the token's scopes. Records when the
+/// token was last used.
+pub async fn token_user(pool: &PgPool, secret: &str) -> Result<Option<AuthUser>, sqlx::Error> {
+    let row = sqlx::query!(
+        r#"
+        WITH used AS (
+            UPDATE api_tokens SET last_used_at = NOW()
+            WHERE token_hash = $1
+            RETURNING user_id, scopes
+        )
+        SELECT u.id, u.username, u.is_admin, u.timezone, used.scopes
+        FROM used
+        JOIN users u ON u.id = used.user_id
+        "#,
+        token_hash(secret)
+    )
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(row.map(|row| AuthUser {
+        id: row.id,
+        username: row.username,
+        is_admin: row.is_admin,
+        timezone: user_timezone(row.timezone.as_deref()),
+        scopes: Some(parse_scopes(&row.scopes)),
+    }))
+}
+
+fn parse_scopes(names: &[String]) -> Vec<TokenScope> {
+    names.iter().filter_map(|name| TokenScope::parse(name)).collect()
+}
@@ -0,0 +1,391 @@
+use std::collections::HashMap;
+use std::fmt::Write;
+use std::time::Duration;
+use chrono::{DateTime, NaiveDate, Utc};
+use sqlx::PgPool;
+use uuid::Uuid;
+use crate::models::dtos::{DateSpan, Digest, DigestContent, DigestEntry, DigestGroup, Task};
+use crate::service::accounts::list_users;
+use crate::service::grouping::list_groups;
+use crate::timezone::{local_date, start_of_day, today, Tz};
+
+const SCHEDULE_INTERVAL: Duration = Duration::from_secs(15 * 60);
+// Longest gap between two recordings still counted as time spent on the earlier one
+const MAX_GAP_MINUTES: i64 = 90;
+const EXCERPT_CHARS: usize = 200;
+
+/// Maps the `kind` used in the API and the `digests` table to a calendar span.
+pub fn parse_kind(kind: &str) -> Option<DateSpan> {
+    match kind {
+        "day" => Some(DateSpan::Day),
+        "week" => Some(DateSpan::Week),
+        _ => None,
+    }
+}
+
+fn kind_name(span: DateSpan) -> &'static str {
+    match span {
+        DateSpan::Day => "day",
+        DateSpan::Week => "week",
+    }
+}
+
+/// Builds `owner_id`'s digest for the day or ISO week containing `date`, with days as seen
+/// in `tz`, and stores it, replacing an earlier digest of the same period.
+pub async fn generate_digest(pool: &PgPool, owner_id: Uuid, span: DateSpan, date: NaiveDate, tz: Tz) -> Result<Digest, sqlx::Error> {
+    let (start, end) = span.bounds(date);
+    let content = build_content(pool, owner_id, start, end, tz).await?;
+    let content_json = serde_json::to_value(&content)
+        .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
+
+    let row = sqlx::query!(
+        r#"
+        INSERT INTO digests (owner_id, kind, period_start, period_end, content)
+        VALUES ($1, $2, $3, $4, $5)
+        ON CONFLICT (owner_id, kind, period_start) DO UPDATE
+        SET period_end = EXCLUDED.period_end, content = EXCLUDED.content, generated_at = NOW()
+        RETURNING id, generated_at
+        "#,
+        owner_id,
+        kind_name(span),
+        start,
+        end,
+        content_json
+    )
+    .fetch_one(pool)
+    .await?;
+
+    Ok(Digest {
+        id: row.id,
+        kind: kind_name(span).to_string(),
+        period_start: start,
+        period_end: end,
+        content,
+        generated_at: row.generated_at,
+    })
+}
+
+async fn build_content(pool: &PgPool, owner_id: Uuid, start: NaiveDate, end: NaiveDate, tz: Tz) -> Result<DigestContent, sqlx::Error> {
+    let from = start_of_day(start, tz);
+    let until = start_of_day(end + chrono::Duration::days(1), tz);
+    let recordings = sqlx::query!(
+        r#"
+        SELECT
+            id,
+            created_at as "created_at!",
+            duration_ms,
+            group_id,
+            transcription_text->>'title' as "title",
+            coalesce(transcription_text->>'improved_transcript', transcription_text->>'transcript') as "transcript"
+        FROM recordings
+        WHERE deleted_at IS NULL
+          AND owner_id = $3
+          AND created_at >= $1 AND created_at < $2
+        ORDER BY created_at ASC, id ASC
+        "#,
+        from,
+        until,
+        owner_id
+    )
+    .fetch_all(pool)
+    .await?;
+
+    let open_tasks = sqlx::query_as!(
+        Task,
+        r#"
+        SELECT
+            t.id,
+            t.recording_id,
+            t.description,
+            t.assignee,
+            t.due_date,
+            t.done_at,
+            t.created_at,
+            r.transcription_text->>'title' as "recording_title",
+            r.group_id
+        FROM tasks t
+        JOIN recordings r ON r.id = t.recording_id
+        WHERE t.done_at IS NULL
+          AND r.deleted_at IS NULL
+          AND r.owner_id = $3
+          AND r.created_at >= $1 AND r.created_at < $2
+        ORDER BY t.due_date ASC NULLS LAST, t.created_at ASC
+        "#,
+        from,
+        until,
+        owner_id
+    )
+    .fetch_all(pool)
+    .await?;
+
+    // Time spent is the recorded duration, or estimated from the gap until the next
+    // recording of the same day for recordings without one
+    let mut minutes: HashMap<Option<Uuid>, i64> = HashMap::new();
+    for (index, current) in recordings.iter().enumerate() {
+        let spent = match (current.duration_ms, recordings.get(index + 1)) {
+            (Some(duration_ms), _) => (duration_ms + 59_999) / 60_000,
+            (None, Some(next))
+                if local_date(current.created_at, tz) == local_date(next.created_at, tz) =>
+            {
+                (next.created_at - current.created_at).num_minutes().min(MAX_GAP_MINUTES)
+            }
+            (None, _) => 0,
+        };
+        *minutes.entry(current.group_id).or_default() += spent;
+    }
+
+    let mut entries: HashMap<Option<Uuid>, Vec<DigestEntry>> = HashMap::new();
+    for recording in &recordings {
+        entries.entry(recording.group_id).or_default().push(DigestEntry {
+            recording_id: recording.id,
+            created_at: recording.created_at,
+            title: recording.title.clone(),
+            excerpt: recording.transcript.as_deref().map(excerpt),
+        });
+    }
+
+    // Groups in their configured order, ungrouped recordings last
+    let mut groups = Vec::new();
+    for group in list_groups(pool, owner_id, true).await? {
+        if let Some(recordings) = entries.remove(&Some(group.id)) {
+            groups.push(DigestGroup {
+                group_id: Some(group.id),
+                name: group.name,
+                minutes_spent: minutes.get(&Some(group.id)).copied().unwrap_or(0),
+                recordings,
+            });
+        }
+    }
+    if let Some(recordings) = entries.remove(&None) {
+        groups.push(DigestGroup {
+            group_id: None,
+            name: "Ungrouped".to_string(),
+            minutes_spent: minutes.get(&None).copied().unwrap_or(0),
+            recordings,
+        });
+    }
+
+    Ok(DigestContent {
+        recording_count: recordings.len() as i64,
+        groups,
+        open_tasks,
+    })
+}
+
+fn excerpt(text: &str) -> String {
+    let text = text.trim();
+    match text.char_indices().nth(EXCERPT_CHARS) {
+        Some((cut, _)) => format!("{}…", text[..cut].trim_end()),
+        None => text.to_string(),
+    }
+}
+
+/// Escapes text for a Markdown table cell, where a `|` would start a new column and a line break end the row.
+fn table_cell(text: &str) -> String {
+    text.replace('|', "\\|").replace(['\r', '\n'], " ")
+}
+
+/// Renders a digest for export, e.g. to paste into a team channel, with times in `tz`.
+pub fn render_markdown(digest: &Digest, tz: Tz) -> String {
+    let mut md = String::new();
+    let content = &digest.content;
+
+    let _ = match digest.kind.as_str() {
+        "week" => writeln!(
+            md,
+            "# Weekly digest: {} – {}",
+            digest.period_start.format("%B %-d"),
+            digest.period_end.format("%B %-d, %Y")
+        ),
+        _ => writeln!(md, "# Daily digest: {}", digest.period_start.format("%A, %B %-d, %Y")),
+    };
+    let _ = writeln!(md, "\n{} recordings.", content.recording_count);
+
+    if !content.groups.is_empty() {
+        md.push_str("\n## Time per day part\n\n| Group | Recordings | Time |\n| --- | --- | --- |\n");
+        for group in &content.groups {
+            let _ = writeln!(
+                md,
+                "| {} | {} | {} |",
+                table_cell(&group.name),
+                group.recordings.len(),
+                group.time_spent()
+            );
+        }
+    }
+
+    for group in &content.groups {
+        let _ = writeln!(md, "\n## {}\n", group.name);
+        for entry in &group.recordings {
+            let time = entry.created_at.with_timezone(&tz).format("%a %H:%M");
+            let title = entry.title.as_deref().unwrap_or("Untitled");
+            let _ = writeln!(md, "- **{}** ({})", title, time);
+            if let Some(excerpt) = &entry.excerpt {
+                let _ = writeln!(md, "  {}", excerpt);
+            }
+        }
+    }
+
+    md.push_str("\n## Open action items\n\n");
+    if content.open_tasks.is_empty() {
+        md.push_str("None.\n");
+    }
+    for task in &content.open_tasks {
+        let _ = write!(md, "- [ ] {}", task.description);
+        if let Some(assignee) = &task.assignee {
+            let _ = write!(md, " — {}", assignee);
+        }
+        if let Some(due) = task.due_date {
+            let _ = write!(md, " (due {})", due.format("%Y-%m-%d"));
+        }
+        md.push('\n');
+    }
+
+    md
+}
+
+/// Generates every user's digests of yesterday and last week once they are complete, with
+/// days in each user's timezone. Set `DIGEST_SCHEDULE=off` to only generate digests on request.
+pub fn start_scheduler(pool: PgPool) {
+    if std::env::var("DIGEST_SCHEDULE").is_ok_and(|v| v.eq_ignore_ascii_case("off")) {
+        return;
+    }
+
+    tokio::spawn(async move {
+        loop {
+            let users = match list_users(&pool).await {
+                Ok(users) => users,
+                Err(e) => {
+                    eprintln!("Failed to list users for digests: {}", e);
+                    Vec::new()
+                }
+            };
+            for user in users {
+                let today = today(user.timezone);
+                let due = [
+                    (DateSpan::Day, today - chrono::Duration::days(1)),
+                    (DateSpan::Week, today - chrono::Duration::days(7)),
+                ];
+                for (span, date) in due {
+                    if let Err(e) = ensure_digest(&pool, user.id, span, date, user.timezone).await {
+                        eprintln!("Failed to generate {} digest for {} of {}: {}", kind_name(span), date, user.username, e);
+                    }
+                }
+            }
+            tokio::time::sleep(SCHEDULE_INTERVAL).await;
+        }
+    });
+}
+
+// Generates the digest unless a complete one is stored. One generated on request before its
+// period was over is missing whatever came after it, so it is generated again.
+async fn ensure_digest(pool: &PgPool, owner_id: Uuid, span: DateSpan, date: NaiveDate, tz: Tz) -> Result<(), sqlx::Error> {
+    let (start, end) = span.bounds(date);
+    let stored = sqlx::query!(
+        "SELECT generated_at FROM digests WHERE owner_id = $1 AND kind = $2 AND period_start = $3",
+        owner_id,
+        kind_name(span),
+        start
+    )
+    .fetch_optional(pool)
+    .await?;
+
+    if !stored.is_some_and(|digest| covers_period(digest.generated_at, end, tz)) {
+        generate_digest(pool, owner_id, span, date, tz).await?;
+        println!("Generated {} digest for {} of {}", kind_name(span), start, owner_id);
+    }
+    Ok(())
+}
+
+// Whether a digest generated at `generated_at` saw the whole period ending on `end`
+fn covers_period(generated_at: DateTime<Utc>, end: NaiveDate, tz: Tz) -> bool {
+    generated_at >= start_of_day(end + chrono::Duration::days(1), tz)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn kinds_round_trip() {
+        for span in [DateSpan::Day, DateSpan::Week] {
+            assert!(parse_kind(kind_name(span)) == Some(span));
+        }
+        assert!(parse_kind("month").is_none());
+        assert!(parse_kind("Day").is_none());
+    }
+
+    #[test]
+    fn digests_generated_before_their_period_ended_are_incomplete() {
+        let berlin = chrono_tz::Europe::Berlin;
+        let sunday = NaiveDate::from_ymd_opt(2026, 10, 18).unwrap();
+        // Midnight after Sunday in Berlin is 22:00 UTC
+        let noon = Utc.with_ymd_and_hms(2026, 10, 18, 10, 0, 0).unwrap();
+        let just_before = Utc.with_ymd_and_hms(2026, 10, 18, 21, 59, 59).unwrap();
+        let midnight = Utc.with_ymd_and_hms(2026, 10, 18, 22, 0, 0).unwrap();
+        assert!(!covers_period(noon, sunday, berlin));
+        assert!(!covers_period(just_before, sunday, berlin));
+        assert!(covers_period(midnight, sunday, berlin));
+    }
+
+    #[test]
+    fn excerpt_cuts_long_text_on_a_character_boundary() {
+        assert_eq!(excerpt("  short  "), "short");
+
+        let long = "é".repeat(EXCERPT_CHARS + 10);
+        let cut = excerpt(&long);
+        assert_eq!(cut.chars().count(), EXCERPT_CHARS + 1);
+        assert!(cut.ends_with('…'));
+    }
+
+    #[test]
+    fn markdown_lists_groups_and_open_tasks() {
+        let created_at = Utc.with_ymd_and_hms(2026, 10, 14, 7, 5, 0).unwrap();
+        let digest = Digest {
+            id: Uuid::nil(),
+            kind: "day".to_string(),
+            period_start: NaiveDate::from_ymd_opt(2026, 10, 14).unwrap(),
+            period_end: NaiveDate::from_ymd_opt(2026, 10, 14).unwrap(),
+            content: DigestContent {
+                recording_count: 1,
+                groups: vec![DigestGroup {
+                    group_id: None,
+                    name: "Hiring".to_string(),
+                    minutes_spent: 85,
+                    recordings: vec![DigestEntry {
+                        recording_id: Uuid::nil(),
+                        created_at,
+                        title: None,
+                        excerpt: Some("Talked to Anna".to_string()),
+                    }],
+                }],
+                open_tasks: vec![Task {
+                    id: Uuid::nil(),
+                    recording_id: Uuid::nil(),
+                    description: "Send offer".to_string(),
+                    assignee: Some("Ben".to_string()),
+                    due_date: NaiveDate::from_ymd_opt(2026, 10, 16),
+                    done_at: None,
+                    created_at,
+                    recording_title: None,
+                    group_id: None,
+                }],
+            },
+            generated_at: created_at,
+        };
+
+        let md = render_markdown(&digest, chrono_tz::Europe::Berlin);
+        assert!(md.starts_with("# Daily digest: Wednesday, October 14, 2026\n"));
+        assert!(md.contains("| Hiring | 1 | 1h 25m |"));
+        assert!(md.contains("- **Untitled** (Wed 09:05)\n  Talked to Anna\n"));
+        assert!(md.contains("- [ ] Send offer — Ben (due 2026-10-16)\n"));
+    }
+
+    #[test]
+    fn table_cells_escape_pipes_and_line_breaks() {
+        assert_eq!(table_cell("Hiring"), "Hiring");
+        assert_eq!(table_cell("Sales | Ops"), "Sales \\| Ops");
+        assert_eq!(table_cell("Q4\nplanning"), "Q4 planning");
+    }
+}
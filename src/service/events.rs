@@ -0,0 +1,108 @@
+use sqlx::PgPool;
+use tokio::sync::broadcast;
+use uuid::Uuid;
+use crate::models::dtos::{RecordingEvent, RecordingFile};
+
+// Events a slow subscriber may fall behind by before it is told to resync
+const CHANNEL_CAPACITY: usize = 256;
+
+/// An event and the user it is for.
+#[derive(Clone)]
+pub struct OwnedEvent {
+    pub owner_id: Uuid,
+    pub event: RecordingEvent,
+}
+
+/// Broadcasts recording changes to the open `/events` streams.
+///
+/// Publishing never fails: with nobody listening the event is simply dropped, and pages
+/// that connect later load the current list anyway.
+#[derive(Clone)]
+pub struct EventBus {
+    sender: broadcast::Sender<OwnedEvent>,
+}
+
+impl Default for EventBus {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl EventBus {
+    pub fn new() -> Self {
+        let (sender, _) = broadcast::channel(CHANNEL_CAPACITY);
+        Self { sender }
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<OwnedEvent> {
+        self.sender.subscribe()
+    }
+
+    pub fn publish(&self, owner_id: Uuid, event: RecordingEvent) {
+        let _ = self.sender.send(OwnedEvent { owner_id, event });
+    }
+
+    /// Publishes the current state of a recording. Events are best effort, so a failed
+    /// lookup is only logged.
+    pub async fn recording_changed(&self, pool: &PgPool, id: Uuid) {
+        if self.sender.receiver_count() == 0 {
+            return;
+        }
+        match listed_recording(pool, id).await {
+            Ok(Some((owner_id, recording))) => self.publish(owner_id, RecordingEvent::Changed { recording: Box::new(recording) }),
+            Ok(None) => {}
+            Err(e) => eprintln!("Failed to load recording {} for its event: {}", id, e),
+        }
+    }
+
+    pub fn recording_removed(&self, owner_id: Uuid, id: Uuid) {
+        self.publish(owner_id, RecordingEvent::Removed { id });
+    }
+}
+
+// The recording as `GET /recordings` lists it, with its owner; nothing for unowned or trashed ones
+async fn listed_recording(pool: &PgPool, id: Uuid) -> Result<Option<(Uuid, RecordingFile)>, sqlx::Error> {
+    let row = sqlx::query!(
+        r#"
+        SELECT
+            owner_id as "owner_id!",
+            '/files/' || file_path as "path!",
+            filename as "name!",
+            transcription_status as "status!",
+            transcription_text as "transcription",
+            transcription_error as "error",
+            mime_type,
+            group_id,
+            suggested_group_id,
+            suggestion_confidence,
+            integrity_error,
+            coalesce(started_at, created_at) as "started_at!",
+            ended_at,
+            duration_ms,
+            created_at as "created_at!"
+        FROM recordings
+        WHERE id = $1 AND deleted_at IS NULL AND owner_id IS NOT NULL
+        "#,
+        id
+    )
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(row.map(|row| (row.owner_id, RecordingFile {
+        id,
+        path: row.path,
+        name: row.name,
+        mime_type: row.mime_type,
+        status: row.status,
+        transcription: row.transcription,
+        error: row.error,
+        group_id: row.group_id,
+        suggested_group_id: row.suggested_group_id,
+        suggestion_confidence: row.suggestion_confidence,
+        integrity_error: row.integrity_error,
+        started_at: row.started_at,
+        ended_at: row.ended_at,
+        duration_ms: row.duration_ms,
+        created_at: row.created_at,
+    })))
+}
@@ -0,0 +1,172 @@
+use serde_json::Value;
+use sqlx::{PgConnection, PgPool};
+use uuid::Uuid;
+use crate::models::dtos::TaskGroup;
+
+/// The owner's task groups in their configured order, archived ones only if asked for.
+pub async fn list_groups(pool: &PgPool, owner_id: Uuid, include_archived: bool) -> Result<Vec<TaskGroup>, sqlx::Error> {
+    sqlx::query_as!(
+        TaskGroup,
+        r#"
+        SELECT id, name, description, ordering, archived_at IS NOT NULL as "archived!"
+        FROM task_groups
+        WHERE owner_id = $1 AND ($2 OR archived_at IS NULL)
+        ORDER BY ordering ASC
+        "#,
+        owner_id,
+        include_archived
+    )
+    .fetch_all(pool)
+    .await
+}
+
+/// Task group proposed by the model for a recording.
+pub struct GroupSuggestion {
+    pub group_id: Uuid,
+    pub confidence: f32,
+}
+
+/// Reads `GROUP_SUGGESTION_THRESHOLD`; suggestions at or above it are applied
+/// automatically to recordings without a group. Unset means never.
+pub fn auto_apply_threshold_from_env() -> Option<f32> {
+    std::env::var("GROUP_SUGGESTION_THRESHOLD")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .filter(|t: &f32| (0.0..=1.0).contains(t))
+}
+
+/// Removes the classification fields from a model reply and matches the
+/// suggested name against the groups that were offered.
+pub fn take_suggestion(reply: &mut Value, groups: &[TaskGroup]) -> Option<GroupSuggestion> {
+    let fields = reply.as_object_mut()?;
+    let name = fields.remove("suggested_group");
+    let confidence = fields.remove("group_confidence");
+
+    let name = name?;
+    let name = name.as_str()?.trim();
+    let group = groups.iter().find(|g| g.name.trim().eq_ignore_ascii_case(name))?;
+    let confidence = confidence
+        .and_then(|c| c.as_f64())
+        .unwrap_or(0.0)
+        .clamp(0.0, 1.0) as f32;
+
+    Some(GroupSuggestion { group_id: group.id, confidence })
+}
+
+/// Stores the suggestion and, if it clears the threshold, assigns the group
+/// to recordings that don't have one yet. Returns whether the group was assigned.
+pub async fn save_suggestion(
+    conn: &mut PgConnection,
+    recording_id: Uuid,
+    suggestion: Option<GroupSuggestion>,
+    auto_apply_threshold: Option<f32>
+) -> Result<bool, sqlx::Error> {
+    let (group_id, confidence) = match &suggestion {
+        Some(s) => (Some(s.group_id), Some(s.confidence)),
+        None => (None, None),
+    };
+    let auto_apply = match (&suggestion, auto_apply_threshold) {
+        (Some(s), Some(threshold)) => s.confidence >= threshold,
+        _ => false,
+    };
+
+    let record = sqlx::query!(
+        r#"
+        UPDATE recordings r
+        SET suggested_group_id = $1,
+            suggestion_confidence = $2,
+            group_id = CASE WHEN r.group_id IS NULL AND $3 THEN $1 ELSE r.group_id END
+        FROM (SELECT id, group_id FROM recordings WHERE id = $4 FOR UPDATE) previous
+        WHERE r.id = previous.id
+        RETURNING r.group_id IS DISTINCT FROM previous.group_id as "regrouped!"
+        "#,
+        group_id,
+        confidence,
+        auto_apply,
+        recording_id
+    )
+    .fetch_optional(&mut *conn)
+    .await?;
+
+    Ok(record.is_some_and(|r| r.regrouped))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn group(name: &str) -> TaskGroup {
+        TaskGroup { id: Uuid::new_v4(), name: name.to_string(), description: None, ordering: 0, archived: false }
+    }
+
+    #[test]
+    fn matches_suggested_name_ignoring_case_and_spaces() {
+        let groups = vec![group("Hiring"), group(" Budget ")];
+        let mut reply = json!({"title": "t", "suggested_group": "budget ", "group_confidence": 0.8});
+
+        let suggestion = take_suggestion(&mut reply, &groups).unwrap();
+        assert_eq!(suggestion.group_id, groups[1].id);
+        assert_eq!(suggestion.confidence, 0.8);
+        assert_eq!(reply, json!({"title": "t"}));
+    }
+
+    #[test]
+    fn clamps_confidence_and_defaults_it_to_zero() {
+        let groups = vec![group("Hiring")];
+        let mut high = json!({"suggested_group": "Hiring", "group_confidence": 7});
+        assert_eq!(take_suggestion(&mut high, &groups).unwrap().confidence, 1.0);
+
+        let mut missing = json!({"suggested_group": "Hiring"});
+        assert_eq!(take_suggestion(&mut missing, &groups).unwrap().confidence, 0.0);
+
+        let mut text = json!({"suggested_group": "Hiring", "group_confidence": "high"});
+        assert_eq!(take_suggestion(&mut text, &groups).unwrap().confidence, 0.0);
+    }
+
+    #[test]
+    fn unknown_or_null_groups_are_dropped_from_the_reply() {
+        let groups = vec![group("Hiring")];
+        let mut unknown = json!({"title": "t", "suggested_group": "Travel", "group_confidence": 0.9});
+        assert!(take_suggestion(&mut unknown, &groups).is_none());
+        assert_eq!(unknown, json!({"title": "t"}));
+
+        let mut null = json!({"suggested_group": null, "group_confidence": 0.9});
+        assert!(take_suggestion(&mut null, &groups).is_none());
+        assert_eq!(null, json!({}));
+    }
+
+    #[test]
+    fn replies_without_suggestion_are_left_alone() {
+        let mut reply = json!({"title": "t"});
+        assert!(take_suggestion(&mut reply, &[group("Hiring")]).is_none());
+        assert_eq!(reply, json!({"title": "t"}));
+    }
+
+    #[sqlx::test]
+    async fn auto_applied_suggestions_report_the_regroup(pool: PgPool) {
+        let user = crate::service::accounts::NewUser { username: "alice", password: "correct horse", is_admin: false, timezone: None };
+        let owner_id = crate::service::accounts::create_user(&pool, &user).await.unwrap();
+        let group_id = sqlx::query_scalar!(
+            "INSERT INTO task_groups (owner_id, name, ordering) VALUES ($1, 'Hiring', 0) RETURNING id",
+            owner_id
+        )
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+        let recording_id = sqlx::query_scalar!(
+            "INSERT INTO recordings (filename, file_path, mime_type, owner_id) VALUES ('a.webm', '2026/10/18/a.webm', 'audio/webm', $1) RETURNING id",
+            owner_id
+        )
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+        let suggestion = || Some(GroupSuggestion { group_id, confidence: 0.9 });
+
+        let mut conn = pool.acquire().await.unwrap();
+        assert!(!save_suggestion(&mut conn, recording_id, suggestion(), Some(0.95)).await.unwrap());
+        assert!(save_suggestion(&mut conn, recording_id, suggestion(), Some(0.8)).await.unwrap());
+        // Already filed under the group, so nothing changes the second time
+        assert!(!save_suggestion(&mut conn, recording_id, suggestion(), Some(0.8)).await.unwrap());
+    }
+}
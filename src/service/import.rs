@@ -0,0 +1,353 @@
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+use chrono::{DateTime, Datelike, Duration, NaiveDate, NaiveDateTime, TimeZone, Utc};
+use sqlx::PgPool;
+use tokio::io::AsyncReadExt;
+use uuid::Uuid;
+use walkdir::WalkDir;
+use crate::models::dtos::CaptureTimes;
+use crate::service::accounts::AuthUser;
+use crate::service::jobs::JobQueue;
+use crate::service::storage::{Storage, StorageError};
+use crate::service::uploads::{
+    discard_stored, file_sha256, find_duplicate, max_upload_bytes, register_recording, relative_dir_for, spool_dir,
+    store_upload, AudioFormat, NewRecording, Registered, SNIFF_LEN
+};
+use crate::timezone::Tz;
+
+/// Transcriptions queued per hour by an import unless `--rate` says otherwise.
+pub const DEFAULT_JOBS_PER_HOUR: u32 = 60;
+
+pub struct ImportOptions {
+    /// Report what would be imported without copying anything or touching the database
+    pub dry_run: bool,
+    /// Imported recordings are queued for transcription at this rate
+    pub jobs_per_hour: u32,
+}
+
+impl Default for ImportOptions {
+    fn default() -> Self {
+        Self { dry_run: false, jobs_per_hour: DEFAULT_JOBS_PER_HOUR }
+    }
+}
+
+#[derive(Default)]
+pub struct ImportSummary {
+    pub imported: usize,
+    pub duplicates: usize,
+    pub skipped: usize,
+    pub failed: usize,
+    /// When the last imported recording is due to be transcribed
+    pub last_transcription: Option<DateTime<Utc>>,
+}
+
+enum ImportOutcome {
+    Imported { file_path: String, recorded_at: DateTime<Tz>, source: &'static str },
+    WouldImport { relative_dir: String, recorded_at: DateTime<Tz>, source: &'static str },
+    Duplicate(Uuid),
+    Skipped(&'static str),
+}
+
+/// Copies every audio file below `root` into `owner`'s recordings under the day it was recorded
+/// (in their timezone) and queues it for transcription, printing one line per file. Files that were
+/// imported before are recognised by checksum, so an interrupted import resumes by running it again.
+pub async fn import_directory(
+    pool: &PgPool,
+    jobs: &JobQueue,
+    storage: &dyn Storage,
+    root: &Path,
+    owner: &AuthUser,
+    options: &ImportOptions
+) -> Result<ImportSummary, StorageError> {
+    let metadata = tokio::fs::metadata(root).await.map_err(|e| format!("{}: {}", root.display(), e))?;
+    if !metadata.is_dir() {
+        return Err(format!("{} is not a directory", root.display()).into());
+    }
+    let files = list_files(root.to_path_buf()).await?;
+
+    // Continue after work that is already queued so reruns don't double the rate
+    let interval = chrono::Duration::seconds(3600 / i64::from(options.jobs_per_hour.max(1)));
+    let mut next_slot = match jobs.last_scheduled(pool).await? {
+        Some(last) if last > Utc::now() => last + interval,
+        _ => Utc::now(),
+    };
+
+    let mut summary = ImportSummary::default();
+    for file in files {
+        let path = match file {
+            Ok(path) => path,
+            Err(e) => {
+                println!("failed      {}", e);
+                summary.failed += 1;
+                continue;
+            }
+        };
+
+        match import_file(pool, jobs, storage, &path, options, owner, next_slot).await {
+            Ok(ImportOutcome::Imported { file_path, recorded_at, source }) => {
+                println!("imported    {} -> {} ({}, {})", path.display(), file_path, format_time(recorded_at), source);
+                summary.imported += 1;
+                summary.last_transcription = Some(next_slot);
+                next_slot += interval;
+            }
+            Ok(ImportOutcome::WouldImport { relative_dir, recorded_at, source }) => {
+                println!("would import {} -> {}/ ({}, {})", path.display(), relative_dir, format_time(recorded_at), source);
+                summary.imported += 1;
+            }
+            Ok(ImportOutcome::Duplicate(id)) => {
+                println!("duplicate   {} (recording {})", path.display(), id);
+                summary.duplicates += 1;
+            }
+            Ok(ImportOutcome::Skipped(reason)) => {
+                println!("skipped     {} ({})", path.display(), reason);
+                summary.skipped += 1;
+            }
+            Err(e) => {
+                println!("failed      {} ({})", path.display(), e);
+                summary.failed += 1;
+            }
+        }
+    }
+
+    Ok(summary)
+}
+
+// Regular files below `root` in name order, skipping hidden files and directories
+async fn list_files(root: PathBuf) -> Result<Vec<Result<PathBuf, walkdir::Error>>, StorageError> {
+    let files = tokio::task::spawn_blocking(move || {
+        WalkDir::new(&root)
+            .sort_by_file_name()
+            .into_iter()
+            .filter_entry(|entry| entry.depth() == 0 || !entry.file_name().to_string_lossy().starts_with('.'))
+            .filter_map(|entry| match entry {
+                Ok(entry) if entry.file_type().is_file() => Some(Ok(entry.into_path())),
+                Ok(_) => None,
+                Err(e) => Some(Err(e)),
+            })
+            .collect()
+    })
+    .await?;
+    Ok(files)
+}
+
+async fn import_file(
+    pool: &PgPool,
+    jobs: &JobQueue,
+    storage: &dyn Storage,
+    path: &Path,
+    options: &ImportOptions,
+    owner: &AuthUser,
+    transcribe_at: DateTime<Utc>
+) -> Result<ImportOutcome, StorageError> {
+    let metadata = tokio::fs::metadata(path).await?;
+    if metadata.len() == 0 {
+        return Ok(ImportOutcome::Skipped("empty file"));
+    }
+    if metadata.len() > max_upload_bytes() {
+        return Ok(ImportOutcome::Skipped("larger than UPLOAD_MAX_BYTES"));
+    }
+
+    let mut header = Vec::with_capacity(SNIFF_LEN);
+    tokio::fs::File::open(path).await?.take(SNIFF_LEN as u64).read_to_end(&mut header).await?;
+    let Some(format) = AudioFormat::detect(&header) else {
+        return Ok(ImportOutcome::Skipped("not a recognised audio format"));
+    };
+
+    let sha256 = file_sha256(path).await?;
+    if let Some(id) = find_duplicate(pool, owner.id, &sha256).await? {
+        return Ok(ImportOutcome::Duplicate(id));
+    }
+
+    let (recorded_at, source) = recorded_at(path, &metadata, owner.timezone);
+    let relative_dir = relative_dir_for(recorded_at.date_naive());
+    if options.dry_run {
+        return Ok(ImportOutcome::WouldImport { relative_dir, recorded_at, source });
+    }
+
+    // Copy into the spool first; storage takes the copy and the original stays untouched
+    let spool = spool_dir(&relative_dir);
+    tokio::fs::create_dir_all(&spool).await?;
+    let temp_path = spool.join(format!(".import-{}.part", Uuid::new_v4()));
+    let mime_type = format.mime_type(&header);
+    let stem = format!("recording_{}", recorded_at.timestamp());
+
+    let stored = match tokio::fs::copy(path, &temp_path).await {
+        Ok(_) => store_upload(storage, &temp_path, &relative_dir, &stem, format, &mime_type).await,
+        Err(e) => Err(e.into()),
+    };
+    let filename = match stored {
+        Ok(filename) => filename,
+        Err(e) => {
+            let _ = tokio::fs::remove_file(&temp_path).await;
+            return Err(e);
+        }
+    };
+    let file_path = format!("{}/{}", relative_dir, filename);
+
+    let recording = NewRecording {
+        owner_id: owner.id,
+        filename: &filename,
+        file_path: &file_path,
+        mime_type: &mime_type,
+        size_bytes: metadata.len() as i64,
+        sha256: &sha256,
+        recorded_at: recorded_at.with_timezone(&Utc),
+        capture: CaptureTimes::default(),
+        transcribe_at: Some(transcribe_at),
+    };
+    match register_recording(pool, jobs, &recording).await {
+        Ok(Registered::New(_)) => Ok(ImportOutcome::Imported { file_path, recorded_at, source }),
+        Ok(Registered::Duplicate(id)) => {
+            discard_stored(storage, &file_path).await;
+            Ok(ImportOutcome::Duplicate(id))
+        }
+        Err(e) => {
+            discard_stored(storage, &file_path).await;
+            Err(e.into())
+        }
+    }
+}
+
+// Prefers a timestamp in the file name, since copying files usually loses their original dates
+fn recorded_at(path: &Path, metadata: &std::fs::Metadata, tz: Tz) -> (DateTime<Tz>, &'static str) {
+    let stem = path.file_stem().map(|s| s.to_string_lossy()).unwrap_or_default();
+    if let Some(time) = timestamp_from_name(&stem, tz) {
+        return (time, "from file name");
+    }
+
+    // The earlier of creation and modification time; either may be unavailable
+    let time = [metadata.created().ok(), metadata.modified().ok()]
+        .into_iter()
+        .flatten()
+        .min()
+        .unwrap_or_else(SystemTime::now);
+    (DateTime::<Utc>::from(time).with_timezone(&tz), "from file metadata")
+}
+
+/// Finds a recording time in names such as `20230514_093012`, `2023-05-14 09.30.12`,
+/// `Voice 2023-05-14` or `recording_1684056612` (Unix seconds), read as local time in `tz`.
+/// A date alone means noon. Times skipped by a daylight saving change are read an hour later,
+/// and repeated ones as the first occurrence.
+pub fn timestamp_from_name(name: &str, tz: Tz) -> Option<DateTime<Tz>> {
+    let groups: Vec<&str> = name
+        .split(|c: char| !c.is_ascii_digit())
+        .filter(|g| !g.is_empty())
+        .collect();
+    let lengths: Vec<usize> = groups.iter().map(|g| g.len()).collect();
+
+    let mut date_only = None;
+    for start in 0..groups.len() {
+        let rest = &groups[start..];
+        let candidate = match lengths[start..] {
+            [14, ..] => parse_naive(rest[0], "%Y%m%d%H%M%S"),
+            [8, 6, ..] => parse_naive(&format!("{}{}", rest[0], rest[1]), "%Y%m%d%H%M%S"),
+            [4, 2, 2, 2, 2, 2, ..] => parse_naive(&rest[..6].concat(), "%Y%m%d%H%M%S"),
+            [4, 2, 2, 2, 2, ..] => parse_naive(&rest[..5].concat(), "%Y%m%d%H%M"),
+            [10, ..] => rest[0]
+                .parse::<i64>()
+                .ok()
+                .and_then(|secs| DateTime::from_timestamp(secs, 0))
+                .map(|time| time.with_timezone(&tz).naive_local()),
+            _ => None,
+        };
+        if let Some(naive) = candidate.filter(|t| plausible(t.date())) {
+            return resolve_local(naive, tz);
+        }
+
+        if date_only.is_none() {
+            date_only = match lengths[start..] {
+                [8, ..] => NaiveDate::parse_from_str(rest[0], "%Y%m%d").ok(),
+                [4, 2, 2, ..] => NaiveDate::parse_from_str(&rest[..3].concat(), "%Y%m%d").ok(),
+                _ => None,
+            }
+            .filter(|d| plausible(*d));
+        }
+    }
+
+    date_only
+        .and_then(|d| d.and_hms_opt(12, 0, 0))
+        .and_then(|naive| resolve_local(naive, tz))
+}
+
+// A clock shown during a spring-forward gap was most likely still on the old offset
+fn resolve_local(naive: NaiveDateTime, tz: Tz) -> Option<DateTime<Tz>> {
+    tz.from_local_datetime(&naive)
+        .earliest()
+        .or_else(|| tz.from_local_datetime(&(naive + Duration::hours(1))).earliest())
+}
+
+fn parse_naive(value: &str, format: &str) -> Option<NaiveDateTime> {
+    NaiveDateTime::parse_from_str(value, format).ok()
+}
+
+// Guards against serial numbers that happen to parse as dates
+fn plausible(date: NaiveDate) -> bool {
+    (1990..=2100).contains(&date.year())
+}
+
+fn format_time(time: DateTime<Tz>) -> String {
+    time.format("%Y-%m-%d %H:%M").to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BERLIN: Tz = chrono_tz::Europe::Berlin;
+
+    fn local(name: &str, tz: Tz) -> Option<String> {
+        timestamp_from_name(name, tz).map(|t| t.format("%Y-%m-%d %H:%M:%S %:z").to_string())
+    }
+
+    #[test]
+    fn reads_each_documented_pattern() {
+        assert_eq!(local("20230514_093012", BERLIN).as_deref(), Some("2023-05-14 09:30:12 +02:00"));
+        assert_eq!(local("20230514093012", BERLIN).as_deref(), Some("2023-05-14 09:30:12 +02:00"));
+        assert_eq!(local("2023-05-14 09.30.12", BERLIN).as_deref(), Some("2023-05-14 09:30:12 +02:00"));
+        assert_eq!(local("2023-05-14 09-30", BERLIN).as_deref(), Some("2023-05-14 09:30:00 +02:00"));
+        assert_eq!(local("Voice 2023-05-14", BERLIN).as_deref(), Some("2023-05-14 12:00:00 +02:00"));
+        assert_eq!(local("memo 20230514", BERLIN).as_deref(), Some("2023-05-14 12:00:00 +02:00"));
+    }
+
+    #[test]
+    fn unix_seconds_are_shown_in_the_timezone() {
+        let time = timestamp_from_name("recording_1684056612", BERLIN).unwrap();
+        assert_eq!(time.timestamp(), 1684056612);
+        assert_eq!(local("recording_1684056612", BERLIN).as_deref(), Some("2023-05-14 11:30:12 +02:00"));
+    }
+
+    #[test]
+    fn a_time_wins_over_an_earlier_date() {
+        assert_eq!(
+            local("Voice 2023-05-01 copy of 20230514_093012", BERLIN).as_deref(),
+            Some("2023-05-14 09:30:12 +02:00")
+        );
+    }
+
+    #[test]
+    fn ignores_serial_numbers_and_impossible_dates() {
+        assert_eq!(local("IMG_0001", BERLIN), None);
+        assert_eq!(local("track 12345678", BERLIN), None);
+        assert_eq!(local("device 0000000001", BERLIN), None);
+        assert_eq!(local("recording_9999999999", BERLIN), None);
+        assert_eq!(local("20231345_093012", BERLIN), None);
+        assert_eq!(local("no digits here", BERLIN), None);
+    }
+
+    #[test]
+    fn implausible_serial_falls_back_to_a_later_date() {
+        assert_eq!(local("unit 31129999 2023-05-14", BERLIN).as_deref(), Some("2023-05-14 12:00:00 +02:00"));
+    }
+
+    #[test]
+    fn times_skipped_by_daylight_saving_move_forward() {
+        // Berlin jumped from 02:00 to 03:00 on 2023-03-26
+        assert_eq!(local("20230326_023012", BERLIN).as_deref(), Some("2023-03-26 03:30:12 +02:00"));
+    }
+
+    #[test]
+    fn repeated_times_take_the_first_occurrence() {
+        // Berlin went from 03:00 back to 02:00 on 2023-10-29
+        assert_eq!(local("20231029_023000", BERLIN).as_deref(), Some("2023-10-29 02:30:00 +02:00"));
+    }
+}
@@ -0,0 +1,262 @@
+use std::collections::{HashMap, HashSet};
+use chrono::{DateTime, NaiveDate, TimeZone, Utc};
+use futures_util::StreamExt;
+use sqlx::PgPool;
+use crate::models::dtos::{CaptureTimes, IntegrityIssue, IntegrityOptions, IntegrityReport};
+use crate::service::accounts::AuthUser;
+use crate::service::jobs::JobQueue;
+use crate::service::storage::{ByteRange, ObjectInfo, Storage, StorageError};
+use crate::service::uploads::{
+    object_sha256, register_recording, AudioFormat, NewRecording, Registered, SNIFF_LEN
+};
+use crate::timezone::Tz;
+
+pub const ORPHAN_FILE: &str = "orphan_file";
+pub const MISSING_FILE: &str = "missing_file";
+pub const EMPTY_FILE: &str = "empty_file";
+pub const SIZE_MISMATCH: &str = "size_mismatch";
+pub const CHECKSUM_MISMATCH: &str = "checksum_mismatch";
+pub const DUPLICATE_AUDIO: &str = "duplicate_audio";
+
+/// Compares every stored object with the `recordings` table, including recordings in the
+/// trash, and applies the repairs asked for in `options`. Reimported orphans belong to
+/// `owner`; without one they are only reported.
+pub async fn check_storage(
+    pool: &PgPool,
+    jobs: &JobQueue,
+    storage: &dyn Storage,
+    options: &IntegrityOptions,
+    owner: Option<&AuthUser>
+) -> Result<IntegrityReport, StorageError> {
+    let mut objects = storage.list().await?;
+    objects.sort_by(|a, b| a.key.cmp(&b.key));
+    let rows = sqlx::query!("SELECT id, owner_id, file_path, size_bytes, sha256, integrity_error FROM recordings ORDER BY file_path")
+        .fetch_all(pool)
+        .await?;
+
+    let mut report = IntegrityReport {
+        checked_files: objects.len(),
+        checked_rows: rows.len(),
+        ..Default::default()
+    };
+    let sizes: HashMap<&str, u64> = objects.iter().map(|o| (o.key.as_str(), o.size)).collect();
+
+    for row in &rows {
+        let size = sizes.get(row.file_path.as_str()).copied();
+        let mut problem = match (size, row.size_bytes) {
+            (None, _) => Some((MISSING_FILE, "File not found in storage".to_string())),
+            (Some(0), _) => Some((EMPTY_FILE, "File is empty".to_string())),
+            (Some(size), Some(expected)) if size as i64 != expected => {
+                Some((SIZE_MISMATCH, format!("Expected {} bytes, found {}", expected, size)))
+            }
+            _ => None,
+        };
+
+        let mut checksum = None;
+        if options.verify_checksums && problem.is_none() {
+            checksum = object_sha256(storage, &row.file_path).await?;
+            match (&checksum, &row.sha256) {
+                (Some(actual), Some(expected)) if actual != expected => {
+                    problem = Some((CHECKSUM_MISMATCH, format!("Expected SHA-256 {}, found {}", expected, actual)));
+                }
+                (None, _) => problem = Some((MISSING_FILE, "File not found in storage".to_string())),
+                _ => {}
+            }
+        }
+
+        if options.mark_broken {
+            let error = problem.as_ref().map(|(_, detail)| detail.clone());
+            // Recordings stored before sizes were tracked get theirs filled in once they check out
+            let backfill = if problem.is_none() { size.map(|s| s as i64) } else { None };
+            if error != row.integrity_error || (row.size_bytes.is_none() && backfill.is_some()) {
+                sqlx::query!(
+                    "UPDATE recordings SET integrity_error = $1, size_bytes = COALESCE(size_bytes, $2) WHERE id = $3",
+                    error,
+                    backfill,
+                    row.id
+                )
+                .execute(pool)
+                .await?;
+                if error != row.integrity_error {
+                    report.marked += 1;
+                }
+            }
+
+            // Checksums of older recordings are filled in unless that would make them a second
+            // live copy of the same owner's audio; those are reported instead
+            if let (None, None, Some(checksum)) = (&problem, &row.sha256, &checksum) {
+                let updated = sqlx::query!(
+                    r#"
+                    UPDATE recordings SET sha256 = $1
+                    WHERE id = $2
+                      AND NOT EXISTS (
+                          SELECT 1 FROM recordings
+                          WHERE sha256 = $1 AND deleted_at IS NULL AND owner_id IS NOT DISTINCT FROM $3
+                      )
+                    RETURNING id
+                    "#,
+                    checksum,
+                    row.id,
+                    row.owner_id
+                )
+                .fetch_optional(pool)
+                .await?;
+
+                if updated.is_none() {
+                    let original = sqlx::query!(
+                        r#"
+                        SELECT id FROM recordings
+                        WHERE sha256 = $1 AND deleted_at IS NULL AND owner_id IS NOT DISTINCT FROM $2
+                        LIMIT 1
+                        "#,
+                        checksum,
+                        row.owner_id
+                    )
+                    .fetch_optional(pool)
+                    .await?;
+                    let detail = match original {
+                        Some(original) => format!("Same audio as recording {}; checksum not recorded", original.id),
+                        None => "Same audio as another recording; checksum not recorded".to_string(),
+                    };
+                    problem = Some((DUPLICATE_AUDIO, detail));
+                }
+            }
+        }
+
+        if let Some((kind, detail)) = problem {
+            report.issues.push(IntegrityIssue {
+                kind: kind.to_string(),
+                file_path: row.file_path.clone(),
+                recording_id: Some(row.id),
+                detail,
+            });
+        }
+    }
+
+    let known: HashSet<&str> = rows.iter().map(|r| r.file_path.as_str()).collect();
+    for object in objects.iter().filter(|o| !known.contains(o.key.as_str())) {
+        if object.size == 0 {
+            report.issues.push(IntegrityIssue {
+                kind: EMPTY_FILE.to_string(),
+                file_path: object.key.clone(),
+                recording_id: None,
+                detail: "Empty file with no recording".to_string(),
+            });
+            continue;
+        }
+
+        let mut issue = IntegrityIssue {
+            kind: ORPHAN_FILE.to_string(),
+            file_path: object.key.clone(),
+            recording_id: None,
+            detail: "File has no recording".to_string(),
+        };
+        if let (true, Some(owner)) = (options.reimport_orphans, owner) {
+            match reimport(pool, jobs, storage, object, owner).await {
+                Ok(Registered::New(id)) => {
+                    issue.recording_id = Some(id);
+                    issue.detail = "File had no recording, reimported".to_string();
+                    report.reimported += 1;
+                }
+                Ok(Registered::Duplicate(id)) => {
+                    issue.recording_id = Some(id);
+                    issue.detail = "File has no recording, its audio belongs to another recording".to_string();
+                }
+                Err(e) => issue.detail = format!("File has no recording, reimport failed: {}", e),
+            }
+        }
+        report.issues.push(issue);
+    }
+
+    Ok(report)
+}
+
+// Creates a recording for an orphaned audio file and queues it for transcription
+async fn reimport(
+    pool: &PgPool,
+    jobs: &JobQueue,
+    storage: &dyn Storage,
+    object: &ObjectInfo,
+    owner: &AuthUser
+) -> Result<Registered, StorageError> {
+    let header = read_header(storage, &object.key).await?;
+    let format = AudioFormat::detect(&header).ok_or("not a recognised audio format")?;
+    let mime_type = format.mime_type(&header);
+    let sha256 = object_sha256(storage, &object.key).await?.ok_or("file disappeared")?;
+    let filename = object.key.rsplit('/').next().unwrap_or(&object.key);
+
+    let recording = NewRecording {
+        owner_id: owner.id,
+        filename,
+        file_path: &object.key,
+        mime_type: &mime_type,
+        size_bytes: object.size as i64,
+        sha256: &sha256,
+        recorded_at: recorded_at_for_key(&object.key, owner.timezone),
+        capture: CaptureTimes::default(),
+        transcribe_at: None,
+    };
+    Ok(register_recording(pool, jobs, &recording).await?)
+}
+
+async fn read_header(storage: &dyn Storage, key: &str) -> Result<Vec<u8>, StorageError> {
+    let range = ByteRange { start: 0, end: Some(SNIFF_LEN as u64 - 1) };
+    let mut object = storage.get_range(key, Some(range)).await?.ok_or("file disappeared")?;
+
+    let mut header = Vec::with_capacity(SNIFF_LEN);
+    while let Some(chunk) = object.body.next().await {
+        header.extend_from_slice(&chunk?);
+    }
+    Ok(header)
+}
+
+// Uploads are named `recording_<unix seconds>_<uuid>` and filed under `YYYY/M/D` (a day in `tz`);
+// use whichever is there
+fn recorded_at_for_key(key: &str, tz: Tz) -> DateTime<Utc> {
+    let parts: Vec<&str> = key.split('/').collect();
+    let filename = parts.last().copied().unwrap_or_default();
+    let stem = filename.split('.').next().unwrap_or_default();
+
+    let timestamp = stem
+        .split('_')
+        .find_map(|part| part.parse::<i64>().ok().filter(|_| part.len() >= 9))
+        .and_then(|secs| DateTime::from_timestamp(secs, 0));
+    if let Some(timestamp) = timestamp {
+        return timestamp;
+    }
+
+    let date = match parts.as_slice() {
+        [year, month, day, _] => NaiveDate::parse_from_str(&format!("{}-{}-{}", year, month, day), "%Y-%m-%d").ok(),
+        _ => None,
+    };
+    date.and_then(|d| d.and_hms_opt(12, 0, 0))
+        .and_then(|naive| tz.from_local_datetime(&naive).earliest())
+        .map(|time| time.with_timezone(&Utc))
+        .unwrap_or_else(Utc::now)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn dates_uploads_by_the_timestamp_in_their_name() {
+        let expected = DateTime::from_timestamp(1792313015, 0).unwrap();
+        assert_eq!(recorded_at_for_key("2026/10/18/recording_1792313015.webm", Tz::UTC), expected);
+        assert_eq!(
+            recorded_at_for_key("2026/10/18/recording_1792313015_0123456789abcdef0123456789abcdef.webm", Tz::UTC),
+            expected
+        );
+        assert_eq!(recorded_at_for_key("2026/10/18/test_recording_1792313015_2.webm", Tz::UTC), expected);
+    }
+
+    #[test]
+    fn falls_back_to_noon_of_the_directory_day() {
+        let time = recorded_at_for_key("2026/5/14/memo.m4a", chrono_tz::Europe::Berlin);
+        assert_eq!(time, Utc.with_ymd_and_hms(2026, 5, 14, 10, 0, 0).unwrap());
+
+        // Short numbers in the name are not timestamps
+        let time = recorded_at_for_key("2026/5/14/memo_12.m4a", Tz::UTC);
+        assert_eq!(time, Utc.with_ymd_and_hms(2026, 5, 14, 12, 0, 0).unwrap());
+    }
+}
@@ -0,0 +1,487 @@
+use std::sync::Arc;
+use std::time::Duration;
+use chrono::{DateTime, Utc};
+use sqlx::PgPool;
+use tokio::sync::Notify;
+use uuid::Uuid;
+use crate::models::dtos::{TaskGroup, WebhookEvent};
+use crate::service::accounts::user_timezone;
+use crate::service::events::EventBus;
+use crate::service::grouping::{auto_apply_threshold_from_env, list_groups, save_suggestion, take_suggestion};
+use crate::service::prompt::{build_prompt, with_recording_date};
+use crate::service::tasks::{replace_extracted_tasks, take_action_items};
+use crate::service::storage::{local_copy, SharedStorage};
+use crate::service::transcription::{SharedTranscriber, TranscriptionRequest};
+use crate::service::uploads::mime_essence;
+use crate::service::versions::{prompt_hash, save_transcription, VersionSource};
+use crate::service::webhooks;
+use crate::timezone::local_date;
+
+const DEFAULT_MAX_ATTEMPTS: i32 = 5;
+const DEFAULT_WORKERS: usize = 2;
+const POLL_INTERVAL: Duration = Duration::from_secs(10);
+const BACKOFF_BASE_SECS: i64 = 30;
+const BACKOFF_MAX_SECS: i64 = 60 * 60;
+const DEFAULT_JOB_TIMEOUT_SECS: u64 = 30 * 60;
+const REAP_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Handle to the Postgres-backed transcription queue.
+///
+/// Jobs live in `transcription_jobs`; the handle only wakes idle workers so
+/// new work is picked up without waiting for the next poll. Workers publish status
+/// changes on the queue's event bus.
+#[derive(Clone)]
+pub struct JobQueue {
+    notify: Arc<Notify>,
+    events: EventBus,
+    max_attempts: i32,
+    job_timeout: Duration,
+    suggestion_threshold: Option<f32>,
+}
+
+// What the model was asked and what it returned
+struct TranscriptionOutcome {
+    reply: serde_json::Value,
+    prompt: String,
+    groups: Vec<TaskGroup>,
+}
+
+struct ClaimedJob {
+    id: Uuid,
+    recording_id: Uuid,
+    attempts: i32,
+    max_attempts: i32,
+}
+
+// What happens to a job whose attempt failed
+#[derive(Debug, PartialEq)]
+enum FailureAction {
+    Retry { after_secs: i64 },
+    Fail,
+}
+
+impl JobQueue {
+    /// Reads `TRANSCRIPTION_MAX_ATTEMPTS` (default 5), `TRANSCRIPTION_JOB_TIMEOUT_SECS`
+    /// (default 1800) and `GROUP_SUGGESTION_THRESHOLD`.
+    pub fn from_env() -> Self {
+        let max_attempts = std::env::var("TRANSCRIPTION_MAX_ATTEMPTS")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .filter(|n: &i32| *n > 0)
+            .unwrap_or(DEFAULT_MAX_ATTEMPTS);
+        let job_timeout = std::env::var("TRANSCRIPTION_JOB_TIMEOUT_SECS")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .filter(|n: &u64| *n > 0)
+            .unwrap_or(DEFAULT_JOB_TIMEOUT_SECS);
+
+        Self {
+            notify: Arc::new(Notify::new()),
+            events: EventBus::new(),
+            max_attempts,
+            job_timeout: Duration::from_secs(job_timeout),
+            suggestion_threshold: auto_apply_threshold_from_env(),
+        }
+    }
+
+    /// Where recording changes are announced to open pages.
+    pub fn events(&self) -> &EventBus {
+        &self.events
+    }
+
+    /// Queues a recording for transcription. Does nothing if it already has an active job;
+    /// returns whether a job was queued.
+    pub async fn enqueue(&self, pool: &PgPool, recording_id: Uuid) -> Result<bool, sqlx::Error> {
+        self.enqueue_at(pool, recording_id, Utc::now()).await
+    }
+
+    /// Queues a recording to be transcribed no earlier than `run_at`, unless it already has an
+    /// active job. Returns whether a job was queued.
+    pub async fn enqueue_at(&self, pool: &PgPool, recording_id: Uuid, run_at: DateTime<Utc>) -> Result<bool, sqlx::Error> {
+        let queued = sqlx::query!(
+            r#"
+            INSERT INTO transcription_jobs (recording_id, max_attempts, run_at)
+            VALUES ($1, $2, $3)
+            ON CONFLICT (recording_id) WHERE status IN ('QUEUED', 'RUNNING') DO NOTHING
+            "#,
+            recording_id,
+            self.max_attempts,
+            run_at
+        )
+        .execute(pool)
+        .await?;
+
+        if queued.rows_affected() == 0 {
+            return Ok(false);
+        }
+        self.notify.notify_one();
+        Ok(true)
+    }
+
+    /// Latest time any queued job is scheduled for, so delayed work can be appended after it.
+    pub async fn last_scheduled(&self, pool: &PgPool) -> Result<Option<DateTime<Utc>>, sqlx::Error> {
+        let record = sqlx::query!("SELECT MAX(run_at) as last FROM transcription_jobs WHERE status = 'QUEUED'")
+            .fetch_one(pool)
+            .await?;
+        Ok(record.last)
+    }
+
+    /// Requeues work left over from a previous run and starts `TRANSCRIPTION_WORKERS` workers (default 2),
+    /// plus a reaper that requeues jobs whose worker stopped reporting back.
+    pub async fn start(&self, pool: PgPool, transcriber: SharedTranscriber, storage: SharedStorage) -> Result<(), sqlx::Error> {
+        self.recover(&pool).await?;
+
+        let workers = std::env::var("TRANSCRIPTION_WORKERS")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .filter(|n: &usize| *n > 0)
+            .unwrap_or(DEFAULT_WORKERS);
+
+        for _ in 0..workers {
+            let queue = self.clone();
+            let pool = pool.clone();
+            let transcriber = transcriber.clone();
+            let storage = storage.clone();
+            tokio::spawn(async move { queue.run_worker(pool, transcriber, storage).await });
+        }
+
+        let queue = self.clone();
+        tokio::spawn(async move { queue.run_reaper(pool).await });
+
+        Ok(())
+    }
+
+    async fn recover(&self, pool: &PgPool) -> Result<(), sqlx::Error> {
+        // Jobs that were running when the server stopped
+        let reset = sqlx::query!(
+            r#"
+            UPDATE transcription_jobs
+            SET status = 'QUEUED', locked_at = NULL, run_at = NOW(), updated_at = NOW()
+            WHERE status = 'RUNNING'
+            "#
+        )
+        .execute(pool)
+        .await?;
+
+        // Pending recordings that never got a job (e.g. uploaded before the queue existed)
+        let queued = sqlx::query!(
+            r#"
+            INSERT INTO transcription_jobs (recording_id, max_attempts)
+            SELECT r.id, $1
+            FROM recordings r
+            WHERE r.transcription_status = 'PENDING'
+              AND NOT EXISTS (
+                  SELECT 1 FROM transcription_jobs j
+                  WHERE j.recording_id = r.id AND j.status IN ('QUEUED', 'RUNNING')
+              )
+            "#,
+            self.max_attempts
+        )
+        .execute(pool)
+        .await?;
+
+        if reset.rows_affected() > 0 || queued.rows_affected() > 0 {
+            println!(
+                "Transcription queue: resumed {} interrupted and {} pending recordings",
+                reset.rows_affected(),
+                queued.rows_affected()
+            );
+        }
+
+        Ok(())
+    }
+
+    // A job stays RUNNING if its result could not be written (e.g. the database was briefly
+    // unreachable); once its claim is older than the timeout it is handed out again
+    async fn run_reaper(&self, pool: PgPool) {
+        loop {
+            tokio::time::sleep(REAP_INTERVAL).await;
+            match requeue_stale(&pool, self.job_timeout).await {
+                Ok(0) => {}
+                Ok(count) => {
+                    println!("Transcription queue: requeued {} stalled jobs", count);
+                    self.notify.notify_waiters();
+                }
+                Err(e) => eprintln!("Failed to requeue stalled transcription jobs: {}", e),
+            }
+        }
+    }
+
+    async fn run_worker(&self, pool: PgPool, transcriber: SharedTranscriber, storage: SharedStorage) {
+        loop {
+            match claim_next(&pool).await {
+                Ok(Some(job)) => {
+                    match run_job(&pool, &transcriber, &storage, &job, self.suggestion_threshold).await {
+                        Ok(()) => self.events.recording_changed(&pool, job.recording_id).await,
+                        Err(e) => {
+                            eprintln!("Failed to record result of transcription job {}: {}", job.id, e);
+                            release(&pool, &job, &e.to_string()).await;
+                        }
+                    }
+                }
+                Ok(None) => {
+                    tokio::select! {
+                        _ = self.notify.notified() => {}
+                        _ = tokio::time::sleep(POLL_INTERVAL) => {}
+                    }
+                }
+                Err(e) => {
+                    eprintln!("Failed to claim transcription job: {}", e);
+                    tokio::time::sleep(POLL_INTERVAL).await;
+                }
+            }
+        }
+    }
+}
+
+async fn claim_next(pool: &PgPool) -> Result<Option<ClaimedJob>, sqlx::Error> {
+    sqlx::query_as!(
+        ClaimedJob,
+        r#"
+        UPDATE transcription_jobs
+        SET status = 'RUNNING', attempts = attempts + 1, locked_at = NOW(), updated_at = NOW()
+        WHERE id = (
+            SELECT id FROM transcription_jobs
+            WHERE status = 'QUEUED' AND run_at <= NOW()
+            ORDER BY run_at ASC
+            LIMIT 1
+            FOR UPDATE SKIP LOCKED
+        )
+        RETURNING id, recording_id, attempts, max_attempts
+        "#
+    )
+    .fetch_optional(pool)
+    .await
+}
+
+async fn requeue_stale(pool: &PgPool, timeout: Duration) -> Result<u64, sqlx::Error> {
+    let result = sqlx::query!(
+        r#"
+        UPDATE transcription_jobs
+        SET status = 'QUEUED', locked_at = NULL, run_at = NOW(), updated_at = NOW()
+        WHERE status = 'RUNNING' AND locked_at < NOW() - make_interval(secs => $1)
+        "#,
+        timeout.as_secs_f64()
+    )
+    .execute(pool)
+    .await?;
+    Ok(result.rows_affected())
+}
+
+// Gives a claimed job back to the queue after its result could not be saved. If the database
+// is still unreachable the reaper picks the job up later.
+async fn release(pool: &PgPool, job: &ClaimedJob, message: &str) {
+    let after_secs = match after_failure(job.attempts, job.max_attempts) {
+        FailureAction::Retry { after_secs } => after_secs,
+        FailureAction::Fail => BACKOFF_BASE_SECS,
+    };
+    let result = sqlx::query!(
+        r#"
+        UPDATE transcription_jobs
+        SET status = 'QUEUED', locked_at = NULL, last_error = $1,
+            run_at = NOW() + make_interval(secs => $2), updated_at = NOW()
+        WHERE id = $3 AND status = 'RUNNING'
+        "#,
+        message,
+        after_secs as f64,
+        job.id
+    )
+    .execute(pool)
+    .await;
+
+    if let Err(e) = result {
+        eprintln!("Failed to release transcription job {}, leaving it to the reaper: {}", job.id, e);
+    }
+}
+
+async fn run_job(
+    pool: &PgPool,
+    transcriber: &SharedTranscriber,
+    storage: &SharedStorage,
+    job: &ClaimedJob,
+    suggestion_threshold: Option<f32>
+) -> Result<(), sqlx::Error> {
+    match transcribe_recording(pool, transcriber, storage, job.recording_id).await {
+        Ok(mut outcome) => {
+            let suggestion = take_suggestion(&mut outcome.reply, &outcome.groups);
+            let action_items = take_action_items(&mut outcome.reply);
+
+            let mut tx = pool.begin().await?;
+
+            let source = VersionSource::Model {
+                model: transcriber.name(),
+                prompt_hash: prompt_hash(&outcome.prompt),
+            };
+            save_transcription(&mut tx, job.recording_id, &outcome.reply, source).await?;
+            let regrouped = save_suggestion(&mut tx, job.recording_id, suggestion, suggestion_threshold).await?;
+            replace_extracted_tasks(&mut tx, job.recording_id, action_items).await?;
+
+            sqlx::query!(
+                "UPDATE recordings SET transcription_status = 'COMPLETED', transcription_error = NULL WHERE id = $1",
+                job.recording_id
+            )
+            .execute(&mut *tx)
+            .await?;
+
+            sqlx::query!(
+                "UPDATE transcription_jobs SET status = 'DONE', locked_at = NULL, last_error = NULL, updated_at = NOW() WHERE id = $1",
+                job.id
+            )
+            .execute(&mut *tx)
+            .await?;
+
+            tx.commit().await?;
+            webhooks::trigger(pool, WebhookEvent::Transcribed, job.recording_id).await;
+            if regrouped {
+                webhooks::trigger(pool, WebhookEvent::Regrouped, job.recording_id).await;
+            }
+            Ok(())
+        }
+        Err(e) => {
+            let message = e.to_string();
+            eprintln!(
+                "Transcription of {} failed (attempt {}/{}): {}",
+                job.recording_id, job.attempts, job.max_attempts, message
+            );
+
+            match after_failure(job.attempts, job.max_attempts) {
+                FailureAction::Fail => {
+                    let mut tx = pool.begin().await?;
+
+                    sqlx::query!(
+                        "UPDATE recordings SET transcription_status = 'FAILED', transcription_error = $1 WHERE id = $2",
+                        message,
+                        job.recording_id
+                    )
+                    .execute(&mut *tx)
+                    .await?;
+
+                    sqlx::query!(
+                        "UPDATE transcription_jobs SET status = 'FAILED', locked_at = NULL, last_error = $1, updated_at = NOW() WHERE id = $2",
+                        message,
+                        job.id
+                    )
+                    .execute(&mut *tx)
+                    .await?;
+
+                    tx.commit().await?;
+                    webhooks::trigger(pool, WebhookEvent::Failed, job.recording_id).await;
+                    Ok(())
+                }
+                FailureAction::Retry { after_secs } => {
+                    sqlx::query!(
+                        r#"
+                        UPDATE transcription_jobs
+                        SET status = 'QUEUED', locked_at = NULL, last_error = $1,
+                            run_at = NOW() + make_interval(secs => $2), updated_at = NOW()
+                        WHERE id = $3
+                        "#,
+                        message,
+                        after_secs as f64,
+                        job.id
+                    )
+                    .execute(pool)
+                    .await?;
+                    Ok(())
+                }
+            }
+        }
+    }
+}
+
+async fn transcribe_recording(
+    pool: &PgPool,
+    transcriber: &SharedTranscriber,
+    storage: &SharedStorage,
+    recording_id: Uuid
+) -> Result<TranscriptionOutcome, Box<dyn std::error::Error + Send + Sync>> {
+    let record = sqlx::query!(
+        r#"
+        SELECT r.file_path, r.mime_type, r.created_at as "created_at!", r.owner_id, u.timezone as "timezone?"
+        FROM recordings r
+        LEFT JOIN users u ON u.id = r.owner_id
+        WHERE r.id = $1
+        "#,
+        recording_id
+    )
+    .fetch_one(pool)
+    .await?;
+
+    // The prompt offers the owner's groups; recordings from before accounts have none to offer
+    let groups = match record.owner_id {
+        Some(owner_id) => list_groups(pool, owner_id, false).await?,
+        None => Vec::new(),
+    };
+    let prompt = build_prompt(&groups);
+    let recorded_on = local_date(record.created_at, user_timezone(record.timezone.as_deref()));
+
+    // Providers read a local file; remote storage is downloaded for the duration of the call
+    let audio = local_copy(storage.as_ref(), &record.file_path).await?;
+    let reply = transcriber.transcribe(TranscriptionRequest {
+        path: &audio.path,
+        mime_type: mime_essence(&record.mime_type),
+        prompt: &with_recording_date(&prompt, recorded_on),
+    }).await;
+    audio.release().await;
+    let reply = reply?;
+
+    Ok(TranscriptionOutcome { reply, prompt, groups })
+}
+
+// Retries with backoff until the job has used all of its attempts
+fn after_failure(attempts: i32, max_attempts: i32) -> FailureAction {
+    if attempts >= max_attempts {
+        FailureAction::Fail
+    } else {
+        FailureAction::Retry { after_secs: backoff_secs(attempts) }
+    }
+}
+
+/// Exponential backoff: 30s, 60s, 120s, ... capped at one hour.
+fn backoff_secs(attempts: i32) -> i64 {
+    let exponent = (attempts - 1).clamp(0, 16) as u32;
+    (BACKOFF_BASE_SECS * 2_i64.pow(exponent)).min(BACKOFF_MAX_SECS)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn backoff_doubles_from_thirty_seconds() {
+        assert_eq!(backoff_secs(1), 30);
+        assert_eq!(backoff_secs(2), 60);
+        assert_eq!(backoff_secs(3), 120);
+        assert_eq!(backoff_secs(7), 1920);
+    }
+
+    #[test]
+    fn backoff_is_capped_at_an_hour() {
+        assert_eq!(backoff_secs(8), BACKOFF_MAX_SECS);
+        assert_eq!(backoff_secs(50), BACKOFF_MAX_SECS);
+        assert_eq!(backoff_secs(i32::MAX), BACKOFF_MAX_SECS);
+    }
+
+    #[test]
+    fn backoff_treats_unclaimed_jobs_as_first_attempt() {
+        assert_eq!(backoff_secs(0), 30);
+        assert_eq!(backoff_secs(-3), 30);
+    }
+
+    #[test]
+    fn failed_attempts_retry_until_the_last_one() {
+        let max_attempts = 3;
+        let actions: Vec<_> = (1..=max_attempts).map(|attempt| after_failure(attempt, max_attempts)).collect();
+        assert_eq!(actions, vec![
+            FailureAction::Retry { after_secs: 30 },
+            FailureAction::Retry { after_secs: 60 },
+            FailureAction::Fail,
+        ]);
+    }
+
+    #[test]
+    fn jobs_past_their_attempts_fail() {
+        assert_eq!(after_failure(1, 1), FailureAction::Fail);
+        assert_eq!(after_failure(6, 5), FailureAction::Fail);
+    }
+}
@@ -1 +1,17 @@
+pub mod api_tokens;
+pub mod accounts;
+pub mod digest;
+pub mod events;
+pub mod grouping;
+pub mod import;
+pub mod integrity;
+pub mod jobs;
+pub mod prompt;
+pub mod shares;
+pub mod storage;
+pub mod tasks;
 pub mod transcription;
+pub mod trash;
+pub mod uploads;
+pub mod versions;
+pub mod webhooks;
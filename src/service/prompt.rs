@@ -0,0 +1,44 @@
+use std::fmt::Write;
+use chrono::NaiveDate;
+use crate::models::dtos::TaskGroup;
+use crate::service::transcription::DEFAULT_PROMPT;
+
+/// Builds the instructions for one transcription: the base prompt followed by
+/// action item extraction and a classification section listing the active task groups.
+pub fn build_prompt(groups: &[TaskGroup]) -> String {
+    let mut prompt = DEFAULT_PROMPT.trim_end().to_string();
+
+    prompt.push_str(
+        "\n\nAlso list the action items (to-dos, follow-ups, things to delegate) mentioned in the recording.\n\
+         Add this field to the JSON object:\n\
+         \"action_items\": [{\"description\": \"what needs to be done\", \"assignee\": \"who should do it, or null\", \"due_date\": \"YYYY-MM-DD, or null\"}]\n\
+         Use an empty list when there are none.\n"
+    );
+
+    if !groups.is_empty() {
+        prompt.push_str("\nAlso decide which of these task groups the recording belongs to:\n");
+        for group in groups {
+            match &group.description {
+                Some(description) => { let _ = writeln!(prompt, "- \"{}\": {}", group.name, description); }
+                None => { let _ = writeln!(prompt, "- \"{}\"", group.name); }
+            }
+        }
+        prompt.push_str(
+            "\nAdd these fields to the JSON object:\n\
+             \"suggested_group\": the exact name of the best matching group, or null if none fits\n\
+             \"group_confidence\": how confident you are in that choice, from 0 to 1\n"
+        );
+    }
+
+    prompt
+}
+
+/// Adds per-recording context. Kept out of `build_prompt` so the prompt hash
+/// stored with each version identifies the instructions, not the recording.
+pub fn with_recording_date(prompt: &str, recorded_on: NaiveDate) -> String {
+    format!(
+        "{}\nThe recording was made on {}; resolve relative due dates such as \"tomorrow\" against that day.\n",
+        prompt,
+        recorded_on.format("%Y-%m-%d (%A)")
+    )
+}
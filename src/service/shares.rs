@@ -0,0 +1,258 @@
+use std::sync::OnceLock;
+use argon2::password_hash::rand_core::{OsRng, RngCore};
+use base64::{Engine as _, engine::general_purpose};
+use chrono::{DateTime, Duration, TimeZone, Utc};
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+use sqlx::PgPool;
+use uuid::Uuid;
+use crate::models::dtos::{RecordingShare, SharedRecording};
+use crate::service::accounts::user_timezone;
+
+const DEFAULT_SHARE_DAYS: i64 = 7;
+const MAX_SHARE_DAYS: i64 = 365;
+
+/// Path prefix of the public share pages.
+pub const SHARE_PATH: &str = "/share/";
+
+/// A shared recording and where its audio is stored.
+pub struct SharedFile {
+    pub recording: SharedRecording,
+    pub file_path: String,
+}
+
+/// Whether share links are signed with `SHARE_SECRET`. Without it a random key is used, and
+/// links stop working when the server restarts.
+pub fn share_secret_configured() -> bool {
+    std::env::var("SHARE_SECRET").is_ok_and(|v| !v.is_empty())
+}
+
+fn share_key() -> &'static [u8] {
+    static KEY: OnceLock<Vec<u8>> = OnceLock::new();
+    KEY.get_or_init(|| match std::env::var("SHARE_SECRET") {
+        Ok(secret) if !secret.is_empty() => secret.into_bytes(),
+        _ => {
+            let mut bytes = vec![0u8; 32];
+            OsRng.fill_bytes(&mut bytes);
+            bytes
+        }
+    })
+}
+
+fn mac(payload: &str) -> Hmac<Sha256> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(share_key()).expect("HMAC accepts keys of any length");
+    mac.update(payload.as_bytes());
+    mac
+}
+
+// Tokens are "<share id>.<expiry as unix seconds>.<signature>", so forged or edited links are
+// refused before the database is asked
+fn share_token(id: Uuid, expires_at: DateTime<Utc>) -> String {
+    let payload = format!("{}.{}", id.simple(), expires_at.timestamp());
+    let signature = general_purpose::URL_SAFE_NO_PAD.encode(mac(&payload).finalize().into_bytes());
+    format!("{}.{}", payload, signature)
+}
+
+fn verify_token(token: &str) -> Option<(Uuid, DateTime<Utc>)> {
+    let (payload, signature) = token.rsplit_once('.')?;
+    let signature = general_purpose::URL_SAFE_NO_PAD.decode(signature).ok()?;
+    mac(payload).verify_slice(&signature).ok()?;
+
+    let (id, expires) = payload.split_once('.')?;
+    let id = Uuid::parse_str(id).ok()?;
+    let expires_at = Utc.timestamp_opt(expires.parse().ok()?, 0).single()?;
+    Some((id, expires_at))
+}
+
+// The share a genuine token names, unless the link had expired by `now`
+fn live_share(token: &str, now: DateTime<Utc>) -> Option<Uuid> {
+    verify_token(token)
+        .filter(|(_, expires_at)| *expires_at > now)
+        .map(|(id, _)| id)
+}
+
+fn share_url(id: Uuid, expires_at: DateTime<Utc>) -> String {
+    format!("{}{}", SHARE_PATH, share_token(id, expires_at))
+}
+
+/// How long a new link lasts: 7 days unless asked otherwise, at most a year.
+pub fn share_lifetime(days: Option<i64>) -> Result<Duration, String> {
+    match days.unwrap_or(DEFAULT_SHARE_DAYS) {
+        days @ 1..=MAX_SHARE_DAYS => Ok(Duration::days(days)),
+        _ => Err(format!("Links can last between 1 and {} days", MAX_SHARE_DAYS)),
+    }
+}
+
+/// Creates a link to one of the owner's recordings.
+pub async fn create_share(
+    pool: &PgPool,
+    owner_id: Uuid,
+    recording_id: Uuid,
+    lifetime: Duration,
+) -> Result<RecordingShare, sqlx::Error> {
+    // Whole seconds, so the link can be rebuilt from the stored expiry
+    let expires_at = Utc.timestamp_opt((Utc::now() + lifetime).timestamp(), 0).unwrap();
+    let row = sqlx::query!(
+        r#"
+        INSERT INTO recording_shares (recording_id, expires_at)
+        SELECT id, $3 FROM recordings
+        WHERE id = $1 AND owner_id = $2 AND deleted_at IS NULL
+        RETURNING id, created_at
+        "#,
+        recording_id,
+        owner_id,
+        expires_at
+    )
+    .fetch_optional(pool)
+    .await?
+    .ok_or(sqlx::Error::RowNotFound)?;
+
+    Ok(RecordingShare {
+        id: row.id,
+        recording_id,
+        url: share_url(row.id, expires_at),
+        created_at: row.created_at,
+        expires_at,
+    })
+}
+
+/// Links to one of the owner's recordings that haven't expired, newest first.
+pub async fn list_shares(pool: &PgPool, owner_id: Uuid, recording_id: Uuid) -> Result<Vec<RecordingShare>, sqlx::Error> {
+    let rows = sqlx::query!(
+        r#"
+        SELECT s.id, s.created_at, s.expires_at
+        FROM recording_shares s
+        JOIN recordings r ON r.id = s.recording_id
+        WHERE s.recording_id = $1 AND r.owner_id = $2 AND s.expires_at > NOW()
+        ORDER BY s.created_at DESC, s.id DESC
+        "#,
+        recording_id,
+        owner_id
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows
+        .into_iter()
+        .map(|row| RecordingShare {
+            id: row.id,
+            recording_id,
+            url: share_url(row.id, row.expires_at),
+            created_at: row.created_at,
+            expires_at: row.expires_at,
+        })
+        .collect())
+}
+
+/// Revokes a link; it stops working straight away.
+pub async fn revoke_share(pool: &PgPool, owner_id: Uuid, recording_id: Uuid, id: Uuid) -> Result<(), sqlx::Error> {
+    sqlx::query!(
+        r#"
+        DELETE FROM recording_shares s
+        USING recordings r
+        WHERE s.id = $1 AND s.recording_id = $2 AND r.id = s.recording_id AND r.owner_id = $3
+        RETURNING s.id
+        "#,
+        id,
+        recording_id,
+        owner_id
+    )
+    .fetch_optional(pool)
+    .await?
+    .ok_or(sqlx::Error::RowNotFound)?;
+    Ok(())
+}
+
+/// The recording behind a share token, if the token is genuine, unexpired and not revoked and
+/// the recording is not in the trash.
+pub async fn shared_recording(pool: &PgPool, token: &str) -> Result<Option<SharedFile>, sqlx::Error> {
+    let Some(id) = live_share(token, Utc::now()) else {
+        return Ok(None);
+    };
+
+    let row = sqlx::query!(
+        r#"
+        SELECT
+            r.transcription_text->>'title' as "title",
+            coalesce(r.transcription_text->>'improved_transcript', r.transcription_text->>'transcript') as "transcript",
+            r.file_path,
+            r.created_at as "created_at!",
+            s.expires_at,
+            u.timezone as "timezone?"
+        FROM recording_shares s
+        JOIN recordings r ON r.id = s.recording_id
+        LEFT JOIN users u ON u.id = r.owner_id
+        WHERE s.id = $1 AND s.expires_at > NOW() AND r.deleted_at IS NULL
+        "#,
+        id
+    )
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(row.map(|row| SharedFile {
+        recording: SharedRecording {
+            title: row.title,
+            transcript: row.transcript,
+            audio_url: format!("{}{}/audio", SHARE_PATH, token),
+            created_at: row.created_at,
+            timezone: user_timezone(row.timezone.as_deref()).to_string(),
+            expires_at: row.expires_at,
+        },
+        file_path: row.file_path,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn expiry() -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2026, 10, 25, 12, 0, 0).unwrap()
+    }
+
+    #[test]
+    fn tokens_verify_to_their_share_and_expiry() {
+        let id = Uuid::new_v4();
+        assert_eq!(verify_token(&share_token(id, expiry())), Some((id, expiry())));
+    }
+
+    #[test]
+    fn edited_tokens_are_refused() {
+        let id = Uuid::new_v4();
+        let token = share_token(id, expiry());
+        let (payload, signature) = token.rsplit_once('.').unwrap();
+
+        // A later expiry or another share under the same signature
+        let later = format!("{}.{}.{}", id.simple(), expiry().timestamp() + 86_400, signature);
+        let other = format!("{}.{}.{}", Uuid::new_v4().simple(), expiry().timestamp(), signature);
+        let resigned = format!("{}.{}", payload, general_purpose::URL_SAFE_NO_PAD.encode([0u8; 32]));
+        for token in [later, other, resigned, payload.to_string(), String::new(), "a.b.c".to_string()] {
+            assert_eq!(verify_token(&token), None, "accepted {:?}", token);
+        }
+    }
+
+    #[test]
+    fn links_stop_working_when_they_expire() {
+        let id = Uuid::new_v4();
+        let token = share_token(id, expiry());
+        assert_eq!(live_share(&token, expiry() - Duration::seconds(1)), Some(id));
+        assert_eq!(live_share(&token, expiry()), None);
+        assert_eq!(live_share(&token, expiry() + Duration::days(1)), None);
+    }
+
+    #[test]
+    fn share_urls_live_under_the_share_path() {
+        let url = share_url(Uuid::new_v4(), expiry());
+        let token = url.strip_prefix(SHARE_PATH).unwrap();
+        assert!(verify_token(token).is_some());
+    }
+
+    #[test]
+    fn lifetimes_are_between_a_day_and_a_year() {
+        assert_eq!(share_lifetime(None), Ok(Duration::days(DEFAULT_SHARE_DAYS)));
+        assert_eq!(share_lifetime(Some(1)), Ok(Duration::days(1)));
+        assert_eq!(share_lifetime(Some(MAX_SHARE_DAYS)), Ok(Duration::days(MAX_SHARE_DAYS)));
+        assert!(share_lifetime(Some(0)).is_err());
+        assert!(share_lifetime(Some(MAX_SHARE_DAYS + 1)).is_err());
+    }
+}
@@ -0,0 +1,127 @@
+use std::io::SeekFrom;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use async_trait::async_trait;
+use futures_util::{stream, StreamExt};
+use tokio::io::{AsyncReadExt, AsyncSeekExt};
+use tokio_util::io::ReaderStream;
+
+use walkdir::WalkDir;
+
+use super::{optional_env, validate_key, ByteRange, ObjectInfo, Storage, StorageError, StoredObject};
+
+/// Keeps recordings in a directory on this machine (`LOCAL_STORAGE_DIR`, default `recordings`).
+pub struct LocalStorage {
+    root: PathBuf,
+}
+
+impl LocalStorage {
+    pub fn from_env() -> Self {
+        Self::new(optional_env("LOCAL_STORAGE_DIR", "recordings"))
+    }
+
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+
+    fn path(&self, key: &str) -> Result<PathBuf, StorageError> {
+        validate_key(key)?;
+        Ok(self.root.join(key))
+    }
+}
+
+#[async_trait]
+impl Storage for LocalStorage {
+    fn name(&self) -> String {
+        format!("local/{}", self.root.display())
+    }
+
+    async fn put(&self, key: &str, source: &Path, _content_type: &str) -> Result<(), StorageError> {
+        let destination = self.path(key)?;
+        if let Some(parent) = destination.parent() {
+            tokio::fs::create_dir_all(parent).await?;
+        }
+
+        // Rename is atomic on the same filesystem; fall back to copying across filesystems
+        if tokio::fs::rename(source, &destination).await.is_err() {
+            tokio::fs::copy(source, &destination).await?;
+            tokio::fs::remove_file(source).await?;
+        }
+        Ok(())
+    }
+
+    async fn get_range(&self, key: &str, range: Option<ByteRange>) -> Result<Option<StoredObject>, StorageError> {
+        let path = self.path(key)?;
+        let mut file = match tokio::fs::File::open(&path).await {
+            Ok(file) => file,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(e.into()),
+        };
+        let total_size = file.metadata().await?.len();
+
+        let (start, end) = match range {
+            Some(range) => (range.start, range.end.unwrap_or(u64::MAX).min(total_size.saturating_sub(1))),
+            None => (0, total_size.saturating_sub(1)),
+        };
+        if start >= total_size || start > end {
+            return Ok(Some(StoredObject {
+                body: Box::pin(stream::empty()),
+                start,
+                end,
+                total_size,
+            }));
+        }
+
+        file.seek(SeekFrom::Start(start)).await?;
+        let body = ReaderStream::new(file.take(end - start + 1)).map(|chunk| chunk.map_err(StorageError::from));
+
+        Ok(Some(StoredObject { body: Box::pin(body), start, end, total_size }))
+    }
+
+    async fn exists(&self, key: &str) -> Result<bool, StorageError> {
+        Ok(tokio::fs::try_exists(self.path(key)?).await?)
+    }
+
+    async fn list(&self) -> Result<Vec<ObjectInfo>, StorageError> {
+        let root = self.root.clone();
+        tokio::task::spawn_blocking(move || {
+            let mut objects = Vec::new();
+            if !root.exists() {
+                return Ok(objects);
+            }
+            let walker = WalkDir::new(&root)
+                .into_iter()
+                .filter_entry(|entry| entry.depth() == 0 || !entry.file_name().to_string_lossy().starts_with('.'));
+            for entry in walker {
+                let entry = entry?;
+                if !entry.file_type().is_file() {
+                    continue;
+                }
+                let key = entry.path()
+                    .strip_prefix(&root)?
+                    .components()
+                    .map(|c| c.as_os_str().to_string_lossy())
+                    .collect::<Vec<_>>()
+                    .join("/");
+                objects.push(ObjectInfo { key, size: entry.metadata()?.len() });
+            }
+            Ok(objects)
+        })
+        .await?
+    }
+
+    async fn delete(&self, key: &str) -> Result<(), StorageError> {
+        match tokio::fs::remove_file(self.path(key)?).await {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e.into()),
+            _ => Ok(()),
+        }
+    }
+
+    async fn presign(&self, _key: &str, _expires_in: Duration) -> Result<Option<String>, StorageError> {
+        Ok(None)
+    }
+
+    fn local_path(&self, key: &str) -> Option<PathBuf> {
+        self.path(key).ok()
+    }
+}
@@ -0,0 +1,157 @@
+pub mod local;
+pub mod s3;
+
+use std::path::{Path, PathBuf};
+use std::pin::Pin;
+use std::sync::Arc;
+use std::time::Duration;
+use async_trait::async_trait;
+use bytes::Bytes;
+use futures_util::{Stream, StreamExt};
+use tokio::io::AsyncWriteExt;
+
+use self::local::LocalStorage;
+use self::s3::S3Storage;
+
+pub type StorageError = Box<dyn std::error::Error + Send + Sync>;
+
+/// Shared handle to the backend selected at startup.
+pub type SharedStorage = Arc<dyn Storage>;
+
+pub type ByteStream = Pin<Box<dyn Stream<Item = Result<Bytes, StorageError>> + Send>>;
+
+/// Inclusive byte range as sent in an HTTP `Range` header; `end: None` means to the end.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ByteRange {
+    pub start: u64,
+    pub end: Option<u64>,
+}
+
+/// Object contents, or the requested part of them.
+pub struct StoredObject {
+    pub body: ByteStream,
+    /// First and last byte included in `body`
+    pub start: u64,
+    pub end: u64,
+    pub total_size: u64,
+}
+
+/// Key and size of a stored object, as returned by `Storage::list`.
+#[derive(Clone, Debug)]
+pub struct ObjectInfo {
+    pub key: String,
+    pub size: u64,
+}
+
+/// Where recordings are kept. Keys are the `file_path` values stored in `recordings`,
+/// e.g. `2026/10/18/recording_1792313015_5f0c6c1f9e2b4b7e8d0a3c2b1e4f6a7d.webm`.
+#[async_trait]
+pub trait Storage: Send + Sync {
+    /// Backend identifier for logs, e.g. `s3/recordings`.
+    fn name(&self) -> String;
+
+    /// Moves a finished local file into storage under `key`.
+    async fn put(&self, key: &str, source: &Path, content_type: &str) -> Result<(), StorageError>;
+
+    /// Streams an object, or part of it. Returns `Ok(None)` if the key does not exist.
+    async fn get_range(&self, key: &str, range: Option<ByteRange>) -> Result<Option<StoredObject>, StorageError>;
+
+    async fn exists(&self, key: &str) -> Result<bool, StorageError>;
+
+    /// Every stored object. Local spool files (names starting with `.`) are skipped.
+    async fn list(&self) -> Result<Vec<ObjectInfo>, StorageError>;
+
+    /// Removes an object; missing keys are not an error.
+    async fn delete(&self, key: &str) -> Result<(), StorageError>;
+
+    /// Time-limited URL clients can fetch directly, or `None` if files must be served by the app.
+    async fn presign(&self, key: &str, expires_in: Duration) -> Result<Option<String>, StorageError>;
+
+    /// Path of the object on this machine, if the backend keeps one.
+    fn local_path(&self, _key: &str) -> Option<PathBuf> {
+        None
+    }
+
+    /// Streams a whole object.
+    async fn get(&self, key: &str) -> Result<Option<StoredObject>, StorageError> {
+        self.get_range(key, None).await
+    }
+}
+
+/// Builds the backend named by `STORAGE_BACKEND` (`local` or `s3`).
+pub fn storage_from_env() -> Result<SharedStorage, String> {
+    let backend = std::env::var("STORAGE_BACKEND").unwrap_or_else(|_| "local".to_string());
+
+    match backend.trim().to_lowercase().as_str() {
+        "local" => Ok(Arc::new(LocalStorage::from_env())),
+        "s3" => Ok(Arc::new(S3Storage::from_env()?)),
+        other => Err(format!("Unknown STORAGE_BACKEND: {}", other)),
+    }
+}
+
+fn required_env(key: &str) -> Result<String, String> {
+    std::env::var(key)
+        .ok()
+        .filter(|v| !v.trim().is_empty())
+        .ok_or_else(|| format!("{} not set", key))
+}
+
+fn optional_env(key: &str, default: &str) -> String {
+    std::env::var(key)
+        .ok()
+        .filter(|v| !v.trim().is_empty())
+        .unwrap_or_else(|| default.to_string())
+}
+
+/// Rejects keys that could escape the storage root.
+pub fn validate_key(key: &str) -> Result<(), StorageError> {
+    let valid = !key.is_empty()
+        && !key.starts_with('/')
+        && key.split('/').all(|segment| !segment.is_empty() && segment != "." && segment != "..");
+    if valid {
+        Ok(())
+    } else {
+        Err(format!("Invalid storage key: {}", key).into())
+    }
+}
+
+/// A local file holding an object's contents, removed on `release` if it was downloaded.
+pub struct LocalCopy {
+    pub path: PathBuf,
+    temporary: bool,
+}
+
+impl LocalCopy {
+    pub async fn release(self) {
+        if self.temporary {
+            let _ = tokio::fs::remove_file(&self.path).await;
+        }
+    }
+}
+
+/// Makes an object available as a local file, downloading it to the temp dir if needed.
+pub async fn local_copy(storage: &dyn Storage, key: &str) -> Result<LocalCopy, StorageError> {
+    if let Some(path) = storage.local_path(key) {
+        return Ok(LocalCopy { path, temporary: false });
+    }
+
+    let object = storage.get(key).await?.ok_or_else(|| format!("{} not found in storage", key))?;
+    let extension = Path::new(key).extension().and_then(|e| e.to_str()).unwrap_or("bin");
+    let path = std::env::temp_dir().join(format!("workflow-{}.{}", uuid::Uuid::new_v4(), extension));
+
+    let copy = LocalCopy { path, temporary: true };
+    if let Err(e) = write_stream(object.body, &copy.path).await {
+        copy.release().await;
+        return Err(e);
+    }
+    Ok(copy)
+}
+
+async fn write_stream(mut body: ByteStream, path: &Path) -> Result<(), StorageError> {
+    let mut file = tokio::fs::File::create(path).await?;
+    while let Some(chunk) = body.next().await {
+        file.write_all(&chunk?).await?;
+    }
+    file.flush().await?;
+    Ok(())
+}
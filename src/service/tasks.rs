@@ -0,0 +1,145 @@
+use chrono::NaiveDate;
+use serde_json::Value;
+use sqlx::PgConnection;
+use uuid::Uuid;
+
+/// Action item as returned by the model.
+pub struct ActionItem {
+    pub description: String,
+    pub assignee: Option<String>,
+    pub due_date: Option<NaiveDate>,
+}
+
+/// Removes `action_items` from a model reply. Entries without a description
+/// are dropped; unparseable due dates are ignored rather than failing the job.
+pub fn take_action_items(reply: &mut Value) -> Vec<ActionItem> {
+    let items = match reply.as_object_mut().and_then(|fields| fields.remove("action_items")) {
+        Some(Value::Array(items)) => items,
+        _ => return Vec::new(),
+    };
+
+    items
+        .iter()
+        .filter_map(|item| {
+            let text = |key: &str| {
+                item.get(key)
+                    .and_then(|v| v.as_str())
+                    .map(|s| s.trim().to_string())
+                    .filter(|s| !s.is_empty())
+            };
+            let description = match item {
+                Value::String(s) => Some(s.trim().to_string()).filter(|s| !s.is_empty()),
+                _ => text("description"),
+            }?;
+            let due_date = text("due_date").and_then(|d| NaiveDate::parse_from_str(&d, "%Y-%m-%d").ok());
+
+            Some(ActionItem { description, assignee: text("assignee"), due_date })
+        })
+        .collect()
+}
+
+/// Replaces the open tasks of a recording with freshly extracted ones.
+/// Tasks already marked done are kept, and items matching them are not re-added.
+pub async fn replace_extracted_tasks(
+    conn: &mut PgConnection,
+    recording_id: Uuid,
+    items: Vec<ActionItem>
+) -> Result<(), sqlx::Error> {
+    sqlx::query!(
+        "DELETE FROM tasks WHERE recording_id = $1 AND done_at IS NULL",
+        recording_id
+    )
+    .execute(&mut *conn)
+    .await?;
+
+    if items.is_empty() {
+        return Ok(());
+    }
+
+    let mut descriptions = Vec::with_capacity(items.len());
+    let mut assignees = Vec::with_capacity(items.len());
+    let mut due_dates = Vec::with_capacity(items.len());
+    for item in items {
+        descriptions.push(item.description);
+        assignees.push(item.assignee);
+        due_dates.push(item.due_date);
+    }
+
+    sqlx::query!(
+        r#"
+        INSERT INTO tasks (recording_id, description, assignee, due_date)
+        SELECT $1, item.description, item.assignee, item.due_date
+        FROM UNNEST($2::text[], $3::text[], $4::date[]) WITH ORDINALITY
+            AS item(description, assignee, due_date, position)
+        WHERE NOT EXISTS (
+            SELECT 1 FROM tasks done
+            WHERE done.recording_id = $1 AND lower(done.description) = lower(item.description)
+        )
+        ORDER BY item.position
+        "#,
+        recording_id,
+        &descriptions,
+        &assignees as &[Option<String>],
+        &due_dates as &[Option<NaiveDate>]
+    )
+    .execute(&mut *conn)
+    .await?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn reads_items_and_removes_them_from_the_reply() {
+        let mut reply = json!({
+            "title": "Planning",
+            "action_items": [
+                {"description": " Send the budget ", "assignee": "Anna", "due_date": "2026-10-20"},
+                {"description": "Book a room", "assignee": null, "due_date": null}
+            ]
+        });
+
+        let items = take_action_items(&mut reply);
+        assert_eq!(reply, json!({"title": "Planning"}));
+        assert_eq!(items.len(), 2);
+        assert_eq!(items[0].description, "Send the budget");
+        assert_eq!(items[0].assignee.as_deref(), Some("Anna"));
+        assert_eq!(items[0].due_date, NaiveDate::from_ymd_opt(2026, 10, 20));
+        assert_eq!(items[1].assignee, None);
+        assert_eq!(items[1].due_date, None);
+    }
+
+    #[test]
+    fn accepts_plain_strings() {
+        let mut reply = json!({"action_items": ["Call the bank", "  "]});
+        let items = take_action_items(&mut reply);
+        assert_eq!(items.len(), 1);
+        assert_eq!(items[0].description, "Call the bank");
+    }
+
+    #[test]
+    fn skips_items_without_description_and_ignores_bad_dates() {
+        let mut reply = json!({"action_items": [
+            {"assignee": "Anna"},
+            {"description": "", "assignee": "Ben"},
+            {"description": "Renew passport", "assignee": " ", "due_date": "next Friday"},
+            42
+        ]});
+        let items = take_action_items(&mut reply);
+        assert_eq!(items.len(), 1);
+        assert_eq!(items[0].description, "Renew passport");
+        assert_eq!(items[0].assignee, None);
+        assert_eq!(items[0].due_date, None);
+    }
+
+    #[test]
+    fn missing_or_malformed_list_yields_nothing() {
+        assert!(take_action_items(&mut json!({"title": "t"})).is_empty());
+        assert!(take_action_items(&mut json!({"action_items": "none"})).is_empty());
+        assert!(take_action_items(&mut json!("not an object")).is_empty());
+    }
+}
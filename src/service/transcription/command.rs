@@ -0,0 +1,73 @@
+use async_trait::async_trait;
+use serde_json::Value;
+use tokio::process::Command;
+use super::{
+    Transcriber, TranscriptionError, TranscriptionRequest,
+    parse_json_reply, plain_transcription, required_env
+};
+
+const INPUT_PLACEHOLDER: &str = "{input}";
+
+/// Runs a local program (e.g. a whisper.cpp binary) and reads the transcript from stdout.
+///
+/// `TRANSCRIPTION_COMMAND` is split on whitespace and run without a shell. The
+/// `{input}` placeholder is replaced with the audio path, or the path is
+/// appended as the last argument when the placeholder is missing. Output that
+/// is already a JSON object is used as-is, anything else is treated as plain text.
+pub struct CommandTranscriber {
+    program: String,
+    args: Vec<String>,
+}
+
+impl CommandTranscriber {
+    pub fn from_env() -> Result<Self, String> {
+        let command = required_env("TRANSCRIPTION_COMMAND")?;
+        let mut parts = command.split_whitespace().map(|s| s.to_string());
+        let program = parts.next().ok_or("TRANSCRIPTION_COMMAND is empty")?;
+
+        Ok(Self {
+            program,
+            args: parts.collect(),
+        })
+    }
+}
+
+#[async_trait]
+impl Transcriber for CommandTranscriber {
+    fn name(&self) -> String {
+        let program = std::path::Path::new(&self.program)
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_else(|| self.program.clone());
+        format!("command/{}", program)
+    }
+
+    async fn transcribe(&self, request: TranscriptionRequest<'_>) -> Result<Value, TranscriptionError> {
+        let input = request.path.to_string_lossy();
+        let mut args: Vec<String> = self.args
+            .iter()
+            .map(|arg| arg.replace(INPUT_PLACEHOLDER, &input))
+            .collect();
+        if !self.args.iter().any(|arg| arg.contains(INPUT_PLACEHOLDER)) {
+            args.push(input.to_string());
+        }
+
+        let output = Command::new(&self.program)
+            .args(&args)
+            .kill_on_drop(true)
+            .output()
+            .await?;
+
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            return Err(format!("{} exited with {}: {}", self.program, output.status, stderr.trim()).into());
+        }
+
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        if stdout.trim().is_empty() {
+            return Err("Transcription command produced no output".into());
+        }
+
+        Ok(parse_json_reply(&stdout).unwrap_or_else(|_| plain_transcription(&stdout)))
+    }
+}
@@ -0,0 +1,127 @@
+use async_trait::async_trait;
+use base64::{Engine as _, engine::general_purpose};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use super::{
+    Transcriber, TranscriptionError, TranscriptionRequest,
+    optional_env, parse_json_reply, required_env
+};
+
+const DEFAULT_BASE_URL: &str = "https://generativelanguage.googleapis.com/v1beta";
+const DEFAULT_MODEL: &str = "gemini-2.0-flash";
+
+/// Sends the audio inline to the Gemini `generateContent` API.
+pub struct GeminiTranscriber {
+    client: reqwest::Client,
+    base_url: String,
+    model: String,
+    api_key: String,
+}
+
+impl GeminiTranscriber {
+    /// Reads `LOCAL_GEMINI_API_KEY`, `GEMINI_MODEL` and `GEMINI_BASE_URL`.
+    pub fn from_env() -> Result<Self, String> {
+        Ok(Self {
+            client: reqwest::Client::new(),
+            base_url: optional_env("GEMINI_BASE_URL", DEFAULT_BASE_URL),
+            model: optional_env("GEMINI_MODEL", DEFAULT_MODEL),
+            api_key: required_env("LOCAL_GEMINI_API_KEY")?,
+        })
+    }
+}
+
+#[async_trait]
+impl Transcriber for GeminiTranscriber {
+    fn name(&self) -> String {
+        format!("gemini/{}", self.model)
+    }
+
+    async fn transcribe(&self, request: TranscriptionRequest<'_>) -> Result<Value, TranscriptionError> {
+        let buffer = tokio::fs::read(request.path).await?;
+        let base64_audio = general_purpose::STANDARD.encode(&buffer);
+
+        let request_body = GenerateContentRequest {
+            contents: vec![Content {
+                parts: vec![
+                    Part::Text { text: request.prompt.to_string() },
+                    Part::InlineData {
+                        inline_data: InlineData {
+                            mime_type: request.mime_type.to_string(),
+                            data: base64_audio,
+                        },
+                    },
+                ],
+            }],
+        };
+
+        let url = format!(
+            "{}/models/{}:generateContent?key={}",
+            self.base_url.trim_end_matches('/'),
+            self.model,
+            self.api_key
+        );
+
+        let res = self.client.post(&url)
+            .json(&request_body)
+            .send()
+            .await?;
+
+        if !res.status().is_success() {
+            let text = res.text().await?;
+            return Err(format!("API Error: {}", text).into());
+        }
+
+        let response_data: GenerateContentResponse = res.json().await?;
+
+        let text = response_data.candidates
+            .and_then(|candidates| candidates.into_iter().next())
+            .and_then(|candidate| candidate.content.parts.into_iter().next())
+            .and_then(|part| part.text)
+            .ok_or("No transcription text found in response")?;
+
+        parse_json_reply(&text)
+    }
+}
+
+#[derive(Serialize)]
+struct GenerateContentRequest {
+    contents: Vec<Content>,
+}
+
+#[derive(Serialize)]
+struct Content {
+    parts: Vec<Part>,
+}
+
+#[derive(Serialize)]
+#[serde(untagged)]
+enum Part {
+    Text { text: String },
+    InlineData { inline_data: InlineData },
+}
+
+#[derive(Serialize)]
+struct InlineData {
+    mime_type: String,
+    data: String,
+}
+
+#[derive(Deserialize)]
+struct GenerateContentResponse {
+    candidates: Option<Vec<Candidate>>,
+}
+
+#[derive(Deserialize)]
+struct Candidate {
+    content: CandidateContent,
+}
+
+#[derive(Deserialize)]
+struct CandidateContent {
+    parts: Vec<CandidatePart>,
+}
+
+#[derive(Deserialize)]
+struct CandidatePart {
+    text: Option<String>,
+}
@@ -0,0 +1,130 @@
+pub mod command;
+pub mod gemini;
+pub mod openai;
+
+use std::path::Path;
+use std::sync::Arc;
+use async_trait::async_trait;
+use serde_json::{json, Value};
+
+use self::command::CommandTranscriber;
+use self::gemini::GeminiTranscriber;
+use self::openai::OpenAiTranscriber;
+
+pub type TranscriptionError = Box<dyn std::error::Error + Send + Sync>;
+
+/// Shared handle to the provider selected at startup.
+pub type SharedTranscriber = Arc<dyn Transcriber>;
+
+/// Default instructions sent to providers that post-process the transcript with a language model.
+pub const DEFAULT_PROMPT: &str = include_str!("../prompt.md");
+
+/// Everything a provider needs to transcribe one recording.
+pub struct TranscriptionRequest<'a> {
+    pub path: &'a Path,
+    pub mime_type: &'a str,
+    pub prompt: &'a str,
+}
+
+#[async_trait]
+pub trait Transcriber: Send + Sync {
+    /// Provider and model identifier, e.g. `gemini/gemini-2.0-flash`.
+    fn name(&self) -> String;
+
+    /// Transcribes the audio and returns a JSON object with at least
+    /// `title`, `transcript` and `improved_transcript`.
+    async fn transcribe(&self, request: TranscriptionRequest<'_>) -> Result<Value, TranscriptionError>;
+}
+
+/// Builds the provider named by `TRANSCRIPTION_PROVIDER` (`gemini`, `openai` or `command`).
+pub fn transcriber_from_env() -> Result<SharedTranscriber, String> {
+    let provider = std::env::var("TRANSCRIPTION_PROVIDER").unwrap_or_else(|_| "gemini".to_string());
+
+    match provider.trim().to_lowercase().as_str() {
+        "gemini" => Ok(Arc::new(GeminiTranscriber::from_env()?)),
+        "openai" => Ok(Arc::new(OpenAiTranscriber::from_env()?)),
+        "command" => Ok(Arc::new(CommandTranscriber::from_env()?)),
+        other => Err(format!("Unknown TRANSCRIPTION_PROVIDER: {}", other)),
+    }
+}
+
+fn required_env(key: &str) -> Result<String, String> {
+    std::env::var(key)
+        .ok()
+        .filter(|v| !v.trim().is_empty())
+        .ok_or_else(|| format!("{} not set", key))
+}
+
+fn optional_env(key: &str, default: &str) -> String {
+    std::env::var(key)
+        .ok()
+        .filter(|v| !v.trim().is_empty())
+        .unwrap_or_else(|| default.to_string())
+}
+
+/// Parses a model reply into a JSON object, stripping markdown code fences if present.
+pub fn parse_json_reply(text: &str) -> Result<Value, TranscriptionError> {
+    let clean_text = text.trim();
+    let clean_text = clean_text
+        .strip_prefix("```json")
+        .or_else(|| clean_text.strip_prefix("```"))
+        .unwrap_or(clean_text);
+    let clean_text = clean_text.trim_end_matches("```").trim();
+
+    let value: Value = serde_json::from_str(clean_text)?;
+    if !value.is_object() {
+        return Err("Transcription reply is not a JSON object".into());
+    }
+    Ok(value)
+}
+
+/// Wraps plain transcript text from providers that do not produce a title or an improved version.
+pub fn plain_transcription(text: &str) -> Value {
+    let text = text.trim();
+    let title = text
+        .split_whitespace()
+        .take(8)
+        .collect::<Vec<_>>()
+        .join(" ");
+
+    json!({
+        "title": title,
+        "transcript": text,
+        "improved_transcript": text,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_bare_json_reply() {
+        let value = parse_json_reply(r#"{"title": "Standup", "transcript": "hi"}"#).unwrap();
+        assert_eq!(value["title"], "Standup");
+    }
+
+    #[test]
+    fn strips_code_fences_from_reply() {
+        let fenced = "```json\n{\"title\": \"Standup\"}\n```";
+        assert_eq!(parse_json_reply(fenced).unwrap()["title"], "Standup");
+
+        let unlabeled = "  ```\n{\"title\": \"Standup\"}\n```  ";
+        assert_eq!(parse_json_reply(unlabeled).unwrap()["title"], "Standup");
+    }
+
+    #[test]
+    fn rejects_replies_that_are_not_objects() {
+        assert!(parse_json_reply("[1, 2, 3]").is_err());
+        assert!(parse_json_reply("\"just text\"").is_err());
+        assert!(parse_json_reply("Sure! Here is the transcript").is_err());
+    }
+
+    #[test]
+    fn plain_transcription_titles_with_first_words() {
+        let value = plain_transcription("  one two three four five six seven eight nine ten\n");
+        assert_eq!(value["title"], "one two three four five six seven eight");
+        assert_eq!(value["transcript"], "one two three four five six seven eight nine ten");
+        assert_eq!(value["improved_transcript"], value["transcript"]);
+    }
+}
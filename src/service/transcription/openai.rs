@@ -0,0 +1,177 @@
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use super::{
+    Transcriber, TranscriptionError, TranscriptionRequest,
+    optional_env, parse_json_reply, plain_transcription
+};
+
+const DEFAULT_BASE_URL: &str = "https://api.openai.com/v1";
+const DEFAULT_TRANSCRIPTION_MODEL: &str = "whisper-1";
+const DEFAULT_CHAT_MODEL: &str = "gpt-4o-mini";
+
+/// Talks to any server implementing the OpenAI `audio/transcriptions` and
+/// `chat/completions` endpoints (OpenAI, LocalAI, faster-whisper-server, ...).
+///
+/// The verbatim transcript comes from the speech model; the chat model then
+/// produces the title and improved transcript. Setting `OPENAI_CHAT_MODEL=none`
+/// skips the second step.
+pub struct OpenAiTranscriber {
+    client: reqwest::Client,
+    base_url: String,
+    api_key: Option<String>,
+    transcription_model: String,
+    chat_model: Option<String>,
+}
+
+impl OpenAiTranscriber {
+    /// Reads `OPENAI_BASE_URL`, `OPENAI_API_KEY`, `OPENAI_TRANSCRIPTION_MODEL` and `OPENAI_CHAT_MODEL`.
+    pub fn from_env() -> Result<Self, String> {
+        let base_url = optional_env("OPENAI_BASE_URL", DEFAULT_BASE_URL);
+        let api_key = std::env::var("OPENAI_API_KEY").ok().filter(|k| !k.trim().is_empty());
+
+        // Self-hosted servers usually run without a key, the hosted API never does.
+        if api_key.is_none() && base_url == DEFAULT_BASE_URL {
+            return Err("OPENAI_API_KEY not set".to_string());
+        }
+
+        let chat_model = optional_env("OPENAI_CHAT_MODEL", DEFAULT_CHAT_MODEL);
+
+        Ok(Self {
+            client: reqwest::Client::new(),
+            base_url: base_url.trim_end_matches('/').to_string(),
+            api_key,
+            transcription_model: optional_env("OPENAI_TRANSCRIPTION_MODEL", DEFAULT_TRANSCRIPTION_MODEL),
+            chat_model: if chat_model.eq_ignore_ascii_case("none") { None } else { Some(chat_model) },
+        })
+    }
+
+    fn authorized(&self, builder: reqwest::RequestBuilder) -> reqwest::RequestBuilder {
+        match &self.api_key {
+            Some(key) => builder.bearer_auth(key),
+            None => builder,
+        }
+    }
+
+    async fn speech_to_text(&self, request: &TranscriptionRequest<'_>) -> Result<String, TranscriptionError> {
+        let buffer = tokio::fs::read(request.path).await?;
+        let file_name = request.path
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_else(|| "recording".to_string());
+
+        let file_part = reqwest::multipart::Part::bytes(buffer)
+            .file_name(file_name)
+            .mime_str(request.mime_type)?;
+        let form = reqwest::multipart::Form::new()
+            .text("model", self.transcription_model.clone())
+            .part("file", file_part);
+
+        let res = self.authorized(self.client.post(format!("{}/audio/transcriptions", self.base_url)))
+            .multipart(form)
+            .send()
+            .await?;
+
+        if !res.status().is_success() {
+            let text = res.text().await?;
+            return Err(format!("API Error: {}", text).into());
+        }
+
+        let response_data: AudioTranscriptionResponse = res.json().await?;
+        Ok(response_data.text)
+    }
+
+    async fn refine(&self, chat_model: &str, prompt: &str, transcript: &str) -> Result<Value, TranscriptionError> {
+        let request_body = ChatCompletionRequest {
+            model: chat_model.to_string(),
+            messages: vec![
+                ChatMessage { role: "system".to_string(), content: prompt.to_string() },
+                ChatMessage {
+                    role: "user".to_string(),
+                    content: format!("The audio has already been transcribed verbatim:\n\n{}", transcript),
+                },
+            ],
+            response_format: ResponseFormat { kind: "json_object".to_string() },
+        };
+
+        let res = self.authorized(self.client.post(format!("{}/chat/completions", self.base_url)))
+            .json(&request_body)
+            .send()
+            .await?;
+
+        if !res.status().is_success() {
+            let text = res.text().await?;
+            return Err(format!("API Error: {}", text).into());
+        }
+
+        let response_data: ChatCompletionResponse = res.json().await?;
+        let text = response_data.choices
+            .into_iter()
+            .next()
+            .and_then(|choice| choice.message.content)
+            .ok_or("No completion text found in response")?;
+
+        let mut value = parse_json_reply(&text)?;
+        // Keep the speech model's output as the verbatim transcript.
+        value["transcript"] = Value::String(transcript.to_string());
+        Ok(value)
+    }
+}
+
+#[async_trait]
+impl Transcriber for OpenAiTranscriber {
+    fn name(&self) -> String {
+        match &self.chat_model {
+            Some(chat_model) => format!("openai/{}+{}", self.transcription_model, chat_model),
+            None => format!("openai/{}", self.transcription_model),
+        }
+    }
+
+    async fn transcribe(&self, request: TranscriptionRequest<'_>) -> Result<Value, TranscriptionError> {
+        let transcript = self.speech_to_text(&request).await?;
+
+        match &self.chat_model {
+            Some(chat_model) => self.refine(chat_model, request.prompt, &transcript).await,
+            None => Ok(plain_transcription(&transcript)),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct AudioTranscriptionResponse {
+    text: String,
+}
+
+#[derive(Serialize)]
+struct ChatCompletionRequest {
+    model: String,
+    messages: Vec<ChatMessage>,
+    response_format: ResponseFormat,
+}
+
+#[derive(Serialize)]
+struct ChatMessage {
+    role: String,
+    content: String,
+}
+
+#[derive(Serialize)]
+struct ResponseFormat {
+    #[serde(rename = "type")]
+    kind: String,
+}
+
+#[derive(Deserialize)]
+struct ChatCompletionResponse {
+    choices: Vec<ChatChoice>,
+}
+
+#[derive(Deserialize)]
+struct ChatChoice {
+    message: ChatChoiceMessage,
+}
+
+#[derive(Deserialize)]
+struct ChatChoiceMessage {
+    content: Option<String>,
+}
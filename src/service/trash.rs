@@ -0,0 +1,92 @@
+use std::time::Duration;
+use sqlx::PgPool;
+use uuid::Uuid;
+use crate::service::storage::SharedStorage;
+use crate::service::uploads::purge_expired_sessions;
+
+const DEFAULT_RETENTION_DAYS: i64 = 30;
+const PURGE_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+/// Days a deleted recording stays in the trash, from `TRASH_RETENTION_DAYS` (default 30).
+pub fn retention_days_from_env() -> i64 {
+    std::env::var("TRASH_RETENTION_DAYS")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .filter(|days: &i64| *days >= 0)
+        .unwrap_or(DEFAULT_RETENTION_DAYS)
+}
+
+/// Outcome of one purge run.
+#[derive(Default)]
+pub struct PurgeReport {
+    pub purged: usize,
+    pub failed: Vec<(String, String)>,
+}
+
+/// Permanently removes recordings deleted more than `retention_days` ago.
+/// The file goes first; if that fails the row is kept so the next run retries it.
+pub async fn purge_expired(pool: &PgPool, storage: &SharedStorage, retention_days: i64) -> Result<PurgeReport, sqlx::Error> {
+    let expired = sqlx::query!(
+        r#"
+        SELECT id, file_path
+        FROM recordings
+        WHERE deleted_at < NOW() - make_interval(days => $1)
+        ORDER BY deleted_at ASC
+        "#,
+        retention_days as i32
+    )
+    .fetch_all(pool)
+    .await?;
+
+    let mut report = PurgeReport::default();
+    for record in expired {
+        if let Err(e) = storage.delete(&record.file_path).await {
+            report.failed.push((record.file_path, e.to_string()));
+            continue;
+        }
+        match delete_row(pool, record.id).await {
+            Ok(()) => report.purged += 1,
+            Err(e) => report.failed.push((record.file_path, e.to_string())),
+        }
+    }
+
+    Ok(report)
+}
+
+async fn delete_row(pool: &PgPool, id: Uuid) -> Result<(), sqlx::Error> {
+    sqlx::query!("DELETE FROM recordings WHERE id = $1 AND deleted_at IS NOT NULL", id)
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+/// Runs `purge_expired` every hour and logs what could not be removed. Abandoned resumable
+/// uploads are cleared on the same schedule.
+pub fn start_purge(pool: PgPool, storage: SharedStorage) {
+    let retention_days = retention_days_from_env();
+
+    tokio::spawn(async move {
+        loop {
+            match purge_expired(&pool, &storage, retention_days).await {
+                Ok(report) => log_report(&report),
+                Err(e) => eprintln!("Trash purge failed: {}", e),
+            }
+            match purge_expired_sessions(&pool).await {
+                Ok(0) => {}
+                Ok(count) => println!("Removed {} expired uploads", count),
+                Err(e) => eprintln!("Failed to remove expired uploads: {}", e),
+            }
+            tokio::time::sleep(PURGE_INTERVAL).await;
+        }
+    });
+}
+
+fn log_report(report: &PurgeReport) {
+    if report.purged == 0 && report.failed.is_empty() {
+        return;
+    }
+    println!("Trash purge: removed {} recordings, {} failed", report.purged, report.failed.len());
+    for (path, error) in &report.failed {
+        eprintln!("  could not purge {}: {}", path, error);
+    }
+}
@@ -0,0 +1,480 @@
+use std::path::{Path, PathBuf};
+use chrono::{DateTime, Datelike, NaiveDate, Utc};
+use futures_util::StreamExt;
+use sha2::{Digest, Sha256};
+use sqlx::PgPool;
+use tokio::io::AsyncReadExt;
+use uuid::Uuid;
+use crate::models::dtos::{CaptureTimes, WebhookEvent};
+use crate::service::jobs::JobQueue;
+use crate::service::storage::{Storage, StorageError};
+use crate::service::webhooks;
+use crate::timezone::{local_date, on_day, Tz};
+
+/// Local directory where uploads are assembled before they are handed to storage.
+const SPOOL_ROOT: &str = "recordings";
+
+/// Largest accepted recording when `UPLOAD_MAX_BYTES` is not set (500 MiB).
+const DEFAULT_MAX_UPLOAD_BYTES: u64 = 500 * 1024 * 1024;
+
+/// Hours an unfinished resumable upload is kept after its last chunk, unless
+/// `UPLOAD_SESSION_TTL_HOURS` says otherwise.
+const DEFAULT_UPLOAD_SESSION_TTL_HOURS: i32 = 24;
+
+/// Bytes read from the start of a file to recognise its container and codec.
+pub const SNIFF_LEN: usize = 4096;
+
+/// Reads `UPLOAD_MAX_BYTES`, the size limit for a single recording.
+pub fn max_upload_bytes() -> u64 {
+    std::env::var("UPLOAD_MAX_BYTES")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .filter(|n: &u64| *n > 0)
+        .unwrap_or(DEFAULT_MAX_UPLOAD_BYTES)
+}
+
+/// Reads `UPLOAD_SESSION_TTL_HOURS`, how long a resumable upload stays open without new chunks.
+pub fn upload_session_ttl_hours() -> i32 {
+    std::env::var("UPLOAD_SESSION_TTL_HOURS")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .filter(|n: &i32| *n > 0)
+        .unwrap_or(DEFAULT_UPLOAD_SESSION_TTL_HOURS)
+}
+
+/// Audio containers accepted for upload.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AudioFormat {
+    WebM,
+    Ogg,
+    Mp4,
+    Wav,
+    Mp3,
+    Flac,
+}
+
+impl AudioFormat {
+    /// Recognises a container from the first `SNIFF_LEN` bytes of a file.
+    pub fn detect(header: &[u8]) -> Option<Self> {
+        match header {
+            [0x1A, 0x45, 0xDF, 0xA3, ..] => Some(AudioFormat::WebM),
+            [b'O', b'g', b'g', b'S', ..] => Some(AudioFormat::Ogg),
+            [_, _, _, _, b'f', b't', b'y', b'p', ..] => Some(AudioFormat::Mp4),
+            [b'R', b'I', b'F', b'F', _, _, _, _, b'W', b'A', b'V', b'E', ..] => Some(AudioFormat::Wav),
+            [b'f', b'L', b'a', b'C', ..] => Some(AudioFormat::Flac),
+            // ID3 tag or a bare MPEG audio frame sync
+            [b'I', b'D', b'3', ..] => Some(AudioFormat::Mp3),
+            [0xFF, second, ..] if second & 0xE0 == 0xE0 => Some(AudioFormat::Mp3),
+            _ => None,
+        }
+    }
+
+    /// Recognises a stored recording from its file extension.
+    pub fn from_extension(extension: &str) -> Option<Self> {
+        match extension.to_ascii_lowercase().as_str() {
+            "webm" => Some(AudioFormat::WebM),
+            "ogg" | "oga" | "opus" => Some(AudioFormat::Ogg),
+            "m4a" | "mp4" => Some(AudioFormat::Mp4),
+            "wav" => Some(AudioFormat::Wav),
+            "mp3" => Some(AudioFormat::Mp3),
+            "flac" => Some(AudioFormat::Flac),
+            _ => None,
+        }
+    }
+
+    /// File extension used when storing a recording of this format.
+    pub fn extension(self) -> &'static str {
+        match self {
+            AudioFormat::WebM => "webm",
+            AudioFormat::Ogg => "ogg",
+            AudioFormat::Mp4 => "m4a",
+            AudioFormat::Wav => "wav",
+            AudioFormat::Mp3 => "mp3",
+            AudioFormat::Flac => "flac",
+        }
+    }
+
+    /// MIME type without parameters, as sent to transcription providers.
+    pub fn essence(self) -> &'static str {
+        match self {
+            AudioFormat::WebM => "audio/webm",
+            AudioFormat::Ogg => "audio/ogg",
+            AudioFormat::Mp4 => "audio/mp4",
+            AudioFormat::Wav => "audio/wav",
+            AudioFormat::Mp3 => "audio/mpeg",
+            AudioFormat::Flac => "audio/flac",
+        }
+    }
+
+    /// Full MIME type, with the codec when the header names one (e.g. `audio/webm;codecs=opus`).
+    pub fn mime_type(self, header: &[u8]) -> String {
+        match self.codec(header) {
+            Some(codec) => format!("{};codecs={}", self.essence(), codec),
+            None => self.essence().to_string(),
+        }
+    }
+
+    // WebM stores the codec id in its track entry, Ogg in the first packet;
+    // both sit well inside the sniffed header
+    fn codec(self, header: &[u8]) -> Option<&'static str> {
+        let markers: &[(&[u8], &'static str)] = match self {
+            AudioFormat::WebM => &[(b"A_OPUS", "opus"), (b"A_VORBIS", "vorbis")],
+            AudioFormat::Ogg => &[(b"OpusHead", "opus"), (b"\x01vorbis", "vorbis"), (b"\x7fFLAC", "flac")],
+            AudioFormat::Mp4 => &[(b"Opus", "opus")],
+            _ => &[],
+        };
+        markers
+            .iter()
+            .find(|(marker, _)| header.windows(marker.len()).any(|w| w == *marker))
+            .map(|(_, codec)| *codec)
+    }
+}
+
+/// Strips parameters such as `;codecs=opus` from a stored MIME type.
+pub fn mime_essence(mime_type: &str) -> &str {
+    mime_type.split(';').next().unwrap_or(mime_type).trim()
+}
+
+/// Where an upload is filed: `relative_dir` under `recordings/` and the time stored on the recording.
+pub struct UploadTarget {
+    pub relative_dir: String,
+    pub recorded_at: DateTime<Utc>,
+}
+
+/// Files uploads under the day capture started in `tz` (default now), or under the day given
+/// as `YYYY-MM-DD`. A back-dated recording keeps the time of day it was captured at.
+pub fn upload_target(date: Option<&str>, started_at: Option<DateTime<Utc>>, tz: Tz) -> UploadTarget {
+    let captured = started_at.unwrap_or_else(Utc::now);
+    let day = date
+        .and_then(|d| NaiveDate::parse_from_str(d, "%Y-%m-%d").ok())
+        .unwrap_or_else(|| local_date(captured, tz));
+
+    UploadTarget {
+        relative_dir: relative_dir_for(day),
+        recorded_at: on_day(day, captured, tz),
+    }
+}
+
+/// Checks capture times sent by a client and derives the duration from the timestamps
+/// when it is missing.
+pub fn normalize_capture(capture: CaptureTimes) -> Result<CaptureTimes, String> {
+    if let Some(duration_ms) = capture.duration_ms {
+        if duration_ms < 0 {
+            return Err("duration_ms must not be negative".to_string());
+        }
+    }
+
+    match (capture.started_at, capture.ended_at) {
+        (Some(started_at), Some(ended_at)) if ended_at < started_at => {
+            Err("ended_at must not be before started_at".to_string())
+        }
+        (Some(started_at), Some(ended_at)) => Ok(CaptureTimes {
+            duration_ms: capture.duration_ms.or(Some((ended_at - started_at).num_milliseconds())),
+            ..capture
+        }),
+        _ => Ok(capture),
+    }
+}
+
+/// Storage directory for recordings made on `day`, e.g. `2026/10/18`.
+pub fn relative_dir_for(day: NaiveDate) -> String {
+    format!("{}/{}/{}", day.year(), day.month(), day.day())
+}
+
+/// Directory for in-progress uploads filed under `relative_dir`.
+pub fn spool_dir(relative_dir: &str) -> PathBuf {
+    PathBuf::from(SPOOL_ROOT).join(relative_dir)
+}
+
+/// Partial file of the resumable upload `id` filed under `relative_dir`.
+pub fn session_part_path(relative_dir: &str, id: Uuid) -> PathBuf {
+    spool_dir(relative_dir).join(format!(".upload-{}.part", id))
+}
+
+/// Removes resumable uploads that expired, with their partial files. Returns how many went.
+pub async fn purge_expired_sessions(pool: &PgPool) -> Result<u64, sqlx::Error> {
+    let expired = sqlx::query!(
+        "DELETE FROM upload_sessions WHERE expires_at < NOW() RETURNING id, relative_dir, status"
+    )
+    .fetch_all(pool)
+    .await?;
+
+    for session in &expired {
+        // Completed sessions already handed their file to storage
+        if session.status != "OPEN" {
+            continue;
+        }
+        let part = session_part_path(&session.relative_dir, session.id);
+        if let Err(e) = tokio::fs::remove_file(&part).await {
+            if e.kind() != std::io::ErrorKind::NotFound {
+                eprintln!("Failed to remove expired upload {}: {}", part.display(), e);
+            }
+        }
+    }
+
+    Ok(expired.len() as u64)
+}
+
+/// Moves a finished upload into storage as `<relative_dir>/<stem>_<uuid>.<extension>`. The random
+/// suffix keeps uploads that share a stem (e.g. made in the same second) from overwriting
+/// each other. Returns the file name used.
+pub async fn store_upload(
+    storage: &dyn Storage,
+    temp_path: &Path,
+    relative_dir: &str,
+    stem: &str,
+    format: AudioFormat,
+    mime_type: &str
+) -> Result<String, StorageError> {
+    let filename = stored_filename(stem, Uuid::new_v4(), format);
+    storage.put(&format!("{}/{}", relative_dir, filename), temp_path, mime_type).await?;
+    Ok(filename)
+}
+
+fn stored_filename(stem: &str, suffix: Uuid, format: AudioFormat) -> String {
+    format!("{}_{}.{}", stem, suffix.simple(), format.extension())
+}
+
+/// Removes a stored file whose `recordings` row could not be written. A failure is
+/// logged rather than returned; the integrity checker reports the file as an orphan.
+pub async fn discard_stored(storage: &dyn Storage, key: &str) {
+    if let Err(e) = storage.delete(key).await {
+        eprintln!("Failed to remove {} from storage after a failed upload: {}", key, e);
+    }
+}
+
+/// Hex SHA-256 of a local file.
+pub async fn file_sha256(path: &Path) -> std::io::Result<String> {
+    let mut file = tokio::fs::File::open(path).await?;
+    let mut hasher = Sha256::new();
+    let mut buffer = vec![0; 64 * 1024];
+    loop {
+        let read = file.read(&mut buffer).await?;
+        if read == 0 {
+            break;
+        }
+        hasher.update(&buffer[..read]);
+    }
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
+/// Hex SHA-256 of a stored object, or `None` if it does not exist.
+pub async fn object_sha256(storage: &dyn Storage, key: &str) -> Result<Option<String>, StorageError> {
+    let Some(mut object) = storage.get(key).await? else {
+        return Ok(None);
+    };
+    let mut hasher = Sha256::new();
+    while let Some(chunk) = object.body.next().await {
+        hasher.update(&chunk?);
+    }
+    Ok(Some(format!("{:x}", hasher.finalize())))
+}
+
+/// Live recording of `owner_id` with the same audio, if there is one. Recordings in the
+/// trash are ignored.
+pub async fn find_duplicate(pool: &PgPool, owner_id: Uuid, sha256: &str) -> Result<Option<Uuid>, sqlx::Error> {
+    let record = sqlx::query!(
+        "SELECT id FROM recordings WHERE owner_id = $1 AND sha256 = $2 AND deleted_at IS NULL",
+        owner_id,
+        sha256
+    )
+    .fetch_optional(pool)
+    .await?;
+    Ok(record.map(|r| r.id))
+}
+
+/// A file that has been moved into storage and is about to get its `recordings` row.
+pub struct NewRecording<'a> {
+    pub owner_id: Uuid,
+    pub filename: &'a str,
+    pub file_path: &'a str,
+    pub mime_type: &'a str,
+    pub size_bytes: i64,
+    pub sha256: &'a str,
+    pub recorded_at: DateTime<Utc>,
+    pub capture: CaptureTimes,
+    /// Delays transcription, e.g. to spread out a bulk import; `None` queues it right away
+    pub transcribe_at: Option<DateTime<Utc>>,
+}
+
+/// Outcome of `register_recording`.
+pub enum Registered {
+    New(Uuid),
+    /// Identical audio was already stored; no row or job was created
+    Duplicate(Uuid),
+}
+
+impl Registered {
+    pub fn id(&self) -> Uuid {
+        match self {
+            Registered::New(id) | Registered::Duplicate(id) => *id,
+        }
+    }
+}
+
+/// Records a stored upload, queues it for transcription and announces it to open pages and
+/// webhooks, unless a live recording of the same owner has the same checksum. Callers check
+/// `find_duplicate` first to avoid storing the file at all; the unique index catches uploads of
+/// the same audio that race each other.
+pub async fn register_recording(pool: &PgPool, jobs: &JobQueue, recording: &NewRecording<'_>) -> Result<Registered, sqlx::Error> {
+    let record = sqlx::query!(
+        r#"
+        INSERT INTO recordings
+            (owner_id, filename, file_path, mime_type, size_bytes, sha256, created_at, started_at, ended_at, duration_ms)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
+        ON CONFLICT (owner_id, sha256) WHERE deleted_at IS NULL DO NOTHING
+        RETURNING id
+        "#,
+        recording.owner_id,
+        recording.filename,
+        recording.file_path,
+        recording.mime_type,
+        recording.size_bytes,
+        recording.sha256,
+        recording.recorded_at,
+        recording.capture.started_at,
+        recording.capture.ended_at,
+        recording.capture.duration_ms
+    )
+    .fetch_optional(pool)
+    .await?;
+
+    match record {
+        Some(record) => {
+            jobs.enqueue_at(pool, record.id, recording.transcribe_at.unwrap_or_else(Utc::now)).await?;
+            jobs.events().recording_changed(pool, record.id).await;
+            webhooks::trigger(pool, WebhookEvent::Uploaded, record.id).await;
+            Ok(Registered::New(record.id))
+        }
+        None => {
+            let existing = find_duplicate(pool, recording.owner_id, recording.sha256).await?.ok_or(sqlx::Error::RowNotFound)?;
+            Ok(Registered::Duplicate(existing))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn padded(prefix: &[u8]) -> Vec<u8> {
+        let mut header = prefix.to_vec();
+        header.resize(64, 0);
+        header
+    }
+
+    #[test]
+    fn detects_containers_from_magic_bytes() {
+        let cases: [(&[u8], AudioFormat); 8] = [
+            (&[0x1A, 0x45, 0xDF, 0xA3], AudioFormat::WebM),
+            (b"OggS", AudioFormat::Ogg),
+            (b"\0\0\0\x20ftypM4A ", AudioFormat::Mp4),
+            (b"RIFF\x24\0\0\0WAVEfmt ", AudioFormat::Wav),
+            (b"fLaC", AudioFormat::Flac),
+            (b"ID3\x04", AudioFormat::Mp3),
+            (&[0xFF, 0xFB, 0x90], AudioFormat::Mp3),
+            (&[0xFF, 0xF3, 0x64], AudioFormat::Mp3),
+        ];
+        for (header, format) in cases {
+            assert_eq!(AudioFormat::detect(&padded(header)), Some(format));
+        }
+    }
+
+    #[test]
+    fn rejects_other_files() {
+        assert_eq!(AudioFormat::detect(b""), None);
+        assert_eq!(AudioFormat::detect(b"RIFF\x24\0\0\0AVI LIST"), None);
+        assert_eq!(AudioFormat::detect(&padded(b"%PDF-1.7")), None);
+        assert_eq!(AudioFormat::detect(&padded(b"<html>")), None);
+        assert_eq!(AudioFormat::detect(&[0xFF, 0xD8, 0xFF, 0xE0]), None);
+    }
+
+    #[test]
+    fn extensions_round_trip() {
+        for format in [AudioFormat::WebM, AudioFormat::Ogg, AudioFormat::Mp4, AudioFormat::Wav, AudioFormat::Mp3, AudioFormat::Flac] {
+            assert_eq!(AudioFormat::from_extension(format.extension()), Some(format));
+        }
+        assert_eq!(AudioFormat::from_extension("OPUS"), Some(AudioFormat::Ogg));
+        assert_eq!(AudioFormat::from_extension("txt"), None);
+    }
+
+    #[test]
+    fn mime_type_names_the_codec_when_the_header_has_one() {
+        let webm = padded(&[0x1A, 0x45, 0xDF, 0xA3, 0x9F, 0x42, 0x86, 0x81, 0x01, b'A', b'_', b'O', b'P', b'U', b'S']);
+        assert_eq!(AudioFormat::WebM.mime_type(&webm), "audio/webm;codecs=opus");
+
+        let ogg = padded(b"OggS\0\x02\0\0\0\0\0\0\0\0\x01vorbis");
+        assert_eq!(AudioFormat::Ogg.mime_type(&ogg), "audio/ogg;codecs=vorbis");
+
+        assert_eq!(AudioFormat::WebM.mime_type(&padded(&[0x1A, 0x45, 0xDF, 0xA3])), "audio/webm");
+        assert_eq!(AudioFormat::Mp3.mime_type(&padded(b"ID3 Opus")), "audio/mpeg");
+    }
+
+    #[test]
+    fn mime_essence_drops_parameters() {
+        assert_eq!(mime_essence("audio/webm;codecs=opus"), "audio/webm");
+        assert_eq!(mime_essence("audio/ogg ; codecs=vorbis"), "audio/ogg");
+        assert_eq!(mime_essence("audio/mpeg"), "audio/mpeg");
+    }
+
+    #[test]
+    fn stored_names_differ_for_the_same_stem() {
+        let first = stored_filename("recording_1792313015", Uuid::new_v4(), AudioFormat::WebM);
+        let second = stored_filename("recording_1792313015", Uuid::new_v4(), AudioFormat::WebM);
+        assert_ne!(first, second);
+        assert!(first.starts_with("recording_1792313015_"));
+        assert!(first.ends_with(".webm"));
+    }
+
+    fn utc(h: u32, m: u32) -> DateTime<Utc> {
+        use chrono::TimeZone;
+        Utc.with_ymd_and_hms(2026, 10, 18, h, m, 0).unwrap()
+    }
+
+    #[test]
+    fn duration_is_derived_from_the_timestamps() {
+        let capture = CaptureTimes { started_at: Some(utc(9, 0)), ended_at: Some(utc(9, 2)), duration_ms: None };
+        assert_eq!(normalize_capture(capture).unwrap().duration_ms, Some(120_000));
+    }
+
+    #[test]
+    fn a_reported_duration_is_kept() {
+        let capture = CaptureTimes { started_at: Some(utc(9, 0)), ended_at: Some(utc(9, 2)), duration_ms: Some(90_000) };
+        assert_eq!(normalize_capture(capture).unwrap().duration_ms, Some(90_000));
+
+        let capture = CaptureTimes { duration_ms: Some(5_000), ..Default::default() };
+        assert_eq!(normalize_capture(capture).unwrap().duration_ms, Some(5_000));
+    }
+
+    #[test]
+    fn inconsistent_capture_times_are_rejected() {
+        let backwards = CaptureTimes { started_at: Some(utc(9, 2)), ended_at: Some(utc(9, 0)), duration_ms: None };
+        assert!(normalize_capture(backwards).is_err());
+
+        let negative = CaptureTimes { duration_ms: Some(-1), ..Default::default() };
+        assert!(normalize_capture(negative).is_err());
+    }
+
+    #[test]
+    fn uploads_are_filed_under_the_local_day_capture_started() {
+        // 23:30 UTC is already the next day in Berlin
+        let target = upload_target(None, Some(utc(23, 30)), chrono_tz::Europe::Berlin);
+        assert_eq!(target.relative_dir, "2026/10/19");
+        assert_eq!(target.recorded_at, utc(23, 30));
+    }
+
+    #[test]
+    fn back_dated_uploads_keep_their_local_time_of_day() {
+        // 10:15 in Berlin, summer time, moved to a winter day
+        let target = upload_target(Some("2026-12-01"), Some(utc(8, 15)), chrono_tz::Europe::Berlin);
+        assert_eq!(target.relative_dir, "2026/12/1");
+        assert_eq!(target.recorded_at.to_rfc3339(), "2026-12-01T09:15:00+00:00");
+    }
+
+    #[test]
+    fn an_unreadable_date_falls_back_to_the_capture_day() {
+        let target = upload_target(Some("yesterday"), Some(utc(9, 0)), Tz::UTC);
+        assert_eq!(target.relative_dir, "2026/10/18");
+        assert_eq!(target.recorded_at, utc(9, 0));
+    }
+}
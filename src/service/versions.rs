@@ -0,0 +1,85 @@
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use similar::TextDiff;
+use sqlx::PgConnection;
+use uuid::Uuid;
+
+/// Origin of a transcription revision.
+pub enum VersionSource {
+    Model { model: String, prompt_hash: String },
+    Manual,
+    Restore { from: Uuid },
+}
+
+/// Hex SHA-256 of a prompt, so revisions can be traced back to the instructions that produced them.
+pub fn prompt_hash(prompt: &str) -> String {
+    format!("{:x}", Sha256::digest(prompt.as_bytes()))
+}
+
+/// Replaces a recording's transcription and records the change as a new version.
+///
+/// Run inside a transaction: the current text is locked, diffed against the
+/// new one and both the version row and `recordings.transcription_text` are written.
+pub async fn save_transcription(
+    conn: &mut PgConnection,
+    recording_id: Uuid,
+    transcription: &Value,
+    source: VersionSource
+) -> Result<Uuid, sqlx::Error> {
+    let current = sqlx::query!(
+        "SELECT transcription_text FROM recordings WHERE id = $1 FOR UPDATE",
+        recording_id
+    )
+    .fetch_optional(&mut *conn)
+    .await?
+    .ok_or(sqlx::Error::RowNotFound)?;
+
+    let diff = diff_transcriptions(current.transcription_text.as_ref(), transcription);
+
+    let (source_name, model, prompt_hash, restored_from) = match source {
+        VersionSource::Model { model, prompt_hash } => ("model", Some(model), Some(prompt_hash), None),
+        VersionSource::Manual => ("manual", None, None, None),
+        VersionSource::Restore { from } => ("restore", None, None, Some(from)),
+    };
+
+    let version = sqlx::query!(
+        r#"
+        INSERT INTO transcription_versions
+            (recording_id, transcription_text, source, model, prompt_hash, restored_from, diff)
+        VALUES ($1, $2, $3, $4, $5, $6, $7)
+        RETURNING id
+        "#,
+        recording_id,
+        transcription,
+        source_name,
+        model,
+        prompt_hash,
+        restored_from,
+        diff
+    )
+    .fetch_one(&mut *conn)
+    .await?;
+
+    sqlx::query!(
+        "UPDATE recordings SET transcription_text = $1 WHERE id = $2",
+        transcription,
+        recording_id
+    )
+    .execute(&mut *conn)
+    .await?;
+
+    Ok(version.id)
+}
+
+// Unified line diff of the pretty-printed JSON
+fn diff_transcriptions(previous: Option<&Value>, current: &Value) -> String {
+    let previous = previous
+        .map(|v| serde_json::to_string_pretty(v).unwrap_or_default())
+        .unwrap_or_default();
+    let current = serde_json::to_string_pretty(current).unwrap_or_default();
+
+    TextDiff::from_lines(&previous, &current)
+        .unified_diff()
+        .header("previous", "current")
+        .to_string()
+}
@@ -0,0 +1,647 @@
+use std::net::{IpAddr, SocketAddr};
+use std::sync::Arc;
+use std::time::Duration;
+use chrono::Utc;
+use hmac::{Hmac, Mac};
+use reqwest::Url;
+use sha2::Sha256;
+use sqlx::postgres::PgListener;
+use sqlx::PgPool;
+use tokio::sync::Notify;
+use uuid::Uuid;
+use crate::models::dtos::{CreateWebhookRequest, CreatedWebhook, Webhook, WebhookDelivery, WebhookEvent};
+use crate::service::accounts::new_token;
+
+const DEFAULT_MAX_ATTEMPTS: i32 = 8;
+const DEFAULT_WORKERS: usize = 4;
+const POLL_INTERVAL: Duration = Duration::from_secs(30);
+const LISTEN_RETRY: Duration = Duration::from_secs(1);
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+const BACKOFF_BASE_SECS: i64 = 30;
+const BACKOFF_MAX_SECS: i64 = 6 * 60 * 60;
+const RECENT_DELIVERIES: i64 = 50;
+const REAP_INTERVAL: Duration = Duration::from_secs(60);
+// Far beyond the request timeout, so only deliveries whose result was never written qualify
+const STALE_AFTER: Duration = Duration::from_secs(5 * 60);
+
+// Postgres channel that wakes the sender, also for deliveries queued by CLI commands
+const NOTIFY_CHANNEL: &str = "webhook_deliveries";
+
+/// Prefix of generated secrets.
+pub const SECRET_PREFIX: &str = "whsec_";
+
+/// Attempts before a delivery is given up, from `WEBHOOK_MAX_ATTEMPTS` (default 8).
+pub fn max_attempts_from_env() -> i32 {
+    std::env::var("WEBHOOK_MAX_ATTEMPTS")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .filter(|n: &i32| *n > 0)
+        .unwrap_or(DEFAULT_MAX_ATTEMPTS)
+}
+
+/// Whether `WEBHOOK_ALLOW_PRIVATE=true` lets webhooks reach loopback and private addresses,
+/// e.g. a receiver on the same machine during development.
+fn private_targets_allowed() -> bool {
+    std::env::var("WEBHOOK_ALLOW_PRIVATE").is_ok_and(|v| v.eq_ignore_ascii_case("true"))
+}
+
+/// Checks a webhook request, returning it with the URL trimmed and the events in canonical
+/// order, without repeats. The message says what is wrong with it.
+pub async fn validate_webhook(request: &CreateWebhookRequest) -> Result<CreateWebhookRequest, String> {
+    let url = request.url.trim();
+    match Url::parse(url) {
+        Ok(parsed) if matches!(parsed.scheme(), "http" | "https") => {
+            resolve_target(&parsed).await?;
+        }
+        _ => return Err("Webhooks need an http or https URL".to_string()),
+    }
+    let events: Vec<WebhookEvent> = WebhookEvent::ALL
+        .into_iter()
+        .filter(|event| request.events.contains(event))
+        .collect();
+    if events.is_empty() {
+        return Err("Webhooks need at least one event".to_string());
+    }
+    Ok(CreateWebhookRequest {
+        url: url.to_string(),
+        secret: request.secret.clone(),
+        events,
+    })
+}
+
+/// Subscribes `owner_id` to recording events, returning the webhook with the secret its
+/// payloads are signed with. The request must have passed [`validate_webhook`].
+pub async fn create_webhook(pool: &PgPool, owner_id: Uuid, request: &CreateWebhookRequest) -> Result<CreatedWebhook, sqlx::Error> {
+    let url = request.url.as_str();
+    let events = request.events.clone();
+    let secret = match request.secret.as_deref().map(str::trim) {
+        Some(secret) if !secret.is_empty() => secret.to_string(),
+        _ => format!("{}{}", SECRET_PREFIX, new_token()),
+    };
+
+    let event_names: Vec<String> = events.iter().map(|event| event.as_str().to_string()).collect();
+    let row = sqlx::query!(
+        r#"
+        INSERT INTO webhooks (owner_id, url, secret, events)
+        VALUES ($1, $2, $3, $4)
+        RETURNING id, created_at
+        "#,
+        owner_id,
+        url,
+        secret,
+        &event_names
+    )
+    .fetch_one(pool)
+    .await?;
+
+    Ok(CreatedWebhook {
+        webhook: Webhook {
+            id: row.id,
+            url: url.to_string(),
+            events,
+            created_at: row.created_at,
+        },
+        secret,
+    })
+}
+
+/// The user's webhooks, newest first.
+pub async fn list_webhooks(pool: &PgPool, owner_id: Uuid) -> Result<Vec<Webhook>, sqlx::Error> {
+    let rows = sqlx::query!(
+        r#"
+        SELECT id, url, events, created_at
+        FROM webhooks
+        WHERE owner_id = $1
+        ORDER BY created_at DESC, id DESC
+        "#,
+        owner_id
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows
+        .into_iter()
+        .map(|row| Webhook {
+            id: row.id,
+            url: row.url,
+            events: row.events.iter().filter_map(|name| WebhookEvent::parse(name)).collect(),
+            created_at: row.created_at,
+        })
+        .collect())
+}
+
+/// Removes a webhook along with its queued deliveries.
+pub async fn delete_webhook(pool: &PgPool, owner_id: Uuid, id: Uuid) -> Result<(), sqlx::Error> {
+    sqlx::query!(
+        "DELETE FROM webhooks WHERE id = $1 AND owner_id = $2 RETURNING id",
+        id,
+        owner_id
+    )
+    .fetch_optional(pool)
+    .await?
+    .ok_or(sqlx::Error::RowNotFound)?;
+    Ok(())
+}
+
+/// The latest deliveries of one of the user's webhooks, newest first.
+pub async fn list_deliveries(pool: &PgPool, owner_id: Uuid, webhook_id: Uuid) -> Result<Vec<WebhookDelivery>, sqlx::Error> {
+    sqlx::query!("SELECT id FROM webhooks WHERE id = $1 AND owner_id = $2", webhook_id, owner_id)
+        .fetch_optional(pool)
+        .await?
+        .ok_or(sqlx::Error::RowNotFound)?;
+
+    sqlx::query_as!(
+        WebhookDelivery,
+        r#"
+        SELECT id, event, status, attempts, response_status, last_error, created_at, updated_at
+        FROM webhook_deliveries
+        WHERE webhook_id = $1
+        ORDER BY created_at DESC, id DESC
+        LIMIT $2
+        "#,
+        webhook_id,
+        RECENT_DELIVERIES
+    )
+    .fetch_all(pool)
+    .await
+}
+
+/// Queues a payload for every webhook of the recording's owner that subscribed to `event`.
+/// The recording is snapshotted now, so later changes don't alter what is sent. Webhooks are
+/// a side effect of the change that triggered them, so failures are only logged.
+pub async fn trigger(pool: &PgPool, event: WebhookEvent, recording_id: Uuid) {
+    if let Err(e) = queue_deliveries(pool, event, recording_id).await {
+        eprintln!("Failed to queue {} webhooks for recording {}: {}", event.as_str(), recording_id, e);
+    }
+}
+
+async fn queue_deliveries(pool: &PgPool, event: WebhookEvent, recording_id: Uuid) -> Result<(), sqlx::Error> {
+    let queued = sqlx::query!(
+        r#"
+        INSERT INTO webhook_deliveries (webhook_id, event, payload, max_attempts)
+        SELECT
+            w.id,
+            $2,
+            jsonb_build_object(
+                'event', $2::text,
+                'occurred_at', NOW(),
+                'recording', jsonb_build_object(
+                    'id', r.id,
+                    'name', r.filename,
+                    'title', r.transcription_text->>'title',
+                    'transcript', coalesce(r.transcription_text->>'improved_transcript', r.transcription_text->>'transcript'),
+                    'status', r.transcription_status,
+                    'error', r.transcription_error,
+                    'group_id', r.group_id,
+                    'group_name', g.name,
+                    'created_at', r.created_at,
+                    'duration_ms', r.duration_ms,
+                    'deleted_at', r.deleted_at
+                )
+            ),
+            $3
+        FROM recordings r
+        JOIN webhooks w ON w.owner_id = r.owner_id AND $2 = ANY(w.events)
+        LEFT JOIN task_groups g ON g.id = r.group_id
+        WHERE r.id = $1
+        "#,
+        recording_id,
+        event.as_str(),
+        max_attempts_from_env()
+    )
+    .execute(pool)
+    .await?;
+
+    if queued.rows_affected() > 0 {
+        sqlx::query!("SELECT pg_notify($1, '')", NOTIFY_CHANNEL)
+            .execute(pool)
+            .await?;
+    }
+    Ok(())
+}
+
+struct ClaimedDelivery {
+    id: Uuid,
+    event: String,
+    payload: serde_json::Value,
+    attempts: i32,
+    max_attempts: i32,
+    url: String,
+    secret: String,
+}
+
+/// Requeues deliveries interrupted by a restart and starts `WEBHOOK_WORKERS` senders
+/// (default 4), so a slow receiver only holds up its own deliveries. Senders wake up when
+/// deliveries are queued and check for due retries every 30 seconds; deliveries stuck
+/// running are handed out again after five minutes.
+pub async fn start_delivery(pool: PgPool) -> Result<(), sqlx::Error> {
+    sqlx::query!(
+        r#"
+        UPDATE webhook_deliveries
+        SET status = 'QUEUED', locked_at = NULL, run_at = NOW(), updated_at = NOW()
+        WHERE status = 'RUNNING'
+        "#
+    )
+    .execute(&pool)
+    .await?;
+
+    let mut listener = PgListener::connect_with(&pool).await?;
+    listener.listen(NOTIFY_CHANNEL).await?;
+
+    let workers = std::env::var("WEBHOOK_WORKERS")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .filter(|n: &usize| *n > 0)
+        .unwrap_or(DEFAULT_WORKERS);
+
+    let notify = Arc::new(Notify::new());
+    for _ in 0..workers {
+        let pool = pool.clone();
+        let notify = notify.clone();
+        tokio::spawn(async move { run_sender(pool, notify).await });
+    }
+    {
+        let pool = pool.clone();
+        let notify = notify.clone();
+        tokio::spawn(async move { run_reaper(pool, notify).await });
+    }
+    tokio::spawn(async move { forward_notifications(listener, notify).await });
+
+    Ok(())
+}
+
+// A delivery stays RUNNING if its result could not be written (e.g. the database was briefly
+// unreachable); once its claim is older than `STALE_AFTER` it is sent again
+async fn run_reaper(pool: PgPool, notify: Arc<Notify>) {
+    loop {
+        tokio::time::sleep(REAP_INTERVAL).await;
+        match requeue_stale(&pool, STALE_AFTER).await {
+            Ok(0) => {}
+            Ok(count) => {
+                println!("Webhooks: requeued {} stalled deliveries", count);
+                notify.notify_one();
+            }
+            Err(e) => eprintln!("Failed to requeue stalled webhook deliveries: {}", e),
+        }
+    }
+}
+
+async fn requeue_stale(pool: &PgPool, timeout: Duration) -> Result<u64, sqlx::Error> {
+    let result = sqlx::query!(
+        r#"
+        UPDATE webhook_deliveries
+        SET status = 'QUEUED', locked_at = NULL, run_at = NOW(), updated_at = NOW()
+        WHERE status = 'RUNNING' AND locked_at < NOW() - make_interval(secs => $1)
+        "#,
+        timeout.as_secs_f64()
+    )
+    .execute(pool)
+    .await?;
+    Ok(result.rows_affected())
+}
+
+async fn run_sender(pool: PgPool, notify: Arc<Notify>) {
+    loop {
+        match claim_next(&pool).await {
+            Ok(Some(delivery)) => {
+                // There may be more; let another sender look while this one waits on the receiver
+                notify.notify_one();
+                if let Err(e) = send(&pool, &delivery).await {
+                    eprintln!("Failed to record result of webhook delivery {}: {}", delivery.id, e);
+                }
+            }
+            Ok(None) => {
+                tokio::select! {
+                    _ = notify.notified() => {}
+                    _ = tokio::time::sleep(POLL_INTERVAL) => {}
+                }
+            }
+            Err(e) => {
+                eprintln!("Failed to claim webhook delivery: {}", e);
+                tokio::time::sleep(POLL_INTERVAL).await;
+            }
+        }
+    }
+}
+
+// `recv` fails straight away while the listener reconnects, so failures back off. The
+// senders' poll picks up deliveries queued in the meantime.
+async fn forward_notifications(mut listener: PgListener, notify: Arc<Notify>) {
+    let mut retry = LISTEN_RETRY;
+    loop {
+        match listener.recv().await {
+            Ok(_) => {
+                retry = LISTEN_RETRY;
+                notify.notify_one();
+            }
+            Err(e) => {
+                eprintln!("Lost webhook notifications, retrying in {}s: {}", retry.as_secs(), e);
+                tokio::time::sleep(retry).await;
+                retry = (retry * 2).min(POLL_INTERVAL);
+            }
+        }
+    }
+}
+
+async fn claim_next(pool: &PgPool) -> Result<Option<ClaimedDelivery>, sqlx::Error> {
+    sqlx::query_as!(
+        ClaimedDelivery,
+        r#"
+        UPDATE webhook_deliveries d
+        SET status = 'RUNNING', attempts = d.attempts + 1, locked_at = NOW(), updated_at = NOW()
+        FROM webhooks w
+        WHERE w.id = d.webhook_id
+          AND d.id = (
+              SELECT id FROM webhook_deliveries
+              WHERE status = 'QUEUED' AND run_at <= NOW()
+              ORDER BY run_at ASC
+              LIMIT 1
+              FOR UPDATE SKIP LOCKED
+          )
+        RETURNING d.id, d.event, d.payload, d.attempts, d.max_attempts, w.url, w.secret
+        "#
+    )
+    .fetch_optional(pool)
+    .await
+}
+
+async fn send(pool: &PgPool, delivery: &ClaimedDelivery) -> Result<(), sqlx::Error> {
+    let (response_status, error) = match post(delivery).await {
+        Ok(status) if status.is_success() => (Some(status.as_u16() as i32), None),
+        Ok(status) => (Some(status.as_u16() as i32), Some(format!("Receiver answered {}", status))),
+        Err(message) => (None, Some(message)),
+    };
+
+    match error {
+        None => {
+            sqlx::query!(
+                r#"
+                UPDATE webhook_deliveries
+                SET status = 'DELIVERED', locked_at = NULL, last_error = NULL, response_status = $1, updated_at = NOW()
+                WHERE id = $2
+                "#,
+                response_status,
+                delivery.id
+            )
+            .execute(pool)
+            .await?;
+        }
+        Some(message) => {
+            eprintln!(
+                "Webhook delivery {} to {} failed (attempt {}/{}): {}",
+                delivery.id, delivery.url, delivery.attempts, delivery.max_attempts, message
+            );
+            let gave_up = delivery.attempts >= delivery.max_attempts;
+            sqlx::query!(
+                r#"
+                UPDATE webhook_deliveries
+                SET status = CASE WHEN $1 THEN 'FAILED' ELSE 'QUEUED' END,
+                    locked_at = NULL, last_error = $2, response_status = $3,
+                    run_at = NOW() + make_interval(secs => $4), updated_at = NOW()
+                WHERE id = $5
+                "#,
+                gave_up,
+                message,
+                response_status,
+                backoff_secs(delivery.attempts) as f64,
+                delivery.id
+            )
+            .execute(pool)
+            .await?;
+        }
+    }
+    Ok(())
+}
+
+// The target is checked again on every attempt, since where a name points can change after
+// the webhook was registered. The connection goes to the addresses that were checked and
+// redirects are not followed, so neither DNS nor the receiver can divert it.
+async fn post(delivery: &ClaimedDelivery) -> Result<reqwest::StatusCode, String> {
+    let url = Url::parse(&delivery.url).map_err(|e| e.to_string())?;
+    let addrs = resolve_target(&url).await?;
+
+    let mut client = reqwest::Client::builder()
+        .timeout(REQUEST_TIMEOUT)
+        .redirect(reqwest::redirect::Policy::none());
+    if let Some(domain) = url.domain() {
+        client = client.resolve_to_addrs(domain, &addrs);
+    }
+    let client = client.build().map_err(|e| e.to_string())?;
+
+    let body = delivery.payload.to_string();
+    let timestamp = Utc::now().timestamp();
+    let response = client
+        .post(url)
+        .headers(signed_headers(delivery, timestamp, &body))
+        .body(body)
+        .send()
+        .await
+        .map_err(|e| e.to_string())?;
+    Ok(response.status())
+}
+
+fn signed_headers(delivery: &ClaimedDelivery, timestamp: i64, body: &str) -> reqwest::header::HeaderMap {
+    let mut headers = reqwest::header::HeaderMap::new();
+    let mut insert = |name: &'static str, value: String| {
+        if let Ok(value) = value.parse() {
+            headers.insert(name, value);
+        }
+    };
+    insert("content-type", "application/json".to_string());
+    insert("x-workflow-event", delivery.event.clone());
+    insert("x-workflow-delivery", delivery.id.to_string());
+    insert("x-workflow-timestamp", timestamp.to_string());
+    insert("x-workflow-signature", format!("sha256={}", signature(&delivery.secret, timestamp, body)));
+    headers
+}
+
+// Resolves the webhook's host and refuses it unless every address is public, so webhooks
+// can't be used to reach the server itself or the network it runs in
+async fn resolve_target(url: &Url) -> Result<Vec<SocketAddr>, String> {
+    let host = url.host_str().ok_or_else(|| "Webhook URLs need a host".to_string())?;
+    let port = url.port_or_known_default().unwrap_or(80);
+    // IPv6 literals come bracketed
+    let host = host.trim_start_matches('[').trim_end_matches(']');
+    let addrs: Vec<SocketAddr> = tokio::net::lookup_host((host, port))
+        .await
+        .map_err(|e| format!("Could not resolve {}: {}", host, e))?
+        .collect();
+
+    if addrs.is_empty() {
+        return Err(format!("Could not resolve {}", host));
+    }
+    if !private_targets_allowed() {
+        if let Some(addr) = addrs.iter().find(|addr| !is_public(addr.ip())) {
+            return Err(format!("Webhooks can't be sent to {} ({})", host, addr.ip()));
+        }
+    }
+    Ok(addrs)
+}
+
+// Loopback, private, link-local (including cloud metadata services), shared, documentation
+// and reserved ranges are all off limits
+fn is_public(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => {
+            let [a, b, ..] = ip.octets();
+            !(ip.is_unspecified()
+                || ip.is_loopback()
+                || ip.is_private()
+                || ip.is_link_local()
+                || ip.is_broadcast()
+                || ip.is_documentation()
+                || ip.is_multicast()
+                || a == 0
+                || a >= 240
+                || (a == 100 && (64..128).contains(&b))
+                || (a == 192 && b == 0)
+                || (a == 198 && (18..20).contains(&b)))
+        }
+        IpAddr::V6(ip) => match ip.to_ipv4_mapped() {
+            Some(v4) => is_public(IpAddr::V4(v4)),
+            None => {
+                !(ip.is_unspecified()
+                    || ip.is_loopback()
+                    || ip.is_multicast()
+                    || ip.is_unique_local()
+                    || ip.is_unicast_link_local()
+                    || ip.segments()[..2] == [0x2001, 0x0db8]
+                    || ip.segments()[..6] == [0; 6])
+            }
+        },
+    }
+}
+
+// Receivers recompute this over the timestamp header and the raw body; including the
+// timestamp lets them refuse replayed payloads
+fn signature(secret: &str, timestamp: i64, body: &str) -> String {
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC accepts keys of any length");
+    mac.update(format!("{}.{}", timestamp, body).as_bytes());
+    format!("{:x}", mac.finalize().into_bytes())
+}
+
+fn backoff_secs(attempts: i32) -> i64 {
+    let exponent = (attempts - 1).clamp(0, 16) as u32;
+    (BACKOFF_BASE_SECS * 2_i64.pow(exponent)).min(BACKOFF_MAX_SECS)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn delivery() -> ClaimedDelivery {
+        ClaimedDelivery {
+            id: Uuid::new_v4(),
+            event: "uploaded".to_string(),
+            payload: serde_json::json!({"event": "uploaded"}),
+            attempts: 1,
+            max_attempts: 8,
+            url: "https://hooks.example.com/workflow".to_string(),
+            secret: "whsec_test".to_string(),
+        }
+    }
+
+    #[test]
+    fn signature_is_the_hmac_of_timestamp_and_body() {
+        let delivery = delivery();
+        let body = delivery.payload.to_string();
+        let headers = signed_headers(&delivery, 1792313015, &body);
+
+        assert_eq!(body, r#"{"event":"uploaded"}"#);
+        assert_eq!(
+            headers["x-workflow-signature"],
+            "sha256=3e6ce5024c4e2c02f08d433367e00aea655b054f9232348a3bd0aef1bd61d635"
+        );
+        assert_eq!(headers["x-workflow-timestamp"], "1792313015");
+        assert_eq!(headers["x-workflow-event"], "uploaded");
+        assert_eq!(headers["x-workflow-delivery"], delivery.id.to_string().as_str());
+        assert_eq!(headers["content-type"], "application/json");
+    }
+
+    #[test]
+    fn backoff_doubles_up_to_the_cap() {
+        assert_eq!(backoff_secs(1), BACKOFF_BASE_SECS);
+        assert_eq!(backoff_secs(2), BACKOFF_BASE_SECS * 2);
+        assert_eq!(backoff_secs(4), BACKOFF_BASE_SECS * 8);
+        assert_eq!(backoff_secs(40), BACKOFF_MAX_SECS);
+    }
+
+    #[test]
+    fn internal_addresses_are_not_public() {
+        let internal = [
+            "127.0.0.1", "10.1.2.3", "172.16.0.1", "192.168.1.1", "169.254.169.254", "100.64.0.1",
+            "0.0.0.0", "255.255.255.255", "224.0.0.1", "192.0.2.1", "198.18.0.1", "240.0.0.1",
+            "::", "::1", "fd00::1", "fe80::1", "ff02::1", "2001:db8::1", "::ffff:127.0.0.1",
+            "::ffff:169.254.169.254", "::127.0.0.1",
+        ];
+        for ip in internal {
+            assert!(!is_public(ip.parse().unwrap()), "{} counted as public", ip);
+        }
+    }
+
+    #[test]
+    fn internet_addresses_are_public() {
+        for ip in ["93.184.216.34", "1.1.1.1", "172.32.0.1", "100.128.0.1", "2606:4700:4700::1111", "::ffff:8.8.8.8"] {
+            assert!(is_public(ip.parse().unwrap()), "{} counted as internal", ip);
+        }
+    }
+
+    #[tokio::test]
+    async fn targets_on_internal_addresses_are_refused() {
+        for url in ["http://127.0.0.1:8080/hook", "http://169.254.169.254/latest/meta-data", "http://[::1]/hook", "http://localhost/hook"] {
+            let url = Url::parse(url).unwrap();
+            assert!(resolve_target(&url).await.is_err(), "accepted {}", url);
+        }
+    }
+
+    #[tokio::test]
+    async fn public_address_targets_resolve_to_themselves() {
+        let url = Url::parse("https://93.184.216.34/hook").unwrap();
+        assert_eq!(resolve_target(&url).await.unwrap(), vec!["93.184.216.34:443".parse().unwrap()]);
+    }
+
+    #[tokio::test]
+    async fn requests_are_normalized_or_refused() {
+        let request = |url: &str, events: Vec<WebhookEvent>| CreateWebhookRequest { url: url.to_string(), secret: None, events };
+        let valid = validate_webhook(&request(" https://93.184.216.34/hook ", vec![WebhookEvent::Deleted, WebhookEvent::Uploaded, WebhookEvent::Deleted]))
+            .await
+            .unwrap();
+        assert_eq!(valid.url, "https://93.184.216.34/hook");
+        assert_eq!(valid.events, vec![WebhookEvent::Uploaded, WebhookEvent::Deleted]);
+
+        assert!(validate_webhook(&request("ftp://93.184.216.34/hook", vec![WebhookEvent::Uploaded])).await.is_err());
+        assert!(validate_webhook(&request("https://93.184.216.34/hook", Vec::new())).await.is_err());
+    }
+
+    #[sqlx::test]
+    async fn only_stalled_deliveries_are_requeued(pool: PgPool) {
+        let user = crate::service::accounts::NewUser { username: "alice", password: "correct horse", is_admin: false, timezone: None };
+        let owner_id = crate::service::accounts::create_user(&pool, &user).await.unwrap();
+        let webhook_id = sqlx::query_scalar!(
+            "INSERT INTO webhooks (owner_id, url, secret, events) VALUES ($1, 'https://hooks.example.com', 'whsec_test', '{uploaded}') RETURNING id",
+            owner_id
+        )
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+        let running = |locked_secs_ago: f64| {
+            sqlx::query_scalar!(
+                r#"
+                INSERT INTO webhook_deliveries (webhook_id, event, payload, max_attempts, status, attempts, locked_at)
+                VALUES ($1, 'uploaded', '{}', 8, 'RUNNING', 1, NOW() - make_interval(secs => $2))
+                RETURNING id
+                "#,
+                webhook_id,
+                locked_secs_ago
+            )
+            .fetch_one(&pool)
+        };
+        let stalled = running(STALE_AFTER.as_secs_f64() + 60.0).await.unwrap();
+        let sending = running(1.0).await.unwrap();
+
+        assert_eq!(requeue_stale(&pool, STALE_AFTER).await.unwrap(), 1);
+        let status = |id: Uuid| sqlx::query_scalar!("SELECT status FROM webhook_deliveries WHERE id = $1", id).fetch_one(&pool);
+        assert_eq!(status(stalled).await.unwrap(), "QUEUED");
+        assert_eq!(status(sending).await.unwrap(), "RUNNING");
+    }
+}
@@ -0,0 +1,148 @@
+use chrono::{DateTime, Duration, NaiveDate, NaiveTime, TimeZone, Utc};
+use leptos::*;
+pub use chrono_tz::Tz;
+
+/// Timezone used when none is configured.
+pub const DEFAULT_TIMEZONE: Tz = Tz::UTC;
+
+/// What the app provides as context so pages can read the configured timezone.
+pub type TimezoneResource = Resource<(), Result<String, ServerFnError>>;
+
+/// Parses an IANA timezone name such as `Europe/Berlin`.
+pub fn parse_timezone(name: &str) -> Option<Tz> {
+    name.trim().parse().ok()
+}
+
+/// `APP_TIMEZONE`, falling back to `TZ` and then UTC. An invalid `APP_TIMEZONE` is an error.
+#[cfg(feature = "ssr")]
+pub fn timezone_from_env() -> Result<Tz, String> {
+    match std::env::var("APP_TIMEZONE") {
+        Ok(name) if !name.trim().is_empty() => {
+            parse_timezone(&name).ok_or_else(|| format!("APP_TIMEZONE={} is not an IANA timezone name", name))
+        }
+        _ => Ok(std::env::var("TZ")
+            .ok()
+            .and_then(|name| parse_timezone(&name))
+            .unwrap_or(DEFAULT_TIMEZONE)),
+    }
+}
+
+/// The timezone that decides which day a recording belongs to. The server checks
+/// `APP_TIMEZONE` on startup, so the fallback only matters for a misconfigured CLI run.
+#[cfg(feature = "ssr")]
+pub fn app_timezone() -> Tz {
+    timezone_from_env().unwrap_or(DEFAULT_TIMEZONE)
+}
+
+/// The configured timezone for display, UTC until it has loaded.
+pub fn use_timezone() -> Signal<Tz> {
+    let resource = use_context::<TimezoneResource>();
+    Signal::derive(move || {
+        resource
+            .and_then(|r| r.get())
+            .and_then(|r| r.ok())
+            .and_then(|name| parse_timezone(&name))
+            .unwrap_or(DEFAULT_TIMEZONE)
+    })
+}
+
+/// The calendar day it currently is in `tz`.
+pub fn today(tz: Tz) -> NaiveDate {
+    Utc::now().with_timezone(&tz).date_naive()
+}
+
+/// The calendar day `time` falls on in `tz`.
+pub fn local_date(time: DateTime<Utc>, tz: Tz) -> NaiveDate {
+    time.with_timezone(&tz).date_naive()
+}
+
+/// The instant `day` begins in `tz`. Where a daylight saving change skips midnight the
+/// day begins at the first local time that exists.
+pub fn start_of_day(day: NaiveDate, tz: Tz) -> DateTime<Utc> {
+    let midnight = day.and_time(NaiveTime::MIN);
+    (0..=12)
+        .map(|quarter| midnight + Duration::minutes(15 * quarter))
+        .find_map(|local| tz.from_local_datetime(&local).earliest())
+        .map(|start| start.with_timezone(&Utc))
+        .unwrap_or_else(|| midnight.and_utc())
+}
+
+/// `time` moved to `day`, keeping its time of day in `tz`. A time of day that a daylight
+/// saving change skips on `day` is moved an hour later.
+pub fn on_day(day: NaiveDate, time: DateTime<Utc>, tz: Tz) -> DateTime<Utc> {
+    let local = day.and_time(time.with_timezone(&tz).time());
+    [local, local + Duration::hours(1)]
+        .iter()
+        .find_map(|local| tz.from_local_datetime(local).earliest())
+        .map(|moved| moved.with_timezone(&Utc))
+        .unwrap_or(time)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BERLIN: Tz = chrono_tz::Europe::Berlin;
+    // Chile moves its clocks at midnight, so some days there have no 00:00
+    const SANTIAGO: Tz = chrono_tz::America::Santiago;
+
+    fn day(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    fn utc(rfc3339: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(rfc3339).unwrap().with_timezone(&Utc)
+    }
+
+    #[test]
+    fn parses_iana_names() {
+        assert_eq!(parse_timezone(" Europe/Berlin "), Some(BERLIN));
+        assert_eq!(parse_timezone("Mars/Olympus"), None);
+    }
+
+    #[test]
+    fn local_date_changes_at_local_midnight() {
+        assert_eq!(local_date(utc("2026-10-18T21:59:59Z"), BERLIN), day(2026, 10, 18));
+        assert_eq!(local_date(utc("2026-10-18T22:00:00Z"), BERLIN), day(2026, 10, 19));
+        assert_eq!(local_date(utc("2026-12-01T22:59:59Z"), BERLIN), day(2026, 12, 1));
+        assert_eq!(local_date(utc("2026-12-01T23:00:00Z"), BERLIN), day(2026, 12, 2));
+    }
+
+    #[test]
+    fn days_around_daylight_saving_changes_have_23_and_25_hours() {
+        let spring = start_of_day(day(2026, 3, 30), BERLIN) - start_of_day(day(2026, 3, 29), BERLIN);
+        assert_eq!(spring, Duration::hours(23));
+        let autumn = start_of_day(day(2026, 10, 26), BERLIN) - start_of_day(day(2026, 10, 25), BERLIN);
+        assert_eq!(autumn, Duration::hours(25));
+        assert_eq!(start_of_day(day(2026, 10, 25), BERLIN), utc("2026-10-24T22:00:00Z"));
+    }
+
+    #[test]
+    fn a_day_without_midnight_starts_at_its_first_local_time() {
+        // 2024-09-08 went from 23:59:59 straight to 01:00
+        assert_eq!(start_of_day(day(2024, 9, 8), SANTIAGO), utc("2024-09-08T01:00:00-03:00"));
+        assert_eq!(local_date(start_of_day(day(2024, 9, 8), SANTIAGO), SANTIAGO), day(2024, 9, 8));
+    }
+
+    #[test]
+    fn on_day_keeps_the_local_time_across_offsets() {
+        // 09:30 in Berlin summer time stays 09:30 in winter
+        let moved = on_day(day(2026, 12, 1), utc("2026-10-18T07:30:00Z"), BERLIN);
+        assert_eq!(moved, utc("2026-12-01T08:30:00Z"));
+    }
+
+    #[test]
+    fn on_day_moves_skipped_times_an_hour_later() {
+        // 02:30 does not exist in Berlin on 2026-03-29
+        let moved = on_day(day(2026, 3, 29), utc("2026-03-28T01:30:00Z"), BERLIN);
+        assert_eq!(moved, utc("2026-03-29T03:30:00+02:00"));
+        assert_eq!(local_date(moved, BERLIN), day(2026, 3, 29));
+    }
+
+    #[test]
+    fn on_day_takes_the_first_of_repeated_times() {
+        // 02:30 happens twice in Berlin on 2026-10-25
+        let moved = on_day(day(2026, 10, 25), utc("2026-10-24T00:30:00Z"), BERLIN);
+        assert_eq!(moved, utc("2026-10-25T02:30:00+02:00"));
+    }
+}